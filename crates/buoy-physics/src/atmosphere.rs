@@ -1,5 +1,13 @@
 //! Atmosphere model based on the US Standard Atmosphere, 1976.
 //!
+//! The atmosphere can also be backed by a gridded [`FluidGrid`], in which case
+//! every property, including wind, is looked up from the grid. Atmospheres of
+//! other planetary bodies come from [`crate::planet::PlanetaryBody`]. Water
+//! vapor is described by a [`Humidity`] profile and lowers the density of the
+//! air through its virtual temperature. Every lookup takes the [`Epoch`] it is
+//! evaluated at, so a [`Climate`] can add daily and seasonal variation.
+//! Gridded atmospheres are a single snapshot and ignore the epoch.
+//!
 //! Reference:
 //! - https://apps.dtic.mil/dtic/tr/fulltext/u2/a035728.pdf
 //! - https://www.translatorscafe.com/unit-converter/en-US/calculator/altitude
@@ -7,62 +15,214 @@
 
 use avian3d::prelude::{Position, RigidBody};
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use uom::si::{
     f32::*,
+    mass_density::kilogram_per_cubic_meter,
     thermodynamic_temperature::{degree_celsius, kelvin},
-    pressure::kilopascal,
+    pressure::{kilopascal, pascal},
 };
 
 use crate::{
+    climate::Climate,
+    clock::Epoch,
     ideal_gas::{ideal_gas_density, GasSpecies},
     constants::{STANDARD_TEMPERATURE, STANDARD_PRESSURE},
+    fluid_volume::FluidGrid,
+    humidity::{dewpoint, saturation_vapor_pressure, virtual_temperature, Humidity},
+    planet::{mars_pressure, mars_temperature},
+    units,
+    water::Medium,
 };
 
 pub(crate) fn plugin(app: &mut App) {
-    app.insert_resource(Atmosphere);
+    app.init_resource::<Atmosphere>();
 }
 
-/// US Standard Atmosphere, 1976
+/// Ambient atmosphere, US Standard Atmosphere, 1976 by default.
 #[derive(Resource)]
-pub struct Atmosphere;
+pub struct Atmosphere {
+    pub model: AtmosphereModel,
+    /// Bulk gas the atmosphere is made of, used to compute its density.
+    pub composition: GasSpecies,
+    /// Water vapor content. Ignored by gridded atmospheres, whose density
+    /// already accounts for it.
+    pub humidity: Humidity,
+    /// Daily and yearly temperature variation. Ignored by gridded
+    /// atmospheres.
+    pub climate: Option<Climate>,
+}
+
+impl Default for Atmosphere {
+    fn default() -> Self {
+        Atmosphere {
+            model: AtmosphereModel::default(),
+            composition: GasSpecies::air(),
+            humidity: Humidity::default(),
+            climate: None,
+        }
+    }
+}
+
+/// Where the atmosphere's properties come from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum AtmosphereModel {
+    /// US Standard Atmosphere, 1976. Still air.
+    #[default]
+    UsStandard1976,
+    /// Properties interpolated from a gridded fluid volume.
+    Grid(FluidGrid),
+    /// NASA Glenn curve fit of the Martian atmosphere. Still air.
+    Mars,
+    /// Temperature and pressure interpolated from a vertical profile sorted
+    /// by altitude. Still air.
+    Profile(Vec<ProfilePoint>),
+}
+
+/// One level of a vertical atmosphere profile.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct ProfilePoint {
+    /// Altitude (m) above the body's mean radius.
+    #[serde(deserialize_with = "units::length")]
+    pub altitude: f32,
+    /// Temperature (K).
+    #[serde(deserialize_with = "units::temperature")]
+    pub temperature: f32,
+    /// Pressure (Pa).
+    #[serde(deserialize_with = "units::pressure")]
+    pub pressure: f32,
+}
 
 impl Atmosphere {
     pub const MAX_ALTITUDE: f32 = 84999.0; // small margin to avoid panics
     pub const MIN_ALTITUDE: f32 = -56.0; // small margin to avoid panics
 
-    /// Temperature (K) of the atmosphere at a position.
-    pub fn temperature(&self, position: Vec3) -> ThermodynamicTemperature {
-        // TODO: Look up temperature based on latitude, longitude, not just altitude
-        coesa_temperature(position.y).unwrap_or_else(|e| {
-            error!("Atmosphere temperature out of bounds: {}", e);
-            STANDARD_TEMPERATURE.clone()
-        }) // we should handle this better
+    /// An atmosphere backed by a gridded fluid volume.
+    pub fn from_grid(grid: FluidGrid) -> Self {
+        Atmosphere {
+            model: AtmosphereModel::Grid(grid),
+            ..default()
+        }
     }
 
-    /// Pressure (Pa) of the atmosphere at a position.
-    pub fn pressure(&self, position: Vec3) -> Pressure {
-        // TODO: Look up pressure based on latitude, longitude, not just altitude
-        coesa_pressure(position.y).unwrap_or_else(|e| {
-            error!("Atmosphere pressure out of bounds: {}", e);
-            STANDARD_PRESSURE.clone()
-        }) // we should handle this better
+    /// Temperature (K) of the atmosphere at a position and time.
+    pub fn temperature(&self, position: Vec3, epoch: Epoch) -> ThermodynamicTemperature {
+        let base = self.base_temperature(position);
+        match (&self.model, &self.climate) {
+            (AtmosphereModel::Grid(_), _) | (_, None) => base,
+            (_, Some(climate)) => ThermodynamicTemperature::new::<kelvin>(
+                base.get::<kelvin>() + climate.temperature_offset(position.y, epoch),
+            ),
+        }
     }
 
-    /// Density (kg/m³) of the atmosphere at a position.
-    pub fn density(&self, position: Vec3) -> MassDensity {
-        ideal_gas_density(
-            self.temperature(position),
-            self.pressure(position),
-            &GasSpecies::air(),
-        )
+    /// Temperature (K) of the underlying model, before any climate offsets.
+    fn base_temperature(&self, position: Vec3) -> ThermodynamicTemperature {
+        match &self.model {
+            // TODO: Look up temperature based on latitude, longitude, not just altitude
+            AtmosphereModel::UsStandard1976 => coesa_temperature(position.y).unwrap_or_else(|e| {
+                error!("Atmosphere temperature out of bounds: {}", e);
+                *STANDARD_TEMPERATURE
+            }), // we should handle this better
+            AtmosphereModel::Grid(grid) => {
+                ThermodynamicTemperature::new::<kelvin>(grid.sample(position).temperature)
+            }
+            AtmosphereModel::Mars => mars_temperature(position.y),
+            AtmosphereModel::Profile(profile) => {
+                ThermodynamicTemperature::new::<kelvin>(profile_temperature(profile, position.y))
+            }
+        }
+    }
+
+    /// Pressure (Pa) of the atmosphere at a position and time.
+    pub fn pressure(&self, position: Vec3, _epoch: Epoch) -> Pressure {
+        match &self.model {
+            // TODO: Look up pressure based on latitude, longitude, not just altitude
+            AtmosphereModel::UsStandard1976 => coesa_pressure(position.y).unwrap_or_else(|e| {
+                error!("Atmosphere pressure out of bounds: {}", e);
+                *STANDARD_PRESSURE
+            }), // we should handle this better
+            AtmosphereModel::Grid(grid) => Pressure::new::<pascal>(grid.sample(position).pressure),
+            AtmosphereModel::Mars => mars_pressure(position.y),
+            AtmosphereModel::Profile(profile) => {
+                Pressure::new::<pascal>(profile_pressure(profile, position.y))
+            }
+        }
+    }
+
+    /// Density (kg/m³) of the atmosphere at a position and time.
+    pub fn density(&self, position: Vec3, epoch: Epoch) -> MassDensity {
+        match &self.model {
+            AtmosphereModel::Grid(grid) => {
+                MassDensity::new::<kilogram_per_cubic_meter>(grid.sample(position).density)
+            }
+            _ => ideal_gas_density(
+                self.virtual_temperature(position, epoch),
+                self.pressure(position, epoch),
+                &self.composition,
+            ),
+        }
+    }
+
+    /// Partial pressure (Pa) of water vapor at a position.
+    pub fn vapor_pressure(&self, position: Vec3, epoch: Epoch) -> Pressure {
+        self.humidity.vapor_pressure(position.y, self.temperature(position, epoch))
+    }
+
+    /// Relative humidity (0–1) with respect to liquid water at a position.
+    pub fn relative_humidity(&self, position: Vec3, epoch: Epoch) -> f32 {
+        let saturation = saturation_vapor_pressure(self.temperature(position, epoch));
+        (self.vapor_pressure(position, epoch) / saturation).value
+    }
+
+    /// Dewpoint (K) at a position. Equal to the temperature when saturated,
+    /// and `None` where the air holds no water vapor, which has no dewpoint.
+    pub fn dewpoint(&self, position: Vec3, epoch: Epoch) -> Option<ThermodynamicTemperature> {
+        if matches!(self.humidity, Humidity::Dry) {
+            return None;
+        }
+        let vapor_pressure = self.vapor_pressure(position, epoch);
+        (vapor_pressure.get::<pascal>() > 0.0).then(|| dewpoint(vapor_pressure))
+    }
+
+    /// Virtual temperature (K) at a position, the temperature dry air would
+    /// need to have the same density as the moist air there.
+    pub fn virtual_temperature(&self, position: Vec3, epoch: Epoch) -> ThermodynamicTemperature {
+        let temperature = self.temperature(position, epoch);
+        match self.humidity {
+            Humidity::Dry => temperature,
+            _ => virtual_temperature(
+                temperature,
+                self.pressure(position, epoch),
+                self.vapor_pressure(position, epoch),
+                &self.composition,
+            ),
+        }
+    }
+
+    /// Velocity (m/s) of the ambient fluid at a position and time, in world
+    /// space.
+    pub fn wind(&self, position: Vec3, _epoch: Epoch) -> Vec3 {
+        match &self.model {
+            AtmosphereModel::Grid(grid) => grid.sample(position).velocity,
+            _ => Vec3::ZERO,
+        }
+    }
+
+    /// The medium at a position. Only gridded atmospheres contain water.
+    pub fn medium(&self, position: Vec3, _epoch: Epoch) -> Medium {
+        match &self.model {
+            AtmosphereModel::Grid(grid) => grid.sample(position).medium,
+            _ => Medium::Air,
+        }
     }
 
     pub fn standard_temperature() -> ThermodynamicTemperature {
-        STANDARD_TEMPERATURE.clone()
+        *STANDARD_TEMPERATURE
     }
 
     pub fn standard_pressure() -> Pressure {
-        STANDARD_PRESSURE.clone()
+        *STANDARD_PRESSURE
     }
 
     pub fn standard_density() -> MassDensity {
@@ -74,6 +234,49 @@ impl Atmosphere {
     }
 }
 
+/// Bracketing levels of a profile and the interpolation factor between them.
+/// Altitudes outside the profile clamp to the nearest pair of levels.
+fn profile_levels(profile: &[ProfilePoint], altitude: f32) -> (ProfilePoint, ProfilePoint, f32) {
+    if profile.len() == 1 {
+        return (profile[0], profile[0], 0.0);
+    }
+    let upper = profile
+        .partition_point(|point| point.altitude <= altitude)
+        .clamp(1, profile.len() - 1);
+    let (a, b) = (profile[upper - 1], profile[upper]);
+    let span = b.altitude - a.altitude;
+    let t = if span > f32::EPSILON {
+        (altitude - a.altitude) / span
+    } else {
+        0.0
+    };
+    (a, b, t)
+}
+
+/// Temperature (K) linearly interpolated from a profile, held constant beyond
+/// its ends.
+fn profile_temperature(profile: &[ProfilePoint], altitude: f32) -> f32 {
+    if profile.is_empty() {
+        return STANDARD_TEMPERATURE.get::<kelvin>();
+    }
+    let (a, b, t) = profile_levels(profile, altitude);
+    a.temperature + (b.temperature - a.temperature) * t.clamp(0.0, 1.0)
+}
+
+/// Pressure (Pa) interpolated log-linearly from a profile. Beyond its ends the
+/// nearest scale height is extrapolated, so pressure keeps falling with
+/// altitude.
+fn profile_pressure(profile: &[ProfilePoint], altitude: f32) -> f32 {
+    if profile.is_empty() {
+        return STANDARD_PRESSURE.get::<pascal>();
+    }
+    let (a, b, t) = profile_levels(profile, altitude);
+    if a.pressure <= 0.0 || b.pressure <= 0.0 {
+        return a.pressure + (b.pressure - a.pressure) * t.clamp(0.0, 1.0);
+    }
+    (a.pressure.ln() + (b.pressure.ln() - a.pressure.ln()) * t).exp()
+}
+
 #[derive(Debug)]
 enum AtmosphereError {
     #[allow(dead_code)]
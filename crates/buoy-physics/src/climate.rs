@@ -0,0 +1,132 @@
+//! Diurnal, seasonal and latitudinal variation of the lower atmosphere.
+//!
+//! The standard atmosphere is a yearly, mid-latitude average. [`Climate`]
+//! offsets its temperature for a particular place and time:
+//!
+//! - A daily swing near the ground that peaks in the mid afternoon and fades
+//!   out at the top of the boundary layer, which deepens during the day.
+//! - A yearly swing that grows toward the poles and is opposite in the two
+//!   hemispheres.
+//! - A warm equator and cold poles relative to 45° latitude.
+//!
+//! The seasonal and latitudinal offsets fade out at the tropopause. Pressure
+//! keeps following the standard profile, so the offsets show up in density.
+//!
+//! Reference:
+//! - https://en.wikipedia.org/wiki/Diurnal_temperature_variation
+//! - Stull (1988), An Introduction to Boundary Layer Meteorology
+
+use std::f32::consts::{PI, TAU};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{clock::Epoch, units};
+
+/// Mean days in a Gregorian year.
+const DAYS_PER_YEAR: f32 = 365.2425;
+
+/// Day of year (from January 1st) of the warmest day in the northern
+/// hemisphere, mid July.
+const WARMEST_DAY_OF_YEAR: f32 = 196.0;
+
+/// Local solar hour of the warmest time of day.
+const WARMEST_HOUR: f32 = 15.0;
+
+/// Altitude (m) above which seasonal and latitudinal offsets vanish.
+const TROPOPAUSE: f32 = 11000.0;
+
+/// Where on the planet the atmosphere is sampled, and how strongly it varies
+/// over the day and the year there. Fields left out of a file keep their
+/// defaults.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Climate {
+    /// Latitude (°), positive north. Taken from the launch site.
+    #[serde(skip)]
+    pub latitude: f32,
+    /// Longitude (°), positive east. Sets local solar time. Taken from the
+    /// launch site.
+    #[serde(skip)]
+    pub longitude: f32,
+    /// Half the daily swing (K) of the surface temperature.
+    #[serde(deserialize_with = "units::temperature_difference")]
+    pub diurnal_amplitude: f32,
+    /// Half the yearly swing (K) of the surface temperature at the poles.
+    #[serde(deserialize_with = "units::temperature_difference")]
+    pub seasonal_amplitude: f32,
+    /// Difference (K) between the yearly mean temperatures of the equator
+    /// and the poles.
+    #[serde(deserialize_with = "units::temperature_difference")]
+    pub equator_to_pole_difference: f32,
+    /// Height (m) of the mixed layer around midday.
+    #[serde(deserialize_with = "units::length")]
+    pub daytime_boundary_layer_height: f32,
+    /// Height (m) of the stable layer at night.
+    #[serde(deserialize_with = "units::length")]
+    pub nighttime_boundary_layer_height: f32,
+    /// Altitude (m) of the ground, which the boundary layer sits on.
+    #[serde(skip)]
+    pub ground_altitude: f32,
+}
+
+impl Default for Climate {
+    fn default() -> Self {
+        Climate {
+            latitude: 45.0,
+            longitude: 0.0,
+            diurnal_amplitude: 5.0,
+            seasonal_amplitude: 15.0,
+            equator_to_pole_difference: 40.0,
+            daytime_boundary_layer_height: 1500.0,
+            nighttime_boundary_layer_height: 200.0,
+            ground_altitude: 0.0,
+        }
+    }
+}
+
+impl Climate {
+    /// Mean local solar time (hours, 0–24).
+    pub fn local_solar_hours(&self, epoch: Epoch) -> f32 {
+        epoch.local_solar_hours(self.longitude) as f32
+    }
+
+    /// Height (m) of the atmospheric boundary layer. It grows after sunrise,
+    /// peaks at noon and collapses to its nighttime height at sunset.
+    pub fn boundary_layer_height(&self, epoch: Epoch) -> f32 {
+        let hours = self.local_solar_hours(epoch);
+        let daylight = (PI * (hours - 6.0) / 12.0).sin().max(0.0);
+        self.nighttime_boundary_layer_height
+            + (self.daytime_boundary_layer_height - self.nighttime_boundary_layer_height) * daylight
+    }
+
+    /// Daily temperature offset (K) at an altitude (m). It fades out at the
+    /// top of the boundary layer, which is shallow at night and is measured
+    /// from the ground rather than from sea level.
+    pub fn diurnal_offset(&self, altitude: f32, epoch: Epoch) -> f32 {
+        let hours = self.local_solar_hours(epoch);
+        let height = self.boundary_layer_height(epoch);
+        let weight = if height > 0.0 {
+            (1.0 - (altitude - self.ground_altitude) / height).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        self.diurnal_amplitude * (TAU * (hours - WARMEST_HOUR) / 24.0).cos() * weight
+    }
+
+    /// Seasonal and latitudinal temperature offset (K) at an altitude (m).
+    pub fn seasonal_offset(&self, altitude: f32, epoch: Epoch) -> f32 {
+        let latitude = self.latitude.to_radians();
+        let day = epoch.day_of_year() as f32;
+        let seasonal = self.seasonal_amplitude
+            * latitude.sin()
+            * (TAU * (day - WARMEST_DAY_OF_YEAR) / DAYS_PER_YEAR).cos();
+        let latitudinal = self.equator_to_pole_difference * (0.5 - latitude.sin().powi(2));
+        let weight = (1.0 - altitude / TROPOPAUSE).clamp(0.0, 1.0);
+        (seasonal + latitudinal) * weight
+    }
+
+    /// Total temperature offset (K) from the standard profile.
+    pub fn temperature_offset(&self, altitude: f32, epoch: Epoch) -> f32 {
+        self.diurnal_offset(altitude, epoch) + self.seasonal_offset(altitude, epoch)
+    }
+}
@@ -0,0 +1,375 @@
+//! Absolute time.
+//!
+//! Time-dependent models such as the diurnal cycle of the atmosphere look up
+//! their state by [`Epoch`], an instant in UTC. Leap seconds are ignored, so
+//! every day is exactly 86400 seconds long.
+//!
+//! The [`SimulationClock`] is the single source of truth for the current
+//! epoch. It starts at a configurable UTC epoch and advances in lockstep with
+//! physics time, so it stops when physics is paused and runs as fast as the
+//! simulation does.
+//!
+//! Reference:
+//! - https://howardhinnant.github.io/date_algorithms.html
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use avian3d::prelude::Physics;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub(crate) fn plugin(app: &mut App) {
+    app.init_resource::<SimulationClock>();
+    app.add_systems(FixedFirst, advance_clock);
+}
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// Absolute simulation time.
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Reflect)]
+pub struct SimulationClock {
+    /// UTC epoch at the start of the simulation.
+    pub start: Epoch,
+    /// Simulated time since the start.
+    pub elapsed: Duration,
+}
+
+impl SimulationClock {
+    pub fn new(start: Epoch) -> Self {
+        SimulationClock {
+            start,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// The current UTC epoch.
+    pub fn now(&self) -> Epoch {
+        self.start.plus_seconds(self.elapsed.as_secs_f64())
+    }
+
+    /// Simulated seconds since the start.
+    pub fn elapsed_secs(&self) -> f64 {
+        self.elapsed.as_secs_f64()
+    }
+}
+
+/// Keep the clock in step with physics time.
+fn advance_clock(mut clock: ResMut<SimulationClock>, physics_time: Res<Time<Physics>>) {
+    clock.elapsed = physics_time.elapsed();
+}
+
+/// An instant in UTC, stored as seconds since 1970-01-01T00:00:00Z.
+///
+/// Epochs are written as ISO 8601 strings such as `2025-07-26T14:30:00Z` in
+/// config files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Reflect, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Epoch {
+    unix_seconds: f64,
+}
+
+/// Calendar date and time of day in UTC.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UtcDateTime {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: f64,
+}
+
+impl Epoch {
+    pub fn from_unix_seconds(unix_seconds: f64) -> Self {
+        Epoch { unix_seconds }
+    }
+
+    /// An epoch from a calendar date and time of day in UTC.
+    pub fn from_utc(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: f64) -> Self {
+        let days = days_from_civil(year as i64, month, day) as f64;
+        Epoch {
+            unix_seconds: days * SECONDS_PER_DAY
+                + hour as f64 * 3600.0
+                + minute as f64 * 60.0
+                + second,
+        }
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z.
+    pub fn unix_seconds(&self) -> f64 {
+        self.unix_seconds
+    }
+
+    /// The epoch a number of seconds later.
+    pub fn plus_seconds(self, seconds: f64) -> Self {
+        Epoch {
+            unix_seconds: self.unix_seconds + seconds,
+        }
+    }
+
+    /// Calendar date and time of day in UTC.
+    pub fn to_utc(&self) -> UtcDateTime {
+        let days = (self.unix_seconds / SECONDS_PER_DAY).floor();
+        let (year, month, day) = civil_from_days(days as i64);
+        let seconds_of_day = self.unix_seconds - days * SECONDS_PER_DAY;
+        let hour = (seconds_of_day / 3600.0).floor();
+        let minute = ((seconds_of_day - hour * 3600.0) / 60.0).floor();
+        UtcDateTime {
+            year: year as i32,
+            month,
+            day,
+            hour: hour as u32,
+            minute: minute as u32,
+            second: seconds_of_day - hour * 3600.0 - minute * 60.0,
+        }
+    }
+
+    /// Hours (0–24) since midnight UTC.
+    pub fn utc_hours(&self) -> f64 {
+        self.unix_seconds.rem_euclid(SECONDS_PER_DAY) / 3600.0
+    }
+
+    /// Fractional days since January 1st, 00:00 UTC of the same year.
+    pub fn day_of_year(&self) -> f64 {
+        let year = self.to_utc().year;
+        let start = days_from_civil(year as i64, 1, 1) as f64 * SECONDS_PER_DAY;
+        (self.unix_seconds - start) / SECONDS_PER_DAY
+    }
+
+    /// Mean local solar time (hours, 0–24) at a longitude (°, positive east).
+    pub fn local_solar_hours(&self, longitude: f32) -> f64 {
+        (self.utc_hours() + longitude as f64 / 15.0).rem_euclid(24.0)
+    }
+}
+
+impl fmt::Display for Epoch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Round to the millisecond first so 59.9996 s never prints as 60.
+        let utc = Epoch::from_unix_seconds((self.unix_seconds * 1000.0).round() / 1000.0).to_utc();
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:",
+            utc.year, utc.month, utc.day, utc.hour, utc.minute
+        )?;
+        if utc.second.fract() == 0.0 {
+            write!(f, "{:02}Z", utc.second as u32)
+        } else {
+            write!(f, "{:06.3}Z", utc.second)
+        }
+    }
+}
+
+/// Why a string could not be read as an [`Epoch`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpochParseError(pub String);
+
+impl fmt::Display for EpochParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid UTC epoch `{}`, expected YYYY-MM-DDTHH:MM:SSZ", self.0)
+    }
+}
+
+impl std::error::Error for EpochParseError {}
+
+impl FromStr for Epoch {
+    type Err = EpochParseError;
+
+    /// Reads an ISO 8601 date, optionally followed by a time of day in UTC,
+    /// e.g. `2025-07-26`, `2025-07-26T14:30:00Z` or `2025-07-26 14:30:00.5`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || EpochParseError(s.to_string());
+        let trimmed = s.trim();
+        let trimmed = trimmed
+            .strip_suffix('Z')
+            .or_else(|| trimmed.strip_suffix("+00:00"))
+            .unwrap_or(trimmed);
+        let (date, time) = match trimmed.split_once(['T', ' ']) {
+            Some((date, time)) => (date, Some(time)),
+            None => (trimmed, None),
+        };
+
+        let mut date_fields = date.splitn(3, '-');
+        let mut next_date = || date_fields.next().ok_or_else(error);
+        let year: i32 = next_date()?.parse().map_err(|_| error())?;
+        let month: u32 = next_date()?.parse().map_err(|_| error())?;
+        let day: u32 = next_date()?.parse().map_err(|_| error())?;
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) {
+            return Err(error());
+        }
+
+        let (hour, minute, second) = match time {
+            Some(time) => {
+                let mut time_fields = time.splitn(3, ':');
+                let hour: u32 = time_fields.next().ok_or_else(error)?.parse().map_err(|_| error())?;
+                let minute: u32 = time_fields.next().ok_or_else(error)?.parse().map_err(|_| error())?;
+                let second: f64 = match time_fields.next() {
+                    Some(second) => second.parse().map_err(|_| error())?,
+                    None => 0.0,
+                };
+                if hour > 23 || minute > 59 || !(0.0..60.0).contains(&second) {
+                    return Err(error());
+                }
+                (hour, minute, second)
+            }
+            None => (0, 0, 0.0),
+        };
+        Ok(Epoch::from_utc(year, month, day, hour, minute, second))
+    }
+}
+
+impl TryFrom<String> for Epoch {
+    type Error = EpochParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Epoch> for String {
+    fn from(epoch: Epoch) -> Self {
+        epoch.to_string()
+    }
+}
+
+fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_from_march = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Proleptic Gregorian date of a number of days since 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_days_match_known_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(-719468), (0, 3, 1));
+    }
+
+    #[test]
+    fn civil_days_round_trip() {
+        // Every day from 1600 to 2400, across leap centuries and negative
+        // day counts.
+        let first = days_from_civil(1600, 1, 1);
+        let last = days_from_civil(2400, 12, 31);
+        let mut expected = (1600, 1, 1);
+        for days in first..=last {
+            let date = civil_from_days(days);
+            assert_eq!(date, expected, "day {days}");
+            assert_eq!(days_from_civil(date.0, date.1, date.2), days);
+            let (year, month, day) = expected;
+            expected = if day < days_in_month(year as i32, month) {
+                (year, month, day + 1)
+            } else if month < 12 {
+                (year, month + 1, 1)
+            } else {
+                (year + 1, 1, 1)
+            };
+        }
+    }
+
+    #[test]
+    fn leap_years_follow_the_gregorian_rule() {
+        assert!(is_leap_year(2024));
+        assert!(is_leap_year(2000));
+        assert!(!is_leap_year(1900));
+        assert!(!is_leap_year(2025));
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2100, 2), 28);
+    }
+
+    #[test]
+    fn parses_dates_and_times() {
+        let epoch: Epoch = "2025-07-26T14:30:00Z".parse().unwrap();
+        assert_eq!(epoch.unix_seconds(), 1_753_540_200.0);
+        assert_eq!("2025-07-26 14:30:00.5".parse::<Epoch>().unwrap().unix_seconds(), 1_753_540_200.5);
+        assert_eq!("2025-07-26T14:30+00:00".parse::<Epoch>().unwrap(), epoch);
+        assert_eq!("2025-07-26".parse::<Epoch>().unwrap(), Epoch::from_utc(2025, 7, 26, 0, 0, 0.0));
+        assert_eq!("1969-12-31T23:59:59Z".parse::<Epoch>().unwrap().unix_seconds(), -1.0);
+    }
+
+    #[test]
+    fn rejects_impossible_dates_and_times() {
+        for text in [
+            "2025-02-29",
+            "2024-02-30",
+            "2025-13-01",
+            "2025-00-10",
+            "2025-07-26T24:00:00Z",
+            "2025-07-26T12:60:00Z",
+            "2025-07-26T12:00:60Z",
+            "2025-07",
+            "tomorrow",
+        ] {
+            assert_eq!(text.parse::<Epoch>(), Err(EpochParseError(text.to_string())), "{text}");
+        }
+        assert!("2024-02-29".parse::<Epoch>().is_ok());
+    }
+
+    #[test]
+    fn displays_and_parses_back() {
+        for text in ["2025-07-26T14:30:00Z", "2000-02-29T23:59:59.250Z", "1950-01-01T00:00:00Z"] {
+            let epoch: Epoch = text.parse().unwrap();
+            assert_eq!(epoch.to_string(), text);
+        }
+        // Rounding to the millisecond carries into the next minute.
+        let epoch = Epoch::from_utc(2025, 12, 31, 23, 59, 59.9996);
+        assert_eq!(epoch.to_string(), "2026-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn utc_calendar_and_solar_time() {
+        let epoch = Epoch::from_utc(2024, 3, 1, 6, 15, 30.0);
+        let utc = epoch.to_utc();
+        assert_eq!((utc.year, utc.month, utc.day, utc.hour, utc.minute), (2024, 3, 1, 6, 15));
+        assert_eq!(utc.second, 30.0);
+        assert_eq!(epoch.utc_hours(), 6.258333333333334);
+        // January has 31 days and February 29 in 2024.
+        assert!((epoch.day_of_year() - (60.0 + 6.258333333333334 / 24.0)).abs() < 1e-9);
+        // 90° west is six hours behind UTC.
+        assert!((epoch.local_solar_hours(-90.0) - 0.258333333333334).abs() < 1e-9);
+        assert!((epoch.local_solar_hours(-105.0) - 23.258333333333334).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,960 @@
+//! Deformable balloon envelope.
+//!
+//! The envelope is a closed triangle mesh whose vertices are point masses and
+//! whose edges are springs, following
+//! `docs/Deformable Balloon Envelope Simulation Design.pdf`. Every fixed step:
+//!
+//! 1. The enclosed volume sets the lift gas pressure through the ideal gas
+//!    law.
+//! 2. The pressure difference across the skin pushes on each face along its
+//!    outward normal, shared equally among its vertices. The difference grows
+//!    toward the top of the envelope because the lift gas is lighter than the
+//!    air around it.
+//! 3. Edges resist stretching with a stiffness derived from the skin's
+//!    elastic modulus and thickness. The skin is a membrane, so edges carry
+//!    no compression.
+//! 4. Pairs of faces sharing an edge resist folding about it with the skin's
+//!    flexural rigidity, so slack fabric creases instead of crumpling at
+//!    every vertex.
+//! 5. When [`SelfCollision`] is enabled, vertices are kept a small distance
+//!    away from faces they do not belong to, so a partially inflated envelope
+//!    folds onto itself instead of passing through itself.
+//! 6. Gravity pulls on the envelope mass.
+//!
+//! Vertices are integrated in the rigid body's local frame, which carries the
+//! bulk motion of the balloon. The anchor vertices at the neck, where the
+//! payload hangs, are held at their rest positions in that frame. The load
+//! they carry is not passed back to the body, and rotation of the body frame
+//! is not accounted for.
+//!
+//! The deformed shape feeds back into the rigid body as its
+//! [`DisplacedVolume`] and, through a convex hull collider, the silhouette used
+//! for drag.
+
+use std::collections::HashMap;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use uom::si::{
+    acceleration::meter_per_second_squared,
+    f32::{Length, Pressure, Volume},
+    length::meter,
+    mass_density::kilogram_per_cubic_meter, pressure::pascal, thermodynamic_temperature::kelvin,
+    volume::cubic_meter,
+};
+
+use crate::{
+    atmosphere::Atmosphere,
+    clock::SimulationClock,
+    forces::{net_force, DisplacedVolume},
+    geometry::{collider_volume, trimesh_volume},
+    ideal_gas::{ideal_gas_pressure, IdealGas},
+    material_properties::Skin,
+    mesh_utils::{MeshHandle, RestState},
+    planet::PlanetaryBody,
+};
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (simulate_envelopes, update_envelope_meshes)
+            .chain()
+            .before(net_force)
+            .in_set(PhysicsStepSet::First),
+    );
+}
+
+/// Default most substeps an envelope takes in one fixed step. A skin
+/// stiffened by the cold needs more substeps, in proportion to the rise in
+/// its natural frequency, and this bounds what it may cost. Past the limit
+/// the integration is unstable, and a warning is logged once.
+pub const MAX_SUBSTEPS: usize = 1024;
+
+/// An edge of the envelope mesh acting as a tension-only spring.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spring {
+    pub a: u32,
+    pub b: u32,
+    /// Length (m) of the edge in the rest state.
+    pub rest_length: f32,
+    /// Spring constant (N/m).
+    pub stiffness: f32,
+}
+
+impl Spring {
+    /// Engineering strain of the spring given its current length (m).
+    pub fn strain(&self, length: f32) -> f32 {
+        (length - self.rest_length) / self.rest_length
+    }
+}
+
+/// Two faces sharing an edge, which resist folding about it.
+///
+/// The fold is measured by the sine of half the dihedral angle between the
+/// faces, following Bridson, Marino and Fedkiw, "Simulation of Clothing with
+/// Folds and Wrinkles" (2003).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hinge {
+    /// The vertex of each face opposite the shared edge.
+    pub wings: [u32; 2],
+    /// Ends of the shared edge, in the winding order of the first face.
+    pub edge: [u32; 2],
+    /// Fold of the hinge in the rest state.
+    pub rest_fold: f32,
+}
+
+impl Hinge {
+    /// The wings followed by the ends of the edge.
+    pub fn vertices(&self) -> [u32; 4] {
+        [self.wings[0], self.wings[1], self.edge[0], self.edge[1]]
+    }
+
+    /// Fold of the hinge given the vertex positions (m): the sine of half the
+    /// dihedral angle, positive when the faces bend outward.
+    pub fn fold(&self, positions: &[Vec3]) -> Option<f32> {
+        self.gradient(positions).map(|(fold, ..)| fold)
+    }
+
+    /// The fold, the force scale and the direction each of the four vertices
+    /// moves to bend the faces inward.
+    fn gradient(&self, positions: &[Vec3]) -> Option<(f32, f32, [Vec3; 4])> {
+        let [x1, x2, x3, x4] = self.vertices().map(|vertex| positions[vertex as usize]);
+        let e = x4 - x3;
+        let length = e.length();
+        let n1 = (x1 - x3).cross(x1 - x4);
+        let n2 = (x2 - x4).cross(x2 - x3);
+        let (area1, area2) = (n1.length_squared(), n2.length_squared());
+        if length <= f32::EPSILON || area1 <= f32::MIN_POSITIVE || area2 <= f32::MIN_POSITIVE {
+            return None;
+        }
+        let (m1, m2) = (n1 / area1, n2 / area2);
+        let axis = e / length;
+        let directions = [
+            length * m1,
+            length * m2,
+            (x1 - x4).dot(axis) * m1 + (x2 - x4).dot(axis) * m2,
+            -(x1 - x3).dot(axis) * m1 - (x2 - x3).dot(axis) * m2,
+        ];
+
+        let (n1, n2) = (n1.normalize(), n2.normalize());
+        let half = (0.5 * (1.0 - n1.dot(n2))).max(0.0).sqrt();
+        let fold = if n1.cross(n2).dot(axis) < 0.0 { -half } else { half };
+        let scale = length * length / (area1.sqrt() + area2.sqrt());
+        Some((fold, scale, directions))
+    }
+}
+
+/// Keeps the vertices of an envelope out of the faces around them.
+///
+/// Once a vertex comes within `thickness` of a face it does not belong to,
+/// an impulse stops it approaching the face and eases it back out, with
+/// Coulomb friction against sliding along it. Vertices already that close to
+/// a face in the rest state, such as along a seam, are left alone. Edges
+/// crossing edges are not detected, and a vertex moving further than
+/// `thickness` in one substep can pass through a face.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct SelfCollision {
+    /// Distance (m) kept between a vertex and other faces.
+    pub thickness: f32,
+    /// Coefficient of friction between touching parts of the skin.
+    pub friction: f32,
+}
+
+/// A mass-spring membrane simulated in the body's local frame.
+#[derive(Component, Debug, Clone)]
+#[require(DisplacedVolume)]
+pub struct Envelope {
+    pub skin: Skin,
+    /// Temperature (K) of the skin, at which its properties are evaluated.
+    /// Updated every fixed step from the air and lift gas on either side.
+    pub film_temperature: f32,
+    /// Damping ratio of every spring, relative to critical damping.
+    pub damping_ratio: f32,
+    /// Vertices attached to the rigid body, which hold their rest positions.
+    pub anchors: Vec<usize>,
+    /// Relative change in volume after which the collider is rebuilt from
+    /// the deformed shape.
+    pub collider_tolerance: f32,
+    /// Resistance (N·m) of every hinge to folding, by default the skin's
+    /// flexural rigidity.
+    pub bending_stiffness: f32,
+    /// Contact between parts of the skin, off by default since a taut
+    /// envelope does not touch itself.
+    pub self_collision: Option<SelfCollision>,
+    /// Most substeps taken in one fixed step, [`MAX_SUBSTEPS`] by default.
+    /// Each substep costs a pass over every spring and hinge and, with
+    /// self-collision, every vertex.
+    pub max_substeps: usize,
+    positions: Vec<Vec3>,
+    velocities: Vec<Vec3>,
+    masses: Vec<f32>,
+    rest_positions: Vec<Vec3>,
+    triangles: Vec<[u32; 3]>,
+    springs: Vec<Spring>,
+    hinges: Vec<Hinge>,
+    /// Highest natural frequency (rad/s) of any vertex on its springs.
+    max_frequency: f32,
+    /// Largest squared natural frequency (rad²/s²) of any vertex on its
+    /// hinges, per unit bending stiffness.
+    hinge_rate: f32,
+    volume: f32,
+    collider_volume: f32,
+    previous_velocity: Option<Vec3>,
+    burst: bool,
+    /// Whether the envelope has warned about needing more substeps than
+    /// `max_substeps`.
+    warned_substeps: bool,
+    /// Faces binned by the cells of a uniform grid, for self-collision.
+    collision_grid: CollisionGrid,
+    /// Number of faces around every vertex.
+    valence: Vec<f32>,
+}
+
+/// Faces of an envelope binned into every cell of a uniform grid that their
+/// bounds overlap, so a vertex only checks the faces in its own cell. Built
+/// once per step and kept between steps to reuse its allocations.
+#[derive(Debug, Clone, Default)]
+struct CollisionGrid {
+    /// Edge length (m) of a cell.
+    cell_size: f32,
+    cells: HashMap<IVec3, Vec<usize>>,
+}
+
+impl CollisionGrid {
+    fn cell(&self, point: Vec3) -> IVec3 {
+        (point / self.cell_size).floor().as_ivec3()
+    }
+
+    /// Bin every face, with its bounds grown by `margin` (m).
+    fn rebuild(&mut self, positions: &[Vec3], triangles: &[[u32; 3]], cell_size: f32, margin: f32) {
+        // Drop the cells nothing reached last time and empty the rest, so
+        // the grid follows the envelope without reallocating every step.
+        self.cells.retain(|_, faces| !faces.is_empty());
+        self.cells.values_mut().for_each(Vec::clear);
+        self.cell_size = cell_size;
+        for (face, triangle) in triangles.iter().enumerate() {
+            let corners = triangle.map(|vertex| positions[vertex as usize]);
+            let min = corners[0].min(corners[1]).min(corners[2]) - margin;
+            let max = corners[0].max(corners[1]).max(corners[2]) + margin;
+            let (low, high) = (self.cell(min), self.cell(max));
+            for x in low.x..=high.x {
+                for y in low.y..=high.y {
+                    for z in low.z..=high.z {
+                        self.cells.entry(IVec3::new(x, y, z)).or_default().push(face);
+                    }
+                }
+            }
+        }
+    }
+
+    /// The faces that may be near a point.
+    fn faces_near(&self, point: Vec3) -> &[usize] {
+        self.cells.get(&self.cell(point)).map_or(&[], Vec::as_slice)
+    }
+}
+
+impl Envelope {
+    /// An envelope whose rest shape is the given mesh.
+    pub fn from_mesh(mesh: &Mesh, skin: Skin) -> Self {
+        Envelope::new(&RestState::from_mesh(mesh), skin)
+    }
+
+    /// An envelope in its rest state. The neck is the lowest vertex and its
+    /// neighbors.
+    pub fn new(rest_state: &RestState, skin: Skin) -> Self {
+        let rest_positions: Vec<Vec3> = rest_state.rest_positions.iter().map(|&p| p.into()).collect();
+        let mut triangles: Vec<[u32; 3]> = rest_state
+            .indices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect();
+
+        // Faces must wind counterclockwise seen from outside so pressure
+        // pushes outward.
+        let signed_volume: f32 = triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                rest_positions[a as usize].dot(rest_positions[b as usize].cross(rest_positions[c as usize]))
+            })
+            .sum();
+        if signed_volume < 0.0 {
+            for triangle in &mut triangles {
+                triangle.swap(1, 2);
+            }
+        }
+
+        // Lump the skin mass onto the vertices, and give each edge the
+        // stiffness of the membrane area it spans. For a mesh of equilateral
+        // triangles this reproduces the skin's in-plane modulus.
+        let areal_density = skin.density * skin.thickness;
+        let membrane_modulus = skin.elasticity * skin.thickness;
+        let mut masses = vec![0.0; rest_positions.len()];
+        let mut edge_areas: HashMap<(u32, u32), f32> = HashMap::new();
+        for &[a, b, c] in &triangles {
+            let area = triangle_area(
+                rest_positions[a as usize],
+                rest_positions[b as usize],
+                rest_positions[c as usize],
+            );
+            for vertex in [a, b, c] {
+                masses[vertex as usize] += areal_density * area / 3.0;
+            }
+            for (i, j) in [(a, b), (b, c), (c, a)] {
+                *edge_areas.entry((i.min(j), i.max(j))).or_default() += area;
+            }
+        }
+        let mut springs: Vec<Spring> = rest_state
+            .rest_lengths
+            .iter()
+            .filter(|(_, rest_length)| **rest_length > f32::EPSILON)
+            .map(|(&(a, b), &rest_length)| Spring {
+                a,
+                b,
+                rest_length,
+                stiffness: membrane_modulus * edge_areas.get(&(a, b)).copied().unwrap_or_default()
+                    / (rest_length * rest_length),
+            })
+            .collect();
+        springs.sort_by_key(|spring| (spring.a, spring.b));
+
+        // Every edge shared by two faces is a hinge. The faces wind the same
+        // way, so the second one runs along the edge in the other direction.
+        let mut opposite: HashMap<(u32, u32), u32> = HashMap::new();
+        for &[a, b, c] in &triangles {
+            for (i, j, k) in [(a, b, c), (b, c, a), (c, a, b)] {
+                opposite.insert((i, j), k);
+            }
+        }
+        let mut hinges: Vec<Hinge> = opposite
+            .iter()
+            .filter(|((i, j), _)| i < j)
+            .filter_map(|(&(i, j), &k)| {
+                let mut hinge = Hinge {
+                    wings: [k, *opposite.get(&(j, i))?],
+                    edge: [i, j],
+                    rest_fold: 0.0,
+                };
+                hinge.rest_fold = hinge.fold(&rest_positions)?;
+                Some(hinge)
+            })
+            .collect();
+        hinges.sort_by_key(|hinge| hinge.edge);
+
+        let mut vertex_stiffness = vec![0.0; rest_positions.len()];
+        for spring in &springs {
+            vertex_stiffness[spring.a as usize] += spring.stiffness;
+            vertex_stiffness[spring.b as usize] += spring.stiffness;
+        }
+        let max_frequency = vertex_stiffness
+            .iter()
+            .zip(&masses)
+            .filter(|(_, mass)| **mass > 0.0)
+            .map(|(stiffness, mass)| (stiffness / mass).sqrt())
+            .fold(0.0, f32::max);
+
+        // The fold changes at half the rate of the dihedral angle.
+        let mut hinge_stiffness = vec![0.0; rest_positions.len()];
+        for hinge in &hinges {
+            if let Some((_, scale, directions)) = hinge.gradient(&rest_positions) {
+                for (vertex, direction) in hinge.vertices().into_iter().zip(directions) {
+                    hinge_stiffness[vertex as usize] += 0.5 * scale * direction.length_squared();
+                }
+            }
+        }
+        let hinge_rate = hinge_stiffness
+            .iter()
+            .zip(&masses)
+            .filter(|(_, mass)| **mass > 0.0)
+            .map(|(stiffness, mass)| stiffness / mass)
+            .fold(0.0, f32::max);
+
+        let anchors = neck_vertices(&rest_positions, &springs);
+        let bending_stiffness = skin.flexural_rigidity();
+        let volume = trimesh_volume(&rest_positions, &triangles);
+        let mut valence = vec![0.0; rest_positions.len()];
+        for triangle in &triangles {
+            for &corner in triangle {
+                valence[corner as usize] += 1.0;
+            }
+        }
+
+        Envelope {
+            skin,
+            film_temperature: 288.15,
+            damping_ratio: 0.1,
+            anchors,
+            collider_tolerance: 0.01,
+            bending_stiffness,
+            self_collision: None,
+            max_substeps: MAX_SUBSTEPS,
+            positions: rest_positions.clone(),
+            velocities: vec![Vec3::ZERO; rest_positions.len()],
+            masses,
+            rest_positions,
+            triangles,
+            springs,
+            hinges,
+            max_frequency,
+            hinge_rate,
+            volume,
+            collider_volume: volume,
+            previous_velocity: None,
+            burst: false,
+            warned_substeps: false,
+            collision_grid: CollisionGrid::default(),
+            valence,
+        }
+    }
+
+    /// Current vertex positions (m) in the body's local frame.
+    pub fn positions(&self) -> &[Vec3] {
+        &self.positions
+    }
+
+    /// Vertex positions (m) of the undeformed envelope.
+    pub fn rest_positions(&self) -> &[Vec3] {
+        &self.rest_positions
+    }
+
+    /// Triangles of the mesh, wound counterclockwise seen from outside.
+    pub fn triangles(&self) -> &[[u32; 3]] {
+        &self.triangles
+    }
+
+    pub fn springs(&self) -> &[Spring] {
+        &self.springs
+    }
+
+    pub fn hinges(&self) -> &[Hinge] {
+        &self.hinges
+    }
+
+    /// Keep parts of the skin from passing through each other, with a
+    /// thickness of a quarter of the mean edge length.
+    pub fn with_self_collision(mut self) -> Self {
+        let count = self.springs.len().max(1) as f32;
+        let thickness = 0.25 * self.springs.iter().map(|spring| spring.rest_length).sum::<f32>() / count;
+        self.self_collision = Some(SelfCollision {
+            thickness,
+            friction: 0.3,
+        });
+        self
+    }
+
+    /// Mass (kg) lumped onto each vertex.
+    pub fn masses(&self) -> &[f32] {
+        &self.masses
+    }
+
+    /// Total mass (kg) of the skin.
+    pub fn mass(&self) -> f32 {
+        self.masses.iter().sum()
+    }
+
+    /// Volume (m³) enclosed by the deformed envelope.
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// Surface area (m²) of the deformed envelope.
+    pub fn area(&self) -> f32 {
+        self.triangles
+            .iter()
+            .map(|&[a, b, c]| {
+                triangle_area(
+                    self.positions[a as usize],
+                    self.positions[b as usize],
+                    self.positions[c as usize],
+                )
+            })
+            .sum()
+    }
+
+    /// Largest engineering strain of any edge.
+    pub fn max_strain(&self) -> f32 {
+        self.springs
+            .iter()
+            .map(|spring| {
+                spring.strain(self.positions[spring.a as usize].distance(self.positions[spring.b as usize]))
+            })
+            .fold(f32::NEG_INFINITY, f32::max)
+    }
+
+    /// The skin with its properties at the film temperature.
+    pub fn material(&self) -> Skin {
+        self.skin.at(self.film_temperature)
+    }
+
+    /// Whether the skin has failed, usually because an edge stretched past
+    /// the skin's strain at failure.
+    pub fn is_burst(&self) -> bool {
+        self.burst
+    }
+
+    /// Tear the skin, so the envelope no longer holds gas.
+    pub fn rupture(&mut self) {
+        self.burst = true;
+    }
+
+    /// Advance the envelope by `dt` seconds.
+    ///
+    /// `acceleration` is the effective gravity (m/s²) felt in the body frame,
+    /// gravity minus the body's own acceleration, and `up` is the local
+    /// direction opposing gravity. The pressure difference (Pa) across the
+    /// skin at the centroid is `differential_pressure(volume)`. Away from the
+    /// centroid, the ambient pressure falls by `air_pressure_gradient` (Pa/m)
+    /// along `up`, while the lift gas of density `gas_density` (kg/m³) is
+    /// pressed along `acceleration`.
+    pub fn step(
+        &mut self,
+        dt: f32,
+        acceleration: Vec3,
+        up: Vec3,
+        air_pressure_gradient: f32,
+        gas_density: f32,
+        differential_pressure: impl Fn(f32) -> f32,
+    ) {
+        if self.burst || dt <= 0.0 || self.positions.is_empty() {
+            return;
+        }
+        // Springs and hinges were sized for the room temperature modulus.
+        let material = self.material();
+        let stiffening = if self.skin.elasticity > 0.0 {
+            (material.elasticity / self.skin.elasticity).max(0.0)
+        } else {
+            1.0
+        };
+        let bending_stiffness = stiffening * self.bending_stiffness.max(0.0);
+        let frequency =
+            (stiffening * self.max_frequency.powi(2) + bending_stiffness * self.hinge_rate).sqrt();
+        // Frequencies scale with the square root of the stiffness, and so
+        // does the number of substeps needed to resolve them, up to a hard
+        // limit on the cost of a step.
+        let max_substeps = self.max_substeps.max(1);
+        let stable_substeps = (dt * frequency).ceil();
+        let within_limit = stable_substeps <= max_substeps as f32;
+        if !within_limit && !self.warned_substeps {
+            warn!(
+                "Envelope needs {stable_substeps} substeps of {dt} s to stay stable but takes at most \
+                 {max_substeps}; its motion will be wrong. Use a shorter fixed step, a coarser mesh \
+                 or a higher max_substeps."
+            );
+            self.warned_substeps = true;
+        }
+        let substeps = if within_limit {
+            (stable_substeps as usize).max(1)
+        } else {
+            max_substeps
+        };
+        let h = dt / substeps as f32;
+        let mut forces = vec![Vec3::ZERO; self.positions.len()];
+        // Anchored and massless vertices are not moved by any force.
+        let mut inverse_mass: Vec<f32> = self
+            .masses
+            .iter()
+            .map(|&mass| if mass > 0.0 { 1.0 / mass } else { 0.0 })
+            .collect();
+        for &anchor in &self.anchors {
+            if let Some(inverse) = inverse_mass.get_mut(anchor) {
+                *inverse = 0.0;
+            }
+        }
+
+        if let Some(collision) = self.self_collision.filter(|collision| collision.thickness > 0.0) {
+            // Faces are binned once for the whole step, so grow their
+            // bounds by as far as a vertex and a face can close in on each
+            // other over it at their current speeds.
+            let count = self.springs.len().max(1) as f32;
+            let mean_length = self.springs.iter().map(|spring| spring.rest_length).sum::<f32>() / count;
+            let max_speed = self.velocities.iter().map(|v| v.length()).fold(0.0, f32::max);
+            self.collision_grid.rebuild(
+                &self.positions,
+                &self.triangles,
+                mean_length.max(2.0 * collision.thickness),
+                collision.thickness + 2.0 * max_speed * dt,
+            );
+        }
+
+        for _ in 0..substeps {
+            self.volume = trimesh_volume(&self.positions, &self.triangles);
+            let centroid = self.positions.iter().copied().sum::<Vec3>() / self.positions.len() as f32;
+            let base_pressure = differential_pressure(self.volume);
+            forces.fill(Vec3::ZERO);
+
+            for &[a, b, c] in &self.triangles {
+                let (pa, pb, pc) = (
+                    self.positions[a as usize],
+                    self.positions[b as usize],
+                    self.positions[c as usize],
+                );
+                let area_vector = 0.5 * (pb - pa).cross(pc - pa);
+                let offset = (pa + pb + pc) / 3.0 - centroid;
+                // The lift gas accelerates with the body, the air around it
+                // does not, so only the gas feels the body's acceleration.
+                let pressure = base_pressure
+                    + air_pressure_gradient * offset.dot(up)
+                    + gas_density * acceleration.dot(offset);
+                let force = pressure * area_vector / 3.0;
+                forces[a as usize] += force;
+                forces[b as usize] += force;
+                forces[c as usize] += force;
+            }
+
+            let mut max_strain = f32::NEG_INFINITY;
+            for spring in &self.springs {
+                let (a, b) = (spring.a as usize, spring.b as usize);
+                let delta = self.positions[b] - self.positions[a];
+                let length = delta.length();
+                max_strain = max_strain.max(spring.strain(length));
+                if length <= spring.rest_length || length <= f32::EPSILON {
+                    continue;
+                }
+                let direction = delta / length;
+                let reduced_mass = self.masses[a] * self.masses[b] / (self.masses[a] + self.masses[b]);
+                let stiffness = stiffening * spring.stiffness;
+                let damping = 2.0 * self.damping_ratio * (stiffness * reduced_mass).sqrt();
+                let stretch_rate = (self.velocities[b] - self.velocities[a]).dot(direction);
+                let tension = stiffness * (length - spring.rest_length) + damping * stretch_rate;
+                forces[a] += tension * direction;
+                forces[b] -= tension * direction;
+            }
+            for hinge in &self.hinges {
+                let Some((fold, scale, directions)) = hinge.gradient(&self.positions) else {
+                    continue;
+                };
+                let moment = bending_stiffness * scale * (fold - hinge.rest_fold);
+                for (vertex, direction) in hinge.vertices().into_iter().zip(directions) {
+                    forces[vertex as usize] += moment * direction;
+                }
+            }
+
+            // Stop as soon as the skin tears rather than integrate a shape
+            // that no longer holds together.
+            if max_strain > material.max_strain {
+                self.burst = true;
+                break;
+            }
+
+            for (i, force) in forces.iter().enumerate() {
+                if inverse_mass[i] == 0.0 {
+                    continue;
+                }
+                self.velocities[i] += (*force / self.masses[i] + acceleration) * h;
+            }
+            if let Some(collision) = self.self_collision {
+                self.collide(collision, &inverse_mass, h);
+            }
+            for (i, velocity) in self.velocities.iter().enumerate() {
+                if inverse_mass[i] != 0.0 {
+                    self.positions[i] += *velocity * h;
+                }
+            }
+        }
+
+        self.volume = trimesh_volume(&self.positions, &self.triangles);
+        if !self.burst && self.max_strain() > material.max_strain {
+            self.burst = true;
+        }
+    }
+}
+
+impl Envelope {
+    /// Whether two vertices share an edge.
+    fn connected(&self, a: u32, b: u32) -> bool {
+        self.springs
+            .binary_search_by_key(&(a.min(b), a.max(b)), |spring| (spring.a, spring.b))
+            .is_ok()
+    }
+
+    /// Stop every vertex approaching the faces it has come within the
+    /// collision thickness of, and ease it back out over `h` seconds. Held
+    /// vertices have no inverse mass.
+    fn collide(&mut self, collision: SelfCollision, inverse_mass: &[f32], h: f32) {
+        if collision.thickness <= 0.0 || h <= 0.0 {
+            return;
+        }
+        let grid = std::mem::take(&mut self.collision_grid);
+
+        for vertex in 0..self.positions.len() {
+            let position = self.positions[vertex];
+            for &face in grid.faces_near(position) {
+                let triangle = self.triangles[face];
+                if triangle.contains(&(vertex as u32)) {
+                    continue;
+                }
+                let corners = triangle.map(|corner| self.positions[corner as usize]);
+                let (closest, weights) = closest_point_on_triangle(position, corners);
+                let offset = position - closest;
+                let distance = offset.length();
+                if distance >= collision.thickness {
+                    continue;
+                }
+                // Neighbors crowding together are the membrane wrinkling in
+                // its own plane rather than folding onto itself.
+                let mut corners_touched = [0; 3];
+                let mut touched = 0;
+                for (&corner, weight) in triangle.iter().zip(weights) {
+                    if weight > 0.0 {
+                        corners_touched[touched] = corner;
+                        touched += 1;
+                    }
+                }
+                let feature = &corners_touched[..touched];
+                if feature.iter().all(|&corner| self.connected(vertex as u32, corner)) {
+                    continue;
+                }
+                // Vertices duplicated along a seam touch the faces on the
+                // other side of it without being in contact.
+                let rest_corners = triangle.map(|corner| self.rest_positions[corner as usize]);
+                let (rest_closest, _) = closest_point_on_triangle(self.rest_positions[vertex], rest_corners);
+                if self.rest_positions[vertex].distance(rest_closest) < collision.thickness {
+                    continue;
+                }
+
+                let normal = if distance > f32::EPSILON {
+                    offset / distance
+                } else {
+                    (corners[1] - corners[0]).cross(corners[2] - corners[0]).normalize_or_zero()
+                };
+                let face_velocity: Vec3 = triangle
+                    .iter()
+                    .zip(weights)
+                    .map(|(&corner, weight)| weight * self.velocities[corner as usize])
+                    .sum();
+                let relative_velocity = self.velocities[vertex] - face_velocity;
+                let approach = relative_velocity.dot(normal);
+                // Close a tenth of the overlap each substep.
+                let separation = 0.1 * (collision.thickness - distance) / h;
+                if approach >= separation {
+                    continue;
+                }
+                let compliance = inverse_mass[vertex]
+                    + triangle
+                        .iter()
+                        .zip(weights)
+                        .map(|(&corner, weight)| weight * weight * inverse_mass[corner as usize])
+                        .sum::<f32>();
+                if compliance <= 0.0 {
+                    continue;
+                }
+                // A vertex or an edge is found once through every face
+                // around it, so share the contact among them.
+                let shared_by = match feature {
+                    [corner] => self.valence[*corner as usize].max(1.0),
+                    [_, _] => 2.0,
+                    _ => 1.0,
+                };
+                let normal_impulse = (separation - approach) / compliance / shared_by;
+                let sliding = relative_velocity - approach * normal;
+                let slip = sliding.length();
+                let friction_impulse = if slip > f32::EPSILON {
+                    -(collision.friction * normal_impulse).min(slip / compliance / shared_by) * sliding / slip
+                } else {
+                    Vec3::ZERO
+                };
+                let impulse = normal_impulse * normal + friction_impulse;
+                self.velocities[vertex] += inverse_mass[vertex] * impulse;
+                for (&corner, weight) in triangle.iter().zip(weights) {
+                    self.velocities[corner as usize] -= weight * inverse_mass[corner as usize] * impulse;
+                }
+            }
+        }
+        self.collision_grid = grid;
+    }
+}
+
+/// The point of a triangle closest to `point`, and its barycentric weights.
+fn closest_point_on_triangle(point: Vec3, [a, b, c]: [Vec3; 3]) -> (Vec3, [f32; 3]) {
+    // After Ericson, "Real-Time Collision Detection", section 5.1.5.
+    let (ab, ac, ap) = (b - a, c - a, point - a);
+    let (d1, d2) = (ab.dot(ap), ac.dot(ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return (a, [1.0, 0.0, 0.0]);
+    }
+    let bp = point - b;
+    let (d3, d4) = (ab.dot(bp), ac.dot(bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return (b, [0.0, 1.0, 0.0]);
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        let v = d1 / (d1 - d3);
+        return (a + v * ab, [1.0 - v, v, 0.0]);
+    }
+    let cp = point - c;
+    let (d5, d6) = (ab.dot(cp), ac.dot(cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return (c, [0.0, 0.0, 1.0]);
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        let w = d2 / (d2 - d6);
+        return (a + w * ac, [1.0 - w, 0.0, w]);
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return (b + w * (c - b), [0.0, 1.0 - w, w]);
+    }
+    let denominator = va + vb + vc;
+    if denominator.abs() <= f32::MIN_POSITIVE {
+        return (a, [1.0, 0.0, 0.0]);
+    }
+    let (v, w) = (vb / denominator, vc / denominator);
+    (a + v * ab + w * ac, [1.0 - v - w, v, w])
+}
+
+/// The lowest vertex of a mesh and the vertices it shares an edge with.
+fn neck_vertices(positions: &[Vec3], springs: &[Spring]) -> Vec<usize> {
+    let Some(lowest) = positions
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y))
+        .map(|(i, _)| i)
+    else {
+        return Vec::new();
+    };
+    let mut neck = vec![lowest];
+    for spring in springs {
+        if spring.a as usize == lowest {
+            neck.push(spring.b as usize);
+        } else if spring.b as usize == lowest {
+            neck.push(spring.a as usize);
+        }
+    }
+    neck
+}
+
+fn triangle_area(a: Vec3, b: Vec3, c: Vec3) -> f32 {
+    0.5 * (b - a).cross(c - a).length()
+}
+
+pub(crate) fn simulate_envelopes(
+    mut query: Query<(
+        &mut Envelope,
+        &mut DisplacedVolume,
+        &Position,
+        &Rotation,
+        &LinearVelocity,
+        Option<&mut IdealGas>,
+        Option<&mut Collider>,
+        Option<&mut ColliderDensity>,
+    )>,
+    atmosphere: Res<Atmosphere>,
+    clock: Res<SimulationClock>,
+    planet: Res<PlanetaryBody>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+    let epoch = clock.now();
+    for (
+        mut envelope,
+        mut displaced_volume,
+        position,
+        rotation,
+        velocity,
+        mut gas,
+        collider,
+        density,
+    ) in query.iter_mut()
+    {
+        if envelope.burst {
+            displaced_volume.0 = 0.0;
+            continue;
+        }
+
+        let body_acceleration = envelope
+            .previous_velocity
+            .map_or(Vec3::ZERO, |previous| (velocity.0 - previous) / dt);
+        envelope.previous_velocity = Some(velocity.0);
+
+        let gravity = planet
+            .gravity(Length::new::<meter>(position.y))
+            .get::<meter_per_second_squared>();
+        let to_local = rotation.0.inverse();
+        let up = to_local * Vec3::Y;
+        let acceleration = to_local * (Vec3::NEG_Y * gravity - body_acceleration);
+
+        let ambient_pressure = atmosphere.pressure(position.0, epoch).get::<pascal>();
+        let air_density = atmosphere.density(position.0, epoch).get::<kilogram_per_cubic_meter>();
+        let gas_density = gas
+            .as_ref()
+            .map_or(air_density, |gas| gas.density().get::<kilogram_per_cubic_meter>());
+
+        // Without a thermal model for the film, it sits halfway between the
+        // lift gas inside and the air outside.
+        let air_temperature = atmosphere.temperature(position.0, epoch).get::<kelvin>();
+        envelope.film_temperature = gas.as_ref().map_or(air_temperature, |gas| {
+            0.5 * (air_temperature + gas.temperature.get::<kelvin>())
+        });
+
+        let lift_gas = gas.as_deref().cloned();
+        let internal_pressure = |volume: f32| match &lift_gas {
+            Some(gas) if volume > f32::EPSILON => ideal_gas_pressure(
+                gas.temperature,
+                Volume::new::<cubic_meter>(volume),
+                gas.mass,
+                &gas.species,
+            )
+            .get::<pascal>(),
+            _ => ambient_pressure,
+        };
+        envelope.step(
+            dt,
+            acceleration,
+            up,
+            air_density * gravity,
+            gas_density,
+            |volume| internal_pressure(volume) - ambient_pressure,
+        );
+
+        let volume = envelope.volume;
+        if let Some(gas) = gas.as_deref_mut() {
+            gas.pressure = Pressure::new::<pascal>(internal_pressure(volume));
+        }
+        if envelope.burst {
+            displaced_volume.0 = 0.0;
+            continue;
+        }
+        displaced_volume.0 = volume;
+
+        // Rebuilding the collider is costly, so only do it once the shape has
+        // changed noticeably. Keep the collider's mass the same.
+        let change = (volume - envelope.collider_volume).abs() / envelope.collider_volume.max(f32::EPSILON);
+        if let Some(mut collider) = collider
+            && change > envelope.collider_tolerance
+            && let Some(hull) = Collider::convex_hull(envelope.positions.clone())
+        {
+            let old_volume = collider_volume(&collider);
+            let new_volume = collider_volume(&hull);
+            if let Some(mut density) = density
+                && new_volume > f32::EPSILON
+            {
+                density.0 *= old_volume / new_volume;
+            }
+            *collider = hull;
+            envelope.collider_volume = volume;
+        }
+    }
+}
+
+/// Copy the deformed shape into the entity's render mesh, if it has one.
+fn update_envelope_meshes(
+    query: Query<(&Envelope, &MeshHandle)>,
+    meshes: Option<ResMut<Assets<Mesh>>>,
+) {
+    let Some(mut meshes) = meshes else {
+        return;
+    };
+    for (envelope, mesh_handle) in query.iter() {
+        let Some(mesh) = meshes.get_mut(&mesh_handle.0) else {
+            continue;
+        };
+        let positions: Vec<[f32; 3]> = envelope.positions.iter().map(|&p| p.into()).collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.compute_smooth_normals();
+    }
+}
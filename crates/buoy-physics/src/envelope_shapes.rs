@@ -0,0 +1,469 @@
+//! Shapes of real balloon envelopes at float.
+//!
+//! A zero-pressure balloon at float takes its "natural shape": the gores are
+//! free to gather, so the film carries no circumferential stress and the
+//! load tapes carry the payload, the film weight and the lift along the
+//! meridians. For every ring of the envelope, with θ the angle of the
+//! meridian above horizontal and s the arc length from the bottom fitting:
+//!
+//! ```text
+//! dr/ds = cos θ
+//! dz/ds = sin θ
+//! dT/ds = w sin θ
+//! T dθ/ds = 2π r b z + w cos θ
+//! ```
+//!
+//! `T` is the meridional tension summed around the envelope, `w` the weight
+//! of the ring per unit meridian length and `b z` the pressure difference
+//! across the film a height `z` above the bottom, where it is zero. The
+//! bottom fitting holds up the payload, `T sin θ = L`, and the top fitting
+//! carries no load, so the meridian arrives there horizontally. The angle at
+//! the bottom is found by shooting.
+//!
+//! A superpressure pumpkin balloon carries the pressure load in lobes of
+//! film that bulge out between the tapes. When the superpressure dominates,
+//! the tapes have constant tension and curvature proportional to their
+//! radius, which integrates to `r = R √(sin θ)` with a height of about 0.6
+//! times the equatorial diameter.
+//!
+//! Both generators output a triangle mesh with a vertex on every load tape,
+//! centered on the volume enclosed so they can stand in for
+//! [`create_icosphere_mesh`](crate::mesh_utils::create_icosphere_mesh).
+//!
+//! Reference:
+//! - Smalley (1970), Development of the e-Balloon, NCAR
+//! - Baginski & Collier (2000), Modeling the Shapes of Constrained Partially
+//!   Inflated High-Altitude Balloons, AIAA Journal 38
+//! - Baginski, Brakke & Schur (2006), Cleft Formation in Pumpkin Balloons,
+//!   Advances in Space Research 37
+
+use std::{
+    f32::consts::{FRAC_PI_2, PI, TAU},
+    fmt,
+};
+
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_asset::RenderAssetUsages, render_resource::PrimitiveTopology},
+};
+
+use crate::mesh_utils::RestState;
+
+/// Why an envelope shape could not be generated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapeError {
+    /// A parameter is out of range.
+    InvalidParameter(&'static str),
+    /// No meridian closes at the top fitting for these parameters.
+    NoSolution,
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShapeError::InvalidParameter(reason) => write!(f, "invalid envelope shape: {}", reason),
+            ShapeError::NoSolution => write!(f, "no natural shape closes for these parameters"),
+        }
+    }
+}
+
+impl std::error::Error for ShapeError {}
+
+/// Pressure gradient (N/m³) of the lift gas relative to the surrounding air,
+/// `(ρ_air - ρ_gas) g`, given the densities (kg/m³) and gravity (m/s²).
+pub fn gas_head(air_density: f32, gas_density: f32, gravity: f32) -> f32 {
+    (air_density - gas_density) * gravity
+}
+
+/// A point on the meridian of an axisymmetric envelope.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeridianPoint {
+    /// Arc length (m) from the bottom fitting.
+    pub arc_length: f32,
+    /// Distance (m) from the axis.
+    pub radius: f32,
+    /// Height (m) above the bottom fitting.
+    pub height: f32,
+    /// Angle (rad) of the meridian above horizontal.
+    pub angle: f32,
+    /// Meridional tension (N) summed around the envelope.
+    pub tension: f32,
+}
+
+/// The meridian of an axisymmetric envelope from its bottom to its top
+/// fitting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Meridian {
+    pub points: Vec<MeridianPoint>,
+}
+
+impl Meridian {
+    /// Length (m) of the meridian, the length of a gore.
+    pub fn length(&self) -> f32 {
+        self.points.last().map_or(0.0, |p| p.arc_length)
+    }
+
+    /// Height (m) of the top fitting above the bottom fitting.
+    pub fn height(&self) -> f32 {
+        self.points.last().map_or(0.0, |p| p.height)
+    }
+
+    /// Largest distance (m) from the axis.
+    pub fn max_radius(&self) -> f32 {
+        self.points.iter().map(|p| p.radius).fold(0.0, f32::max)
+    }
+
+    /// Volume (m³) of the fully deployed envelope.
+    pub fn volume(&self) -> f32 {
+        self.points
+            .windows(2)
+            .map(|pair| {
+                let (r0, r1) = (pair[0].radius, pair[1].radius);
+                PI * (r0 * r0 + r0 * r1 + r1 * r1) / 3.0 * (pair[1].height - pair[0].height)
+            })
+            .sum()
+    }
+
+    /// Height (m) of the centroid of the enclosed volume above the bottom
+    /// fitting.
+    pub fn centroid_height(&self) -> f32 {
+        let (moment, volume) = self.points.windows(2).fold((0.0, 0.0), |(moment, volume), pair| {
+            let (r0, r1) = (pair[0].radius, pair[1].radius);
+            let slice = PI * (r0 * r0 + r0 * r1 + r1 * r1) / 3.0 * (pair[1].height - pair[0].height);
+            (moment + slice * 0.5 * (pair[0].height + pair[1].height), volume + slice)
+        });
+        if volume.abs() > f32::EPSILON { moment / volume } else { 0.0 }
+    }
+
+    /// Radius (m) and height (m) at an arc length (m) from the bottom fitting.
+    pub fn sample(&self, arc_length: f32) -> (f32, f32) {
+        let index = self
+            .points
+            .partition_point(|p| p.arc_length < arc_length)
+            .clamp(1, self.points.len().max(2) - 1);
+        let (a, b) = (self.points[index - 1], self.points[index]);
+        let span = b.arc_length - a.arc_length;
+        let t = if span > f32::EPSILON { ((arc_length - a.arc_length) / span).clamp(0.0, 1.0) } else { 0.0 };
+        (a.radius + (b.radius - a.radius) * t, a.height + (b.height - a.height) * t)
+    }
+}
+
+/// A zero-pressure balloon in its natural shape at float.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NaturalShape {
+    /// Number of gores, one load tape along each seam.
+    pub gores: usize,
+    /// Weight (N) hanging from the bottom fitting.
+    pub payload_weight: f32,
+    /// Pressure gradient (N/m³) of the lift gas relative to the air. See
+    /// [`gas_head`].
+    pub gas_head: f32,
+    /// Weight (N/m²) of the film per unit area.
+    pub film_weight: f32,
+    /// Weight (N/m) of each load tape per unit length.
+    pub tape_weight: f32,
+}
+
+impl Default for NaturalShape {
+    /// A small polyethylene balloon lifting a 10 kg payload with helium at
+    /// sea level.
+    fn default() -> Self {
+        NaturalShape {
+            gores: 24,
+            payload_weight: 98.1,
+            gas_head: gas_head(1.225, 0.169, 9.81),
+            film_weight: 920.0 * 25.0e-6 * 9.81,
+            tape_weight: 0.0,
+        }
+    }
+}
+
+impl NaturalShape {
+    /// Steps per characteristic length when integrating the meridian.
+    const STEPS_PER_LENGTH: f32 = 400.0;
+    /// Longest meridian tried, in characteristic lengths.
+    const MAX_LENGTH: f32 = 20.0;
+
+    /// Characteristic length (m) of the shape, `(L / b)^(1/3)`.
+    pub fn characteristic_length(&self) -> f32 {
+        (self.payload_weight / self.gas_head).cbrt()
+    }
+
+    /// Smalley's film weight parameter Σ, the weight of the film relative to
+    /// the lift. Shapes of the same Σ are similar.
+    pub fn sigma(&self) -> f32 {
+        self.film_weight / (self.gas_head * self.characteristic_length())
+    }
+
+    /// Solve for the meridian.
+    pub fn meridian(&self) -> Result<Meridian, ShapeError> {
+        if self.gores < 3 {
+            return Err(ShapeError::InvalidParameter("at least 3 gores are needed"));
+        }
+        if !self.payload_weight.is_finite() || self.payload_weight <= 0.0 {
+            return Err(ShapeError::InvalidParameter("payload weight must be positive"));
+        }
+        if !self.gas_head.is_finite() || self.gas_head <= 0.0 {
+            return Err(ShapeError::InvalidParameter("lift gas must be lighter than air"));
+        }
+        if self.film_weight < 0.0 || self.tape_weight < 0.0 {
+            return Err(ShapeError::InvalidParameter("weights must not be negative"));
+        }
+
+        // Scan the bottom angle down from vertical for a sign change of the
+        // closing angle, then refine it by bisection. Shallower bottom angles
+        // can close too, but only after looping around.
+        let closing_error = |angle: f32| self.integrate(angle).map(|m| m.points.last().unwrap().angle - PI);
+        let samples = 64;
+        let mut bracket = None;
+        let mut previous: Option<(f32, f32)> = None;
+        for i in (1..samples).rev() {
+            let angle = FRAC_PI_2 * i as f32 / samples as f32;
+            let Some(error) = closing_error(angle) else {
+                previous = None;
+                continue;
+            };
+            if let Some((previous_angle, previous_error)) = previous
+                && previous_error.signum() != error.signum()
+            {
+                bracket = Some((previous_angle, angle, previous_error));
+                break;
+            }
+            previous = Some((angle, error));
+        }
+        let (mut low, mut high, mut low_error) = bracket.ok_or(ShapeError::NoSolution)?;
+        for _ in 0..40 {
+            let mid = 0.5 * (low + high);
+            let error = closing_error(mid).ok_or(ShapeError::NoSolution)?;
+            if error.signum() == low_error.signum() {
+                low = mid;
+                low_error = error;
+            } else {
+                high = mid;
+            }
+        }
+        self.integrate(0.5 * (low + high)).ok_or(ShapeError::NoSolution)
+    }
+
+    /// Integrate the meridian from the bottom fitting, leaving it at an
+    /// angle (rad) above horizontal, until it returns to the axis.
+    fn integrate(&self, bottom_angle: f32) -> Option<Meridian> {
+        let length = self.characteristic_length();
+        let ds = length / Self::STEPS_PER_LENGTH;
+        let max_steps = (Self::MAX_LENGTH * Self::STEPS_PER_LENGTH) as usize;
+        let tapes = self.gores as f32 * self.tape_weight;
+
+        // State is [r, z, θ, T].
+        let derivative = |state: [f32; 4]| -> [f32; 4] {
+            let [r, z, angle, tension] = state;
+            let weight = TAU * r.max(0.0) * self.film_weight + tapes;
+            [
+                angle.cos(),
+                angle.sin(),
+                (TAU * r * self.gas_head * z + weight * angle.cos()) / tension,
+                weight * angle.sin(),
+            ]
+        };
+
+        let mut state = [0.0, 0.0, bottom_angle, self.payload_weight / bottom_angle.sin()];
+        let mut points = vec![MeridianPoint {
+            arc_length: 0.0,
+            radius: 0.0,
+            height: 0.0,
+            angle: state[2],
+            tension: state[3],
+        }];
+        for step in 1..=max_steps {
+            let k1 = derivative(state);
+            let k2 = derivative(add(state, k1, 0.5 * ds));
+            let k3 = derivative(add(state, k2, 0.5 * ds));
+            let k4 = derivative(add(state, k3, ds));
+            let next: [f32; 4] =
+                std::array::from_fn(|i| state[i] + ds / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]));
+            // Below the bottom fitting the film would be pressed inward, which
+            // it cannot hold.
+            if !next.iter().all(|x| x.is_finite()) || next[3] <= 0.0 || next[1] < 0.0 {
+                return None;
+            }
+            let arc_length = step as f32 * ds;
+            if next[0] <= 0.0 {
+                // Back on the axis: interpolate to the crossing.
+                let t = state[0] / (state[0] - next[0]);
+                let at: [f32; 4] = std::array::from_fn(|i| state[i] + (next[i] - state[i]) * t);
+                points.push(MeridianPoint {
+                    arc_length: arc_length - ds * (1.0 - t),
+                    radius: 0.0,
+                    height: at[1],
+                    angle: at[2],
+                    tension: at[3],
+                });
+                return Some(Meridian { points });
+            }
+            state = next;
+            points.push(MeridianPoint {
+                arc_length,
+                radius: state[0],
+                height: state[1],
+                angle: state[2],
+                tension: state[3],
+            });
+        }
+        None
+    }
+
+    /// A mesh of the natural shape with `columns_per_gore` faces across each
+    /// gore and `rings` faces along the meridian.
+    pub fn mesh(&self, columns_per_gore: usize, rings: usize) -> Result<(Mesh, RestState), ShapeError> {
+        let meridian = self.meridian()?;
+        let columns = self.gores * columns_per_gore.max(1);
+        let rings = rings.max(2);
+        let center = meridian.centroid_height();
+        let length = meridian.length();
+        Ok(revolved_mesh(columns, rings, |column, ring| {
+            let (radius, height) = meridian.sample(length * ring as f32 / rings as f32);
+            let azimuth = TAU * column as f32 / columns as f32;
+            Vec3::new(radius * azimuth.cos(), height - center, -radius * azimuth.sin())
+        }))
+    }
+}
+
+fn add(state: [f32; 4], slope: [f32; 4], step: f32) -> [f32; 4] {
+    std::array::from_fn(|i| state[i] + slope[i] * step)
+}
+
+/// A fully inflated superpressure pumpkin balloon.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PumpkinShape {
+    /// Number of lobes, one load tape between each pair.
+    pub gores: usize,
+    /// Radius (m) of the load tapes at the equator.
+    pub equator_radius: f32,
+    /// Half the angle (rad) subtended by the arc of each lobe. Zero gives
+    /// flat gores and π/2 semicircular lobes.
+    pub bulge_angle: f32,
+}
+
+impl Default for PumpkinShape {
+    fn default() -> Self {
+        PumpkinShape {
+            gores: 24,
+            equator_radius: 2.0,
+            bulge_angle: 0.5,
+        }
+    }
+}
+
+impl PumpkinShape {
+    /// Radius (m) and height (m) of the load tapes at an angle (rad) of the
+    /// tape above horizontal, from 0 at the bottom to π at the top.
+    pub fn tape(&self, angle: f32) -> (f32, f32) {
+        let radius = self.equator_radius * angle.sin().max(0.0).sqrt();
+        // z = R/2 ∫ √(sin φ) dφ, integrated with Simpson's rule.
+        let n = 32;
+        let h = angle / n as f32;
+        let integrand = |phi: f32| phi.sin().max(0.0).sqrt();
+        let sum: f32 = (0..=n)
+            .map(|i| {
+                let weight = if i == 0 || i == n { 1.0 } else if i % 2 == 1 { 4.0 } else { 2.0 };
+                weight * integrand(i as f32 * h)
+            })
+            .sum();
+        (radius, 0.5 * self.equator_radius * sum * h / 3.0)
+    }
+
+    /// Height (m) of the top fitting above the bottom fitting.
+    pub fn height(&self) -> f32 {
+        self.tape(PI).1
+    }
+
+    /// A mesh of the pumpkin with `columns_per_gore` faces across each lobe
+    /// and `rings` faces from the bottom to the top fitting. Rings are evenly
+    /// spaced in tape angle.
+    pub fn mesh(&self, columns_per_gore: usize, rings: usize) -> Result<(Mesh, RestState), ShapeError> {
+        if self.gores < 3 {
+            return Err(ShapeError::InvalidParameter("at least 3 gores are needed"));
+        }
+        if !self.equator_radius.is_finite() || self.equator_radius <= 0.0 {
+            return Err(ShapeError::InvalidParameter("equator radius must be positive"));
+        }
+        if !(0.0..=FRAC_PI_2).contains(&self.bulge_angle) {
+            return Err(ShapeError::InvalidParameter("bulge angle must be between 0 and π/2"));
+        }
+        let columns_per_gore = columns_per_gore.max(1);
+        let columns = self.gores * columns_per_gore;
+        let rings = rings.max(2);
+        let center = 0.5 * self.height();
+        let gore_angle = TAU / self.gores as f32;
+        Ok(revolved_mesh(columns, rings, |column, ring| {
+            let (radius, height) = self.tape(PI * ring as f32 / rings as f32);
+            // Place the vertex on the circular arc of its lobe, spanning the
+            // chord between the two tapes in the horizontal plane.
+            let gore = column / columns_per_gore;
+            let across = (column % columns_per_gore) as f32 / columns_per_gore as f32;
+            let half_chord = radius * (0.5 * gore_angle).sin();
+            let middle = Vec2::from_angle(gore_angle * (gore as f32 + 0.5));
+            let point = if self.bulge_angle > f32::EPSILON {
+                let arc_radius = half_chord / self.bulge_angle.sin();
+                let arc_center = middle * (radius * (0.5 * gore_angle).cos() - arc_radius * self.bulge_angle.cos());
+                let beta = self.bulge_angle * (2.0 * across - 1.0);
+                arc_center + arc_radius * (middle * beta.cos() + middle.perp() * beta.sin())
+            } else {
+                let start = Vec2::from_angle(gore_angle * gore as f32) * radius;
+                let end = Vec2::from_angle(gore_angle * (gore as f32 + 1.0)) * radius;
+                start.lerp(end, across)
+            };
+            Vec3::new(point.x, height - center, -point.y)
+        }))
+    }
+}
+
+/// A closed mesh around the vertical axis with a vertex at each pole and
+/// `rings - 1` rings of `columns` vertices between them. `vertex(column,
+/// ring)` gives the position of each ring vertex, with ring 0 at the bottom.
+fn revolved_mesh(columns: usize, rings: usize, vertex: impl Fn(usize, usize) -> Vec3) -> (Mesh, RestState) {
+    let mut positions: Vec<[f32; 3]> = Vec::with_capacity(columns * (rings - 1) + 2);
+    let bottom = vertex(0, 0);
+    let top = vertex(0, rings);
+    positions.push(bottom.into());
+    for ring in 1..rings {
+        for column in 0..columns {
+            positions.push(vertex(column, ring).into());
+        }
+    }
+    positions.push(top.into());
+    let top_index = (positions.len() - 1) as u32;
+
+    let ring_vertex = |column: usize, ring: usize| (1 + (ring - 1) * columns + column % columns) as u32;
+    let mut indices = Vec::with_capacity(6 * columns * rings);
+    for column in 0..columns {
+        // Counterclockwise seen from outside, with azimuth increasing
+        // counterclockwise seen from above.
+        indices.extend([0, ring_vertex(column + 1, 1), ring_vertex(column, 1)]);
+        for ring in 1..rings - 1 {
+            let (a, b) = (ring_vertex(column, ring), ring_vertex(column + 1, ring));
+            let (c, d) = (ring_vertex(column, ring + 1), ring_vertex(column + 1, ring + 1));
+            indices.extend([a, b, d, a, d, c]);
+        }
+        indices.extend([ring_vertex(column, rings - 1), ring_vertex(column + 1, rings - 1), top_index]);
+    }
+
+    let uvs: Vec<[f32; 2]> = positions
+        .iter()
+        .map(|&[x, y, z]| {
+            [
+                0.5 + z.atan2(x) / TAU,
+                (y - bottom.y) / (top.y - bottom.y).max(f32::EPSILON),
+            ]
+        })
+        .collect();
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_indices(Indices::U32(indices));
+    mesh.compute_smooth_normals();
+    let rest_state = RestState::from_mesh(&mesh);
+    (mesh, rest_state)
+}
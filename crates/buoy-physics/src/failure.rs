@@ -0,0 +1,126 @@
+//! How and when a balloon envelope fails.
+//!
+//! A skin fails in one of three ways, each judged against its properties at
+//! the current film temperature (see [`Skin::at`]):
+//!
+//! - Below its brittle temperature the film cracks instead of stretching.
+//!   Polyethylene reaches this in the coldest layers of the tropopause.
+//! - Above its maximum temperature the film softens and gives way.
+//! - An edge of the envelope stretches past the strain at failure, which
+//!   shrinks as the film cools.
+//!
+//! Membrane stress past the stress at failure is reported by
+//! [`MembraneStress`] but does not fail the envelope on its own.
+//!
+//! The first failure of each envelope ruptures it, marks it [`Failed`] and
+//! sends an [`EnvelopeFailure`] event.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    envelope::Envelope,
+    material_properties::Skin,
+    membrane::{update_membrane_stress, MembraneStress},
+};
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_event::<EnvelopeFailure>();
+    app.register_type::<Failed>();
+    app.add_systems(
+        FixedUpdate,
+        detect_envelope_failures
+            .after(update_membrane_stress)
+            .in_set(PhysicsStepSet::First),
+    );
+}
+
+/// The way a skin failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum FailureKind {
+    /// The film was colder than its brittle temperature.
+    ColdBrittle,
+    /// The film was hotter than its maximum temperature.
+    Overheat,
+    /// The film stretched past its strain at failure.
+    Overstrain,
+}
+
+impl std::fmt::Display for FailureKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailureKind::ColdBrittle => write!(f, "cold brittle fracture"),
+            FailureKind::Overheat => write!(f, "overheat"),
+            FailureKind::Overstrain => write!(f, "overstrain"),
+        }
+    }
+}
+
+/// Sent once when an envelope fails.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct EnvelopeFailure {
+    pub entity: Entity,
+    pub kind: FailureKind,
+    /// Film temperature (K) at failure.
+    pub temperature: f32,
+    /// Largest engineering strain of any edge at failure.
+    pub strain: f32,
+    /// Largest von Mises stress (Pa) of any face at failure, or zero if it
+    /// was not known.
+    pub stress: f32,
+}
+
+/// Marks an envelope that has failed, and how.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct Failed(pub FailureKind);
+
+/// How a skin at a film temperature (K) fails under the given engineering
+/// strain, if it does. Temperature limits are checked first since they fail
+/// the film whatever its load. `skin` is evaluated at the temperature here,
+/// so pass the room temperature skin.
+pub fn evaluate_failure(skin: &Skin, temperature: f32, strain: f32) -> Option<FailureKind> {
+    let material = skin.at(temperature);
+    if temperature < material.min_temperature {
+        Some(FailureKind::ColdBrittle)
+    } else if temperature > material.max_temperature {
+        Some(FailureKind::Overheat)
+    } else if strain > material.max_strain {
+        Some(FailureKind::Overstrain)
+    } else {
+        None
+    }
+}
+
+/// Check every intact envelope against its failure criteria.
+fn detect_envelope_failures(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Envelope, &Position, Option<&MembraneStress>), Without<Failed>>,
+    mut failures: EventWriter<EnvelopeFailure>,
+) {
+    for (entity, mut envelope, position, stress) in query.iter_mut() {
+        let temperature = envelope.film_temperature;
+        let strain = envelope.max_strain();
+        let stress = stress.map_or(0.0, |stress| stress.max_von_mises);
+        // The envelope stops itself as soon as an edge tears, which may be
+        // just short of the strain it ends the step with.
+        let kind = match evaluate_failure(&envelope.skin, temperature, strain) {
+            Some(kind) => kind,
+            None if envelope.is_burst() => FailureKind::Overstrain,
+            None => continue,
+        };
+        envelope.rupture();
+        commands.entity(entity).insert(Failed(kind));
+        warn!(
+            "Envelope failed by {kind} at {:.1} m, {temperature:.1} K and {:.0}% strain",
+            position.y,
+            strain * 100.0
+        );
+        failures.write(EnvelopeFailure {
+            entity,
+            kind,
+            temperature,
+            strain,
+            stress,
+        });
+    }
+}
@@ -0,0 +1,408 @@
+//! Gridded fluid volume for spatially varying ambient media.
+//!
+//! Rather than computing ambient properties from an analytic profile, the
+//! fluid volume looks them up from a regular 3D grid of cells. Each cell
+//! carries the density, temperature, pressure, velocity and medium of the
+//! fluid at its center, and positions between cell centers are trilinearly
+//! interpolated. This covers thermals, inversions, ocean layers and synthetic
+//! test fields with one representation.
+//!
+//! Grids can be built in code with [`FluidGrid::from_fn`], or loaded from RON
+//! or from a compact little-endian binary file:
+//!
+//! | bytes | content                                            |
+//! |-------|----------------------------------------------------|
+//! | 8     | magic `BUOYGRID`                                   |
+//! | 4     | format version (`u32`, currently 1)                |
+//! | 12    | dimensions (`u32` × 3)                             |
+//! | 12    | origin (m, `f32` × 3)                              |
+//! | 12    | spacing (m, `f32` × 3)                             |
+//! | 25 n  | cells: density, temperature, pressure, velocity × 3 (`f32`), medium (`u8`) |
+//!
+//! Cells are stored with x varying fastest, then y, then z.
+
+use std::{fs, path::Path};
+
+use bevy::{asset::Asset, prelude::*, reflect::TypePath};
+use serde::{Deserialize, Serialize};
+
+use crate::water::Medium;
+
+const BINARY_MAGIC: &[u8; 8] = b"BUOYGRID";
+const BINARY_VERSION: u32 = 1;
+const BINARY_CELL_SIZE: usize = 6 * 4 + 1;
+
+/// Fluid properties at the center of one grid cell, in SI units.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct FluidCell {
+    /// Density (kg/m³)
+    pub density: f32,
+    /// Temperature (K)
+    pub temperature: f32,
+    /// Pressure (Pa)
+    pub pressure: f32,
+    /// Velocity (m/s) of the fluid in world space
+    pub velocity: [f32; 3],
+    #[serde(default)]
+    pub medium: Medium,
+}
+
+/// Fluid properties interpolated at an arbitrary position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FluidSample {
+    /// Density (kg/m³)
+    pub density: f32,
+    /// Temperature (K)
+    pub temperature: f32,
+    /// Pressure (Pa)
+    pub pressure: f32,
+    /// Velocity (m/s) of the fluid in world space
+    pub velocity: Vec3,
+    /// Medium of the nearest cell
+    pub medium: Medium,
+}
+
+/// A regular 3D grid of fluid cells. Grids are validated when they are
+/// built or read, so every grid has at least one cell and positive spacing.
+#[derive(Resource, Asset, TypePath, Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "FluidGridData")]
+pub struct FluidGrid {
+    /// World position (m) of the center of the first cell.
+    origin: [f32; 3],
+    /// Distance (m) between neighboring cell centers along each axis.
+    spacing: [f32; 3],
+    /// Number of cells along each axis.
+    dimensions: [u32; 3],
+    /// Cells, with x varying fastest, then y, then z.
+    cells: Vec<FluidCell>,
+}
+
+/// A grid as written in a file, before it is validated.
+#[derive(Deserialize)]
+struct FluidGridData {
+    origin: [f32; 3],
+    spacing: [f32; 3],
+    dimensions: [u32; 3],
+    cells: Vec<FluidCell>,
+}
+
+impl TryFrom<FluidGridData> for FluidGrid {
+    type Error = FluidGridError;
+
+    fn try_from(data: FluidGridData) -> Result<Self, Self::Error> {
+        let grid = FluidGrid {
+            origin: data.origin,
+            spacing: data.spacing,
+            dimensions: data.dimensions,
+            cells: data.cells,
+        };
+        grid.validate()?;
+        Ok(grid)
+    }
+}
+
+/// Number of cells a grid of these dimensions holds, if it fits in memory.
+fn cell_count(dimensions: [u32; 3]) -> Option<usize> {
+    dimensions
+        .iter()
+        .try_fold(1usize, |count, &d| count.checked_mul(d as usize))
+}
+
+#[derive(Debug)]
+pub enum FluidGridError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+    /// The binary file is not a fluid grid or has an unsupported version.
+    Format(String),
+    /// The number of cells doesn't match the dimensions.
+    CellCount { expected: usize, found: usize },
+}
+
+impl std::fmt::Display for FluidGridError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FluidGridError::Io(e) => write!(f, "could not read fluid grid: {}", e),
+            FluidGridError::Ron(e) => write!(f, "could not parse fluid grid: {}", e),
+            FluidGridError::Format(e) => write!(f, "invalid fluid grid: {}", e),
+            FluidGridError::CellCount { expected, found } => write!(
+                f,
+                "fluid grid dimensions need {} cells but {} were given",
+                expected, found
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FluidGridError {}
+
+impl From<std::io::Error> for FluidGridError {
+    fn from(e: std::io::Error) -> Self {
+        FluidGridError::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for FluidGridError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        FluidGridError::Ron(e)
+    }
+}
+
+impl FluidGrid {
+    pub fn new(
+        origin: Vec3,
+        spacing: Vec3,
+        dimensions: UVec3,
+        cells: Vec<FluidCell>,
+    ) -> Result<Self, FluidGridError> {
+        let grid = FluidGrid {
+            origin: origin.into(),
+            spacing: spacing.into(),
+            dimensions: dimensions.into(),
+            cells,
+        };
+        grid.validate()?;
+        Ok(grid)
+    }
+
+    /// Build a grid by evaluating `cell` at the world position of every cell
+    /// center. Handy for synthetic test fields.
+    pub fn from_fn(
+        origin: Vec3,
+        spacing: Vec3,
+        dimensions: UVec3,
+        mut cell: impl FnMut(Vec3) -> FluidCell,
+    ) -> Result<Self, FluidGridError> {
+        let count = match cell_count(dimensions.into()) {
+            Some(count) if count > 0 => count,
+            _ => {
+                return Err(FluidGridError::Format(format!(
+                    "dimensions {dimensions} give no cells or too many"
+                )));
+            }
+        };
+        let mut cells = Vec::with_capacity(count);
+        for k in 0..dimensions.z {
+            for j in 0..dimensions.y {
+                for i in 0..dimensions.x {
+                    cells.push(cell(origin + spacing * UVec3::new(i, j, k).as_vec3()));
+                }
+            }
+        }
+        FluidGrid::new(origin, spacing, dimensions, cells)
+    }
+
+    /// Parse a grid from RON text.
+    pub fn from_ron(text: &str) -> Result<Self, FluidGridError> {
+        Ok(ron::from_str(text)?)
+    }
+
+    /// World position (m) of the center of the first cell.
+    pub fn origin(&self) -> Vec3 {
+        Vec3::from(self.origin)
+    }
+
+    /// Distance (m) between neighboring cell centers along each axis.
+    pub fn spacing(&self) -> Vec3 {
+        Vec3::from(self.spacing)
+    }
+
+    /// Number of cells along each axis.
+    pub fn dimensions(&self) -> UVec3 {
+        UVec3::from(self.dimensions)
+    }
+
+    /// Cells, with x varying fastest, then y, then z.
+    pub fn cells(&self) -> &[FluidCell] {
+        &self.cells
+    }
+
+    /// Decode a grid from the binary format described in the module docs.
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, FluidGridError> {
+        let mut reader = ByteReader { bytes, offset: 0 };
+        if reader.take(BINARY_MAGIC.len())? != BINARY_MAGIC {
+            return Err(FluidGridError::Format("missing BUOYGRID header".to_string()));
+        }
+        let version = reader.u32()?;
+        if version != BINARY_VERSION {
+            return Err(FluidGridError::Format(format!("unsupported version {}", version)));
+        }
+        let dimensions = [reader.u32()?, reader.u32()?, reader.u32()?];
+        let origin = [reader.f32()?, reader.f32()?, reader.f32()?];
+        let spacing = [reader.f32()?, reader.f32()?, reader.f32()?];
+
+        let count = cell_count(dimensions)
+            .filter(|count| count.checked_mul(BINARY_CELL_SIZE).is_some())
+            .ok_or_else(|| FluidGridError::Format(format!("dimensions {dimensions:?} are too large")))?;
+        if reader.remaining() != count * BINARY_CELL_SIZE {
+            return Err(FluidGridError::CellCount {
+                expected: count,
+                found: reader.remaining() / BINARY_CELL_SIZE,
+            });
+        }
+        let mut cells = Vec::with_capacity(count);
+        for _ in 0..count {
+            cells.push(FluidCell {
+                density: reader.f32()?,
+                temperature: reader.f32()?,
+                pressure: reader.f32()?,
+                velocity: [reader.f32()?, reader.f32()?, reader.f32()?],
+                medium: match reader.u8()? {
+                    0 => Medium::Air,
+                    1 => Medium::Water,
+                    other => {
+                        return Err(FluidGridError::Format(format!("unknown medium {}", other)));
+                    }
+                },
+            });
+        }
+        let grid = FluidGrid {
+            origin,
+            spacing,
+            dimensions,
+            cells,
+        };
+        grid.validate()?;
+        Ok(grid)
+    }
+
+    /// Encode the grid in the binary format described in the module docs.
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(48 + self.cells.len() * BINARY_CELL_SIZE);
+        bytes.extend_from_slice(BINARY_MAGIC);
+        bytes.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+        for d in self.dimensions {
+            bytes.extend_from_slice(&d.to_le_bytes());
+        }
+        for value in self.origin.iter().chain(self.spacing.iter()) {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        for cell in &self.cells {
+            for value in [cell.density, cell.temperature, cell.pressure]
+                .iter()
+                .chain(cell.velocity.iter())
+            {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            bytes.push(match cell.medium {
+                Medium::Air => 0,
+                Medium::Water => 1,
+            });
+        }
+        bytes
+    }
+
+    /// Load a grid from a file, picking the format from its extension:
+    /// `.ron` for RON, anything else for the binary format.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, FluidGridError> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|extension| extension == "ron") {
+            FluidGrid::from_ron(&fs::read_to_string(path)?)
+        } else {
+            FluidGrid::from_binary(&fs::read(path)?)
+        }
+    }
+
+    fn validate(&self) -> Result<(), FluidGridError> {
+        let Some(expected) = cell_count(self.dimensions) else {
+            return Err(FluidGridError::Format(format!(
+                "dimensions {:?} are too large",
+                self.dimensions
+            )));
+        };
+        if expected == 0 || expected != self.cells.len() {
+            return Err(FluidGridError::CellCount {
+                expected,
+                found: self.cells.len(),
+            });
+        }
+        if self.spacing.iter().any(|&s| s <= 0.0 || !s.is_finite()) {
+            return Err(FluidGridError::Format("cell spacing must be positive".to_string()));
+        }
+        Ok(())
+    }
+
+    fn cell(&self, i: u32, j: u32, k: u32) -> &FluidCell {
+        let [nx, ny, _] = self.dimensions;
+        &self.cells[(i + nx * (j + ny * k)) as usize]
+    }
+
+    /// Fluid properties at a world position, trilinearly interpolated between
+    /// the surrounding cell centers. Positions outside the grid take the
+    /// values at the nearest boundary.
+    pub fn sample(&self, position: Vec3) -> FluidSample {
+        let dimensions = UVec3::from(self.dimensions);
+        let max_index = (dimensions - UVec3::ONE).as_vec3();
+        let grid_position = ((position - Vec3::from(self.origin)) / Vec3::from(self.spacing))
+            .clamp(Vec3::ZERO, max_index);
+        let lower = grid_position.floor().as_uvec3().min(dimensions - UVec3::ONE);
+        let upper = (lower + UVec3::ONE).min(dimensions - UVec3::ONE);
+        let t = grid_position - lower.as_vec3();
+
+        let mut sample = FluidSample {
+            density: 0.0,
+            temperature: 0.0,
+            pressure: 0.0,
+            velocity: Vec3::ZERO,
+            medium: self.cell(lower.x, lower.y, lower.z).medium,
+        };
+        let mut nearest_weight = -1.0;
+        for corner in 0..8 {
+            let (ux, uy, uz) = (corner & 1 == 1, corner & 2 == 2, corner & 4 == 4);
+            let weight = if ux { t.x } else { 1.0 - t.x }
+                * if uy { t.y } else { 1.0 - t.y }
+                * if uz { t.z } else { 1.0 - t.z };
+            let cell = self.cell(
+                if ux { upper.x } else { lower.x },
+                if uy { upper.y } else { lower.y },
+                if uz { upper.z } else { lower.z },
+            );
+            sample.density += weight * cell.density;
+            sample.temperature += weight * cell.temperature;
+            sample.pressure += weight * cell.pressure;
+            sample.velocity += weight * Vec3::from(cell.velocity);
+            if weight > nearest_weight {
+                nearest_weight = weight;
+                sample.medium = cell.medium;
+            }
+        }
+        sample
+    }
+}
+
+/// Little-endian cursor over the bytes of a binary grid file.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], FluidGridError> {
+        let end = self.offset + count;
+        let slice = self
+            .bytes
+            .get(self.offset..end)
+            .ok_or_else(|| FluidGridError::Format("file is truncated".to_string()))?;
+        self.offset = end;
+        Ok(slice)
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    fn u8(&mut self) -> Result<u8, FluidGridError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, FluidGridError> {
+        let mut word = [0; 4];
+        word.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(word))
+    }
+
+    fn f32(&mut self) -> Result<f32, FluidGridError> {
+        let mut word = [0; 4];
+        word.copy_from_slice(self.take(4)?);
+        Ok(f32::from_le_bytes(word))
+    }
+}
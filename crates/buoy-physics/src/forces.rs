@@ -1,4 +1,4 @@
-use avian3d::{math::Scalar, prelude::*};
+use avian3d::{math::Scalar, prelude::{AngularVelocity, *}};
 use bevy::prelude::*;
 use uom::si::{
     acceleration::meter_per_second_squared, area::square_meter, f32::*, length::meter,
@@ -7,43 +7,149 @@ use uom::si::{
 };
 
 use crate::atmosphere::Atmosphere;
-use crate::constants::{EARTH_RADIUS_M, STANDARD_GRAVITY};
-use crate::geometry::{projected_area, collider_volume};
+use crate::clock::SimulationClock;
+use crate::geometry::{collider_volume, projected_silhouette, Silhouette};
+use crate::planet::PlanetaryBody;
+use crate::turbulence::Gust;
+use crate::water::Water;
 
 pub(crate) fn plugin(app: &mut App) {
     app.insert_resource(Gravity(Vec3::ZERO));
+    app.register_type::<ForceBreakdown>();
     app.add_systems(FixedUpdate, (net_force).in_set(PhysicsStepSet::First));
 }
 
-fn net_force(
+pub(crate) fn net_force(
     mut query: Query<(
         &mut ExternalForce,
+        &mut ForceBreakdown,
+        &mut ExternalTorque,
         &Position,
+        &Rotation,
         &ComputedMass,
-        &Collider,
+        &ComputedCenterOfMass,
+        (Ref<Collider>, &mut SilhouetteCache),
         &LinearVelocity,
+        &AngularVelocity,
         &DragCoefficient,
+        Option<&DisplacedVolume>,
+        Option<&RotationalDamping>,
+        Option<&Gust>,
     )>,
     atmosphere: Res<Atmosphere>,
+    clock: Res<SimulationClock>,
+    planet: Res<PlanetaryBody>,
+    water: Option<Res<Water>>,
+    mut surface_density: Local<Option<MassDensity>>,
 ) {
-    for (mut external_force, position, computed_mass, collider, velocity, drag_coefficient) in
-        query.iter_mut()
+    let epoch = clock.now();
+    // Sampling the reference atmosphere is not free, so only do it again
+    // when the planet changes.
+    let reference_density = match *surface_density {
+        Some(density) if !planet.is_changed() => density,
+        _ => *surface_density.insert(planet.surface_density()),
+    };
+    for (
+        mut external_force,
+        mut breakdown,
+        mut external_torque,
+        position,
+        rotation,
+        computed_mass,
+        center_of_mass,
+        (collider, mut silhouette_cache),
+        velocity,
+        angular_velocity,
+        drag_coefficient,
+        displaced_volume,
+        rotational_damping,
+        gust,
+    ) in query.iter_mut()
     {
         let mass = uom::si::f32::Mass::new::<kilogram>(computed_mass.value());
-        let drag_area =
-            uom::si::f32::Area::new::<square_meter>(projected_area(collider, velocity.0));
-        let gravity = local_gravity(uom::si::f32::Length::new::<meter>(position.y));
+
+        // Drag comes from the body's motion relative to the ambient fluid,
+        // and the collider's silhouette depends on where that flow comes
+        // from in the body's own frame. Gusts ride on top of the mean wind.
+        let wind = atmosphere.wind(position.0, epoch) + gust.map_or(Vec3::ZERO, |g| g.velocity);
+        let airspeed = velocity.0 - wind;
+        let body_airspeed = rotation.0.inverse() * airspeed;
+        let silhouette = if body_airspeed.length() > f32::EPSILON {
+            silhouette_cache.silhouette(&collider, body_airspeed)
+        } else {
+            Silhouette::default()
+        };
+        let drag_area = uom::si::f32::Area::new::<square_meter>(silhouette.area);
+
+        let gravity = planet.gravity(uom::si::f32::Length::new::<meter>(position.y));
         let weight = weight(mass, gravity);
-        let buoyancy = buoyancy(gravity, uom::si::f32::Volume::new::<cubic_meter>(collider_volume(collider)), atmosphere.density(position.0));
+        let displaced_volume = displaced_volume.map_or_else(|| collider_volume(&collider), |v| v.0);
+
+        // Split the displaced volume between the water below the free surface
+        // and the air above it. The atmosphere ends at the surface.
+        let mut air_position = position.0;
+        let (submerged_fraction, center_of_buoyancy, water_density) = match water.as_deref() {
+            Some(water) => {
+                air_position.y = air_position.y.max(water.surface_height);
+                let (submerged_volume, center_of_buoyancy) =
+                    water.submerged_volume(&collider, position.0, rotation.0);
+                let total_volume = collider_volume(&collider);
+                let fraction = if total_volume > f32::EPSILON {
+                    (submerged_volume / total_volume).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (fraction, center_of_buoyancy, water.density())
+            }
+            None => (0.0, position.0, MassDensity::default()),
+        };
+        let air_density = atmosphere.density(air_position, epoch);
+        let ambient_density = air_density * (1.0 - submerged_fraction) + water_density * submerged_fraction;
+        let air_buoyancy = buoyancy(
+            gravity,
+            uom::si::f32::Volume::new::<cubic_meter>(displaced_volume * (1.0 - submerged_fraction)),
+            air_density,
+        );
+        let water_buoyancy = buoyancy(
+            gravity,
+            uom::si::f32::Volume::new::<cubic_meter>(displaced_volume * submerged_fraction),
+            water_density,
+        );
+        let buoyancy = air_buoyancy + water_buoyancy;
         let drag = drag(
-            velocity.0,
-            atmosphere.density(position.0),
+            airspeed,
+            ambient_density,
             drag_area,
             drag_coefficient.0,
         );
-        let net_force = weight + buoyancy + drag;
+        *breakdown = ForceBreakdown {
+            weight,
+            buoyancy,
+            drag,
+            ..default()
+        };
+        let net_force = breakdown.total();
+
+        // Drag acts at the center of pressure. When it sits behind the center
+        // of mass the moment turns the body into the flow, otherwise it
+        // destabilizes it.
+        let lever_arm = rotation.0 * (silhouette.centroid - center_of_mass.0);
+        let mut net_torque = aerodynamic_moment(drag, lever_arm);
+        // Water buoyancy acts at the centroid of the submerged volume, which
+        // rights floating bodies.
+        let world_center_of_mass = position.0 + rotation.0 * center_of_mass.0;
+        net_torque += (center_of_buoyancy - world_center_of_mass).cross(water_buoyancy);
+        if let Some(rotational_damping) = rotational_damping {
+            net_torque += damping_torque(
+                angular_velocity.0,
+                ambient_density,
+                reference_density,
+                rotational_damping.0,
+            );
+        }
 
         external_force.apply_force(net_force);
+        external_torque.apply_torque(net_torque);
     }
 }
 
@@ -66,7 +172,7 @@ pub fn drag(
         * velocity / velocity_magnitude
 }
 
-/// Upward force (N) vector due to atmosphere displaced by the given gas volume.
+/// Upward force (N) vector due to the ambient fluid displaced by the given volume.
 /// The direction of this force is always world-space up (it opposes gravity).
 pub fn buoyancy(
     gravity_acceleration: Acceleration,
@@ -79,14 +185,96 @@ pub fn buoyancy(
             * gravity_acceleration.get::<meter_per_second_squared>())
 }
 
-/// Fraction of standard gravity at an altitude (m) above mean sea level.
-pub fn local_gravity(altitude: Length) -> Acceleration {
-    *EARTH_RADIUS_M / (*EARTH_RADIUS_M + altitude) * *STANDARD_GRAVITY
+/// Moment (N·m) about the center of mass of a force applied at `lever_arm`,
+/// the world-space offset from the center of mass to the point of action.
+pub fn aerodynamic_moment(force: Vec3, lever_arm: Vec3) -> Vec3 {
+    lever_arm.cross(force)
+}
+
+/// Torque (N·m) opposing a body's angular velocity (rad/s). The damping
+/// coefficient (N·m·s/rad) is given at a reference density, the body's
+/// surface density, and scales with the ambient density.
+pub fn damping_torque(
+    angular_velocity: Vec3,
+    ambient_density: MassDensity,
+    reference_density: MassDensity,
+    damping_coefficient: Scalar,
+) -> Vec3 {
+    let density_ratio = (ambient_density / reference_density).get::<ratio>();
+    -damping_coefficient * density_ratio * angular_velocity
 }
 
 fn weight(mass: uom::si::f32::Mass, gravity: Acceleration) -> Vec3 {
     Vec3::NEG_Y * mass.get::<kilogram>() * gravity.get::<meter_per_second_squared>()
 }
 
+/// Drag coefficient of a body. Bodies with one feel weight, buoyancy and
+/// drag every step, and the moments of drag and buoyancy are applied as a
+/// torque that does not persist between steps.
 #[derive(Component, Default)]
+#[require(
+    ForceBreakdown,
+    SilhouetteCache,
+    ExternalTorque = ExternalTorque::default().with_persistence(false)
+)]
 pub struct DragCoefficient(pub Scalar);
+
+/// Smallest cosine between flow directions (in the body frame) that share a
+/// cached silhouette, about a degree apart.
+const SILHOUETTE_DIRECTION_TOLERANCE: f32 = 0.99985;
+
+/// The silhouette a body last showed the flow. Exact silhouettes of meshes
+/// and compounds are expensive, so they are only traced again when the
+/// collider changes or the flow turns by more than about a degree.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct SilhouetteCache {
+    direction: Vec3,
+    silhouette: Silhouette,
+}
+
+impl SilhouetteCache {
+    /// The silhouette of a collider seen along a direction in its own frame.
+    pub fn silhouette(&mut self, collider: &Ref<Collider>, direction: Vec3) -> Silhouette {
+        let direction = direction.normalize();
+        if collider.is_changed() || self.direction.dot(direction) < SILHOUETTE_DIRECTION_TOLERANCE {
+            self.direction = direction;
+            self.silhouette = projected_silhouette(collider, direction);
+        }
+        self.silhouette
+    }
+}
+
+/// Each force (N) that went into a body's net force over the last step, in
+/// world space. Terms that are not modeled yet stay zero.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Reflect)]
+pub struct ForceBreakdown {
+    pub weight: Vec3,
+    /// Buoyancy of the air and water displaced.
+    pub buoyancy: Vec3,
+    /// Drag on the airspeed, the velocity relative to the wind and gusts.
+    pub drag: Vec3,
+    /// Wind forces other than drag.
+    pub wind: Vec3,
+    pub coriolis: Vec3,
+    /// Reaction of the fluid accelerated along with the body.
+    pub added_mass: Vec3,
+}
+
+impl ForceBreakdown {
+    /// Net force (N) of all terms.
+    pub fn total(&self) -> Vec3 {
+        self.weight + self.buoyancy + self.drag + self.wind + self.coriolis + self.added_mass
+    }
+}
+
+/// Volume (m³) of ambient fluid displaced by a body whose shape is not fully
+/// described by its collider, such as a deformable envelope mesh. When
+/// present, buoyancy uses this volume instead of the collider volume.
+#[derive(Component, Default)]
+pub struct DisplacedVolume(pub Scalar);
+
+/// Aerodynamic rotational damping coefficient (N·m·s/rad) at the surface
+/// density of the [`PlanetaryBody`]. Bodies without it only feel the moment of drag about their
+/// center of mass.
+#[derive(Component, Default)]
+pub struct RotationalDamping(pub Scalar);
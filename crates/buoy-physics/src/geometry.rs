@@ -2,11 +2,22 @@
 
 use crate::constants::PI;
 use avian3d::{
-    parry::shape::{ShapeType, SharedShape},
+    parry::{
+        math::{Isometry, Point, Real, Vector},
+        shape::{Shape, ShapeType, SharedShape},
+    },
     prelude::*,
 };
 use bevy::math::{Quat, Vec2, Vec3};
 
+/// Number of support directions sampled around the silhouette of a curved
+/// convex shape (ball, cylinder, capsule, cone) that is nested in a compound.
+const SILHOUETTE_SUPPORT_SAMPLES: usize = 64;
+
+/// Number of subdivisions used to tessellate curved shapes before clipping
+/// them against a plane.
+const CLIPPING_SUBDIVISIONS: u32 = 24;
+
 pub fn sphere_volume(radius: f32) -> f32 {
     (4.0 / 3.0) * PI * f32::powf(radius, 3.0)
 }
@@ -42,16 +53,17 @@ pub fn projected_area_of_cuboid(half_size: Vec3, direction: Vec3) -> f32 {
     2.0 * (a * b * nz + b * c * nx + a * c * ny)
 }
 
+/// Projected area of a Y-aligned cylinder seen along `direction`: the end
+/// cap ellipse plus the rectangle of the side wall.
 pub fn projected_area_of_cylinder(radius: f32, height: f32, direction: Vec3) -> f32 {
-    let nx = direction.x.abs();
-    let ny = direction.y.abs();
-    let nz = direction.z.abs();
+    let cos_axis = direction.normalize().y.abs().min(1.0);
+    let sin_axis = (1.0 - cos_axis * cos_axis).sqrt();
 
-    // Cylindrical surface contribution: 2rh|nz|
-    let cylindrical_area = 2.0 * radius * height * nz;
+    // End cap contribution: πr²|cos θ|
+    let end_caps_area = PI * radius * radius * cos_axis;
 
-    // End caps contribution: πr²(|nx| + |ny|)
-    let end_caps_area = PI * radius * radius * (nx + ny);
+    // Cylindrical surface contribution: 2rh|sin θ|
+    let cylindrical_area = 2.0 * radius * height * sin_axis;
 
     cylindrical_area + end_caps_area
 }
@@ -60,8 +72,8 @@ pub fn projected_area_of_convex_hull(points: &[Vec3], direction: Vec3) -> f32 {
     // Project vertices onto plane perpendicular to direction
     let projected_points = project_vertices_onto_plane(points, direction);
 
-    // Calculate area of the projected polygon
-    polygon_area(&projected_points)
+    // Calculate area of the convex hull of the projected points
+    polygon_area(&convex_hull_2d(&projected_points))
 }
 
 /// Generate the 8 corners of a cuboid from its half-size
@@ -83,12 +95,98 @@ fn generate_cuboid_corners(half_size: Vec3) -> Vec<Vec3> {
 }
 
 /// Feature-based projected area calculation using Parry-style silhouette method
-/// For each face: projected_area += face_area × |face_normal ⋅ dir|
-fn feature_based_projected_area(_shape: &SharedShape, _direction: Vec3) -> Option<f32> {
-    // TODO: Implement feature-based calculation using shape topology
-    // This would use the shape's faces, edges, and vertices directly
-    // For now, return None to fall back to AABB projection
-    None
+///
+/// The shape is broken into convex pieces whose outlines are projected onto
+/// the plane perpendicular to `direction`: convex polyhedra and cuboids by the
+/// 2D hull of their vertices, triangle meshes triangle by triangle, curved
+/// convex shapes by sampling their support map, and compounds by recursing
+/// into each child with its local transform. The silhouette area is the area
+/// of the union of those outlines, so overlapping children are counted once.
+///
+/// Returns `None` when the shape (or one of its children) has no usable
+/// topology, so the caller can fall back to AABB projection.
+fn feature_based_silhouette(shape: &SharedShape, direction: Vec3) -> Option<Silhouette> {
+    let mut outlines = Vec::new();
+    silhouette_outlines(shape.as_ref(), &Isometry::identity(), direction, &mut outlines)?;
+    if outlines.is_empty() {
+        return None;
+    }
+    let (area, centroid) = union_area_and_centroid_of_convex_polygons(&outlines);
+    let (right, up) = plane_basis(direction);
+    Some(Silhouette {
+        area,
+        centroid: right * centroid.x + up * centroid.y,
+    })
+}
+
+/// Collect the projected convex outlines of a shape placed at `isometry`.
+fn silhouette_outlines(
+    shape: &dyn Shape,
+    isometry: &Isometry<Real>,
+    direction: Vec3,
+    outlines: &mut Vec<Vec<Vec2>>,
+) -> Option<()> {
+    let to_world = |point: &Point<Real>| -> Vec3 { (isometry * point).into() };
+
+    match shape.shape_type() {
+        ShapeType::Compound => {
+            let compound = shape.as_compound()?;
+            for (child_isometry, child) in compound.shapes() {
+                silhouette_outlines(child.as_ref(), &(isometry * child_isometry), direction, outlines)?;
+            }
+        }
+        ShapeType::TriMesh => {
+            let trimesh = shape.as_trimesh()?;
+            let vertices: Vec<Vec3> = trimesh.vertices().iter().map(to_world).collect();
+            for triangle in trimesh.indices() {
+                let corners = triangle.map(|i| vertices[i as usize]);
+                push_outline(project_vertices_onto_plane(&corners, direction), outlines);
+            }
+        }
+        ShapeType::ConvexPolyhedron => {
+            let polyhedron = shape.as_convex_polyhedron()?;
+            let points: Vec<Vec3> = polyhedron.points().iter().map(to_world).collect();
+            push_outline(convex_hull_2d(&project_vertices_onto_plane(&points, direction)), outlines);
+        }
+        ShapeType::Cuboid => {
+            let cuboid = shape.as_cuboid()?;
+            let points: Vec<Vec3> = generate_cuboid_corners(cuboid.half_extents.into())
+                .into_iter()
+                .map(|corner| to_world(&Point::new(corner.x, corner.y, corner.z)))
+                .collect();
+            push_outline(convex_hull_2d(&project_vertices_onto_plane(&points, direction)), outlines);
+        }
+        _ => {
+            // Any other convex shape is traced by its support points around
+            // the plane perpendicular to the direction.
+            let support_map = shape.as_support_map()?;
+            let (right, up) = plane_basis(direction);
+            let points: Vec<Vec3> = (0..SILHOUETTE_SUPPORT_SAMPLES)
+                .map(|i| {
+                    let angle = 2.0 * PI * i as f32 / SILHOUETTE_SUPPORT_SAMPLES as f32;
+                    let dir = right * angle.cos() + up * angle.sin();
+                    support_map.support_point(isometry, &Vector::new(dir.x, dir.y, dir.z)).into()
+                })
+                .collect();
+            push_outline(convex_hull_2d(&project_vertices_onto_plane(&points, direction)), outlines);
+        }
+    }
+    Some(())
+}
+
+/// Keep an outline if it encloses any area, wound counter-clockwise.
+fn push_outline(mut outline: Vec<Vec2>, outlines: &mut Vec<Vec<Vec2>>) {
+    if outline.len() < 3 {
+        return;
+    }
+    let signed_area = signed_polygon_area(&outline);
+    if signed_area.abs() <= f32::EPSILON * f32::EPSILON {
+        return;
+    }
+    if signed_area < 0.0 {
+        outline.reverse();
+    }
+    outlines.push(outline);
 }
 
 /// AABB projected area fallback
@@ -101,35 +199,69 @@ fn aabb_projected_area(collider: &Collider, direction: Vec3) -> f32 {
     projected_area_of_convex_hull(&corners, direction)
 }
 
-/// Projected area for capsule shape
+/// AABB silhouette fallback, centered on the projected center of the AABB.
+fn aabb_projected_silhouette(collider: &Collider, direction: Vec3) -> Silhouette {
+    let center = collider.aabb(Vec3::ZERO, Quat::IDENTITY).center();
+    Silhouette {
+        area: aabb_projected_area(collider, direction),
+        centroid: center - center.dot(direction) * direction,
+    }
+}
+
+/// Projected area for a Y-aligned capsule shape
 pub fn projected_area_of_capsule(radius: f32, half_height: f32, direction: Vec3) -> f32 {
-    let nx = direction.x.abs();
-    let ny = direction.y.abs();
-    let nz = direction.z.abs();
+    let cos_axis = direction.normalize().y.abs().min(1.0);
+    let sin_axis = (1.0 - cos_axis * cos_axis).sqrt();
 
-    // Cylindrical part contribution: 2rh|nz|
-    let cylindrical_area = 2.0 * radius * half_height * nz;
+    // Cylindrical part contribution: 2r(2h)|sin θ|
+    let cylindrical_area = 4.0 * radius * half_height * sin_axis;
 
-    // Hemispherical caps contribution: πr²(|nx| + |ny|)
-    let caps_area = PI * radius * radius * (nx + ny);
+    // Hemispherical caps always project to a full disk: πr²
+    let caps_area = PI * radius * radius;
 
     cylindrical_area + caps_area
 }
 
 /// Calculate the projected area of a collider in the direction of motion.
 /// Used for aerodynamic drag force calculations: F_drag = 0.5 * rho * v^2 * C_d * A_projected
+///
+/// The direction must be given in the collider's local frame.
 pub fn projected_area(collider: &Collider, direction: Vec3) -> f32 {
+    projected_silhouette(collider, direction).area
+}
+
+/// The outline of a shape projected onto the plane perpendicular to a flow
+/// direction.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Silhouette {
+    /// Projected area (m²).
+    pub area: f32,
+    /// Centroid of the projected area, in the same frame as the shape, lying
+    /// on the plane through the origin perpendicular to the direction. Drag
+    /// acts along the direction, so this is the center of pressure.
+    pub centroid: Vec3,
+}
+
+/// Calculate the projected area and its centroid for a collider seen along
+/// `direction`, given in the collider's local frame.
+pub fn projected_silhouette(collider: &Collider, direction: Vec3) -> Silhouette {
     let normalized_direction = direction.normalize();
     let shape = collider.shape();
     let shape_type = shape.shape_type();
 
+    // Primitives are centered on the origin, so their silhouettes are too.
+    let centered = |area: f32| Silhouette {
+        area,
+        centroid: Vec3::ZERO,
+    };
+
     match shape_type {
         // 1. Sphere – Use analytical projection: π * r²
         ShapeType::Ball => {
             if let Some(radius) = shape.as_ball() {
-                projected_area_of_sphere(radius.radius, normalized_direction)
+                centered(projected_area_of_sphere(radius.radius, normalized_direction))
             } else {
-                aabb_projected_area(collider, normalized_direction)
+                aabb_projected_silhouette(collider, normalized_direction)
             }
         }
 
@@ -137,84 +269,86 @@ pub fn projected_area(collider: &Collider, direction: Vec3) -> f32 {
         ShapeType::Cuboid => {
             if let Some(cuboid) = shape.as_cuboid() {
                 let corners = generate_cuboid_corners(cuboid.half_extents.into());
-                projected_area_of_convex_hull(&corners, normalized_direction)
+                centered(projected_area_of_convex_hull(&corners, normalized_direction))
             } else {
-                aabb_projected_area(collider, normalized_direction)
+                aabb_projected_silhouette(collider, normalized_direction)
             }
         }
 
         // 3. Cylinder – Use analytical formula
         ShapeType::Cylinder => {
             if let Some(cylinder) = shape.as_cylinder() {
-                projected_area_of_cylinder(
+                centered(projected_area_of_cylinder(
                     cylinder.radius,
                     cylinder.half_height * 2.0,
                     normalized_direction,
-                )
+                ))
             } else {
-                aabb_projected_area(collider, normalized_direction)
+                aabb_projected_silhouette(collider, normalized_direction)
             }
         }
 
         // 4. Capsule – Use analytical formula
         ShapeType::Capsule => {
             if let Some(capsule) = shape.as_capsule() {
-                projected_area_of_capsule(capsule.radius, capsule.half_height(), normalized_direction)
+                centered(projected_area_of_capsule(
+                    capsule.radius,
+                    capsule.half_height(),
+                    normalized_direction,
+                ))
             } else {
-                aabb_projected_area(collider, normalized_direction)
+                aabb_projected_silhouette(collider, normalized_direction)
             }
         }
 
-        // 5. ConvexHull – Use Parry-style silhouette method
-        ShapeType::ConvexPolyhedron => {
-            if let Some(area) = feature_based_projected_area(shape, normalized_direction) {
-                area
-            } else {
+        // 5. ConvexHull, triangle meshes and compounds – Use Parry-style
+        //    silhouette method on the union of projected pieces
+        ShapeType::ConvexPolyhedron | ShapeType::TriMesh | ShapeType::Compound => {
+            feature_based_silhouette(shape, normalized_direction)
                 // Fall back to AABB projection
-                aabb_projected_area(collider, normalized_direction)
-            }
+                .unwrap_or_else(|| aabb_projected_silhouette(collider, normalized_direction))
         }
 
         // 6. Other convex shapes – Try feature-based first
-        _ if shape.is_convex() => {
-            if let Some(area) = feature_based_projected_area(shape, normalized_direction) {
-                area
-            } else {
-                // Fall back to AABB projection
-                aabb_projected_area(collider, normalized_direction)
-            }
-        }
+        _ if shape.is_convex() => feature_based_silhouette(shape, normalized_direction)
+            .unwrap_or_else(|| aabb_projected_silhouette(collider, normalized_direction)),
 
-        // 7. Non-convex shapes – Use AABB fallback
-        _ => aabb_projected_area(collider, normalized_direction),
+        // 7. Other non-convex shapes – Use AABB fallback
+        _ => aabb_projected_silhouette(collider, normalized_direction),
     }
 }
 
-pub fn project_vertices_onto_plane(points: &[Vec3], normal: Vec3) -> Vec<Vec2> {
+/// Orthonormal basis (right, up) of the plane perpendicular to `normal`.
+pub fn plane_basis(normal: Vec3) -> (Vec3, Vec3) {
     let normalized_normal = normal.normalize();
+    let up = if normalized_normal.dot(Vec3::Y).abs() < 0.9 {
+        Vec3::Y
+    } else {
+        Vec3::X
+    };
+    let right = normalized_normal.cross(up).normalize();
+    let plane_up = normalized_normal.cross(right).normalize();
+    (right, plane_up)
+}
+
+pub fn project_vertices_onto_plane(points: &[Vec3], normal: Vec3) -> Vec<Vec2> {
+    // Create orthonormal basis for the plane
+    let (right, plane_up) = plane_basis(normal);
 
     points
         .iter()
-        .map(|&p| {
-            // Project point onto plane perpendicular to normal
-            let projected = p - p.dot(normalized_normal) * normalized_normal;
-
-            // Create orthonormal basis for the plane
-            let up = if normalized_normal.dot(Vec3::Y).abs() < 0.9 {
-                Vec3::Y
-            } else {
-                Vec3::X
-            };
-            let right = normalized_normal.cross(up).normalize();
-            let plane_up = normalized_normal.cross(right).normalize();
-
-            // Convert to 2D coordinates in the plane
-            Vec2::new(projected.dot(right), projected.dot(plane_up))
-        })
+        // Convert to 2D coordinates in the plane. The component along the
+        // normal is dropped by the dot products.
+        .map(|&p| Vec2::new(p.dot(right), p.dot(plane_up)))
         .collect()
 }
 
 pub fn polygon_area(points: &[Vec2]) -> f32 {
+    signed_polygon_area(points).abs()
+}
+
+/// Shoelace area of a polygon, positive when wound counter-clockwise.
+fn signed_polygon_area(points: &[Vec2]) -> f32 {
     let n = points.len();
     let mut area = 0.0;
     for i in 0..n {
@@ -222,8 +356,235 @@ pub fn polygon_area(points: &[Vec2]) -> f32 {
         let p2 = points[(i + 1) % n];
         area += p1.x * p2.y - p2.x * p1.y;
     }
-    0.5 * area.abs()
+    0.5 * area
+}
+
+/// Convex hull of a set of 2D points (Andrew's monotone chain), wound
+/// counter-clockwise without collinear points.
+pub fn convex_hull_2d(points: &[Vec2]) -> Vec<Vec2> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let cross = |o: Vec2, a: Vec2, b: Vec2| (a - o).perp_dot(b - o);
+    let mut hull: Vec<Vec2> = Vec::with_capacity(sorted.len() * 2);
+    // Lower hull
+    for &p in &sorted {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    // Upper hull
+    let lower_len = hull.len() + 1;
+    for &p in sorted.iter().rev().skip(1) {
+        while hull.len() >= lower_len && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+            hull.pop();
+        }
+        hull.push(p);
+    }
+    hull.pop();
+    hull
+}
+
+/// Area of the union of convex polygons, each wound counter-clockwise.
+///
+/// The plane is cut into vertical slabs at every vertex and every crossing
+/// between edges of different polygons. Inside a slab no two edges cross, so
+/// the covered length varies linearly across the slab and the covered area is
+/// exactly the slab width times the covered length at the slab's middle.
+pub fn union_area_of_convex_polygons(polygons: &[Vec<Vec2>]) -> f32 {
+    let mut area = 0.0;
+    for_each_union_slab(polygons, |left, right, intervals| {
+        let middle = 0.5 * (left + right);
+        let covered: f32 = intervals.iter().map(|(lo, hi)| hi.at(middle) - lo.at(middle)).sum();
+        area += (right - left) * covered;
+    });
+    area
+}
+
+/// Area and centroid of the union of convex polygons, each wound
+/// counter-clockwise.
+///
+/// Uses the same slabs as [`union_area_of_convex_polygons`]. The first
+/// moments are quadratic in x inside a slab, so two-point Gauss-Legendre
+/// quadrature integrates them exactly.
+pub fn union_area_and_centroid_of_convex_polygons(polygons: &[Vec<Vec2>]) -> (f32, Vec2) {
+    let gauss_offset = 0.5 / 3.0_f32.sqrt();
+    let mut area = 0.0;
+    let mut moment = Vec2::ZERO;
+    for_each_union_slab(polygons, |left, right, intervals| {
+        let width = right - left;
+        let middle = 0.5 * (left + right);
+        for x in [middle - gauss_offset * width, middle + gauss_offset * width] {
+            let (covered, y_moment) = intervals.iter().fold((0.0, 0.0), |(length, y_moment), (lo, hi)| {
+                let (lo, hi) = (lo.at(x), hi.at(x));
+                (length + hi - lo, y_moment + 0.5 * (hi * hi - lo * lo))
+            });
+            area += 0.5 * width * covered;
+            moment += 0.5 * width * Vec2::new(x * covered, y_moment);
+        }
+    });
+    if area <= f32::EPSILON {
+        return (area, Vec2::ZERO);
+    }
+    (area, moment / area)
+}
+
+/// A polygon edge seen as a line `y = intercept + slope * x`.
+#[derive(Debug, Clone, Copy)]
+struct SlabLine {
+    intercept: f32,
+    slope: f32,
+}
+
+impl SlabLine {
+    fn through(a: Vec2, b: Vec2) -> Self {
+        let slope = (b.y - a.y) / (b.x - a.x);
+        Self {
+            intercept: a.y - slope * a.x,
+            slope,
+        }
+    }
+
+    fn at(&self, x: f32) -> f32 {
+        self.intercept + self.slope * x
+    }
+}
+
+/// Sweep the union of convex polygons slab by slab.
+///
+/// For every slab `[left, right]` the callback receives the disjoint
+/// `(lower, upper)` boundary lines of the covered intervals, which stay valid
+/// across the whole slab.
+fn for_each_union_slab(polygons: &[Vec<Vec2>], mut slab: impl FnMut(f32, f32, &[(SlabLine, SlabLine)])) {
+    // Non-vertical edges tagged with the polygon they belong to.
+    let mut edges: Vec<(usize, Vec2, Vec2)> = Vec::new();
+    let mut cuts: Vec<f32> = Vec::new();
+    for (polygon_index, polygon) in polygons.iter().enumerate() {
+        for (i, &a) in polygon.iter().enumerate() {
+            let b = polygon[(i + 1) % polygon.len()];
+            cuts.push(a.x);
+            if a.x != b.x {
+                let (a, b) = if a.x < b.x { (a, b) } else { (b, a) };
+                edges.push((polygon_index, a, b));
+            }
+        }
+    }
+
+    // Crossings between edges of different polygons, found with a
+    // sweep-and-prune over the edges' x ranges.
+    edges.sort_by(|a, b| a.1.x.total_cmp(&b.1.x));
+    for (i, &(polygon_a, a0, a1)) in edges.iter().enumerate() {
+        for &(polygon_b, b0, b1) in &edges[i + 1..] {
+            if b0.x > a1.x {
+                break;
+            }
+            if polygon_a == polygon_b {
+                continue;
+            }
+            if let Some(x) = segment_crossing_x(a0, a1, b0, b1) {
+                cuts.push(x);
+            }
+        }
+    }
+
+    cuts.sort_by(f32::total_cmp);
+    cuts.dedup_by(|a, b| (*a - *b).abs() <= f32::EPSILON * a.abs().max(1.0));
+
+    // Polygons sorted by their leftmost vertex so each slab only looks at
+    // polygons that can overlap it.
+    let mut bounds: Vec<(f32, f32, usize)> = polygons
+        .iter()
+        .enumerate()
+        .map(|(i, polygon)| {
+            let (min, max) = polygon
+                .iter()
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| (min.min(p.x), max.max(p.x)));
+            (min, max, i)
+        })
+        .collect();
+    bounds.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut next_polygon = 0;
+    // Active polygons as (rightmost x, polygon index).
+    let mut active: Vec<(f32, usize)> = Vec::new();
+    let mut intervals: Vec<(SlabLine, SlabLine)> = Vec::new();
+    let mut merged: Vec<(SlabLine, SlabLine)> = Vec::new();
+    for window in cuts.windows(2) {
+        let (left, right) = (window[0], window[1]);
+        let middle = 0.5 * (left + right);
+        while next_polygon < bounds.len() && bounds[next_polygon].0 < right {
+            active.push((bounds[next_polygon].1, bounds[next_polygon].2));
+            next_polygon += 1;
+        }
+        active.retain(|&(max, _)| max > left);
+
+        intervals.clear();
+        for &(_, i) in &active {
+            if let Some(interval) = convex_polygon_interval(&polygons[i], middle) {
+                intervals.push(interval);
+            }
+        }
+        intervals.sort_by(|a, b| a.0.at(middle).total_cmp(&b.0.at(middle)));
+
+        merged.clear();
+        for &(lo, hi) in &intervals {
+            match merged.last_mut() {
+                Some(last) if lo.at(middle) <= last.1.at(middle) => {
+                    if hi.at(middle) > last.1.at(middle) {
+                        last.1 = hi;
+                    }
+                }
+                _ => merged.push((lo, hi)),
+            }
+        }
+        if !merged.is_empty() {
+            slab(left, right, &merged);
+        }
+    }
 }
+
+/// Lower and upper boundary lines of a convex polygon at `x`, if it spans `x`.
+fn convex_polygon_interval(polygon: &[Vec2], x: f32) -> Option<(SlabLine, SlabLine)> {
+    let mut lower: Option<SlabLine> = None;
+    let mut upper: Option<SlabLine> = None;
+    for (i, &a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        if a.x == b.x || x < a.x.min(b.x) || x > a.x.max(b.x) {
+            continue;
+        }
+        let line = SlabLine::through(a, b);
+        if lower.is_none_or(|lower| line.at(x) < lower.at(x)) {
+            lower = Some(line);
+        }
+        if upper.is_none_or(|upper| line.at(x) > upper.at(x)) {
+            upper = Some(line);
+        }
+    }
+    Some((lower?, upper?))
+}
+
+/// The x coordinate where two segments properly cross, if they do.
+fn segment_crossing_x(a0: Vec2, a1: Vec2, b0: Vec2, b1: Vec2) -> Option<f32> {
+    let da = a1 - a0;
+    let db = b1 - b0;
+    let denominator = da.perp_dot(db);
+    if denominator.abs() <= f32::EPSILON {
+        return None;
+    }
+    let t = (b0 - a0).perp_dot(db) / denominator;
+    let u = (b0 - a0).perp_dot(da) / denominator;
+    if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+        Some(a0.x + t * da.x)
+    } else {
+        None
+    }
+}
+
 // Volume calculation functions for different shapes
 
 pub fn cuboid_volume(half_size: Vec3) -> f32 {
@@ -242,6 +603,9 @@ pub fn capsule_volume(radius: f32, half_height: f32) -> f32 {
 
 /// Calculate the volume of a convex polyhedron using the divergence theorem
 /// V = (1/3) * sum(face_area * face_normal ⋅ face_centroid)
+///
+/// Faces are lists of vertex indices wound counter-clockwise when seen from
+/// outside the polyhedron.
 pub fn convex_polyhedron_volume(points: &[Vec3], faces: &[Vec<usize>]) -> f32 {
     let mut volume = 0.0;
 
@@ -257,72 +621,192 @@ pub fn convex_polyhedron_volume(points: &[Vec3], faces: &[Vec<usize>]) -> f32 {
         }
         centroid /= face_indices.len() as f32;
 
-        // Calculate face normal using first three vertices
-        let v0 = points[face_indices[0]];
-        let v1 = points[face_indices[1]];
-        let v2 = points[face_indices[2]];
-        let normal = (v1 - v0).cross(v2 - v0).normalize();
-
-        // Calculate face area
-        let mut face_area = 0.0;
+        // Calculate the face's vector area (face_area * face_normal)
+        let mut vector_area = Vec3::ZERO;
         for i in 0..face_indices.len() {
             let p1 = points[face_indices[i]];
             let p2 = points[face_indices[(i + 1) % face_indices.len()]];
-            face_area += p1.cross(p2).length();
+            vector_area += p1.cross(p2);
         }
-        face_area *= 0.5;
+        vector_area *= 0.5;
 
         // Add contribution to volume
-        volume += face_area * normal.dot(centroid);
+        volume += vector_area.dot(centroid);
     }
 
-    volume / 3.0
+    volume.abs() / 3.0
+}
+
+/// Calculate the volume enclosed by a closed triangle mesh using the
+/// divergence theorem: V = (1/6) * sum(v0 ⋅ (v1 × v2)).
+///
+/// The mesh must be closed and consistently wound, but it does not need to be
+/// convex.
+pub fn trimesh_volume(vertices: &[Vec3], indices: &[[u32; 3]]) -> f32 {
+    let signed_volume: f32 = indices
+        .iter()
+        .map(|&[a, b, c]| {
+            let v0 = vertices[a as usize];
+            let v1 = vertices[b as usize];
+            let v2 = vertices[c as usize];
+            v0.dot(v1.cross(v2))
+        })
+        .sum();
+    signed_volume.abs() / 6.0
 }
 
 /// Calculate the volume of a collider
 pub fn collider_volume(collider: &Collider) -> f32 {
-    let shape = collider.shape();
-    let shape_type = shape.shape_type();
+    shape_volume(collider.shape().as_ref()).unwrap_or_else(|| {
+        // For other shapes, use AABB volume as fallback
+        let aabb = collider.aabb(Vec3::ZERO, Quat::IDENTITY);
+        aabb_volume(&aabb) * 0.5 // Conservative estimate
+    })
+}
 
-    match shape_type {
-        ShapeType::Ball => {
-            if let Some(ball) = shape.as_ball() {
-                sphere_volume(ball.radius)
-            } else {
-                0.0
-            }
-        }
+/// Volume of a shape computed from its topology, if it encloses a volume.
+///
+/// Compound shapes report the sum of their children's volumes, so children
+/// are expected not to overlap.
+pub fn shape_volume(shape: &dyn Shape) -> Option<f32> {
+    match shape.shape_type() {
+        ShapeType::Ball => shape.as_ball().map(|ball| sphere_volume(ball.radius)),
+
+        ShapeType::Cuboid => shape
+            .as_cuboid()
+            .map(|cuboid| cuboid_volume(cuboid.half_extents.into())),
+
+        ShapeType::Cylinder => shape
+            .as_cylinder()
+            .map(|cylinder| cylinder_volume(cylinder.radius, cylinder.half_height * 2.0)),
+
+        ShapeType::Capsule => shape
+            .as_capsule()
+            .map(|capsule| capsule_volume(capsule.radius, capsule.half_height())),
+
+        ShapeType::ConvexPolyhedron => shape.as_convex_polyhedron().map(|polyhedron| {
+            let (points, indices) = polyhedron.to_trimesh();
+            let vertices: Vec<Vec3> = points.into_iter().map(Into::into).collect();
+            trimesh_volume(&vertices, &indices)
+        }),
+
+        ShapeType::TriMesh => shape.as_trimesh().map(|trimesh| {
+            let vertices: Vec<Vec3> = trimesh.vertices().iter().map(|&p| p.into()).collect();
+            trimesh_volume(&vertices, trimesh.indices())
+        }),
+
+        ShapeType::Compound => shape.as_compound().and_then(|compound| {
+            compound
+                .shapes()
+                .iter()
+                .map(|(_, child)| shape_volume(child.as_ref()))
+                .sum()
+        }),
+
+        _ => None,
+    }
+}
 
-        ShapeType::Cuboid => {
-            if let Some(cuboid) = shape.as_cuboid() {
-                cuboid_volume(cuboid.half_extents.into())
-            } else {
-                0.0
-            }
+/// Volume (m³) and world-space centroid of the part of a collider below the
+/// horizontal plane at `height` (m).
+///
+/// The collider is placed at `position` with `rotation`, tessellated and
+/// clipped against the plane. The clipped fraction of the tessellated volume
+/// is applied to the exact collider volume so that a fully submerged body
+/// displaces exactly [`collider_volume`].
+pub fn volume_below_plane(collider: &Collider, position: Vec3, rotation: Quat, height: f32) -> (f32, Vec3) {
+    let aabb = collider.aabb(position, rotation);
+    if aabb.min.y >= height {
+        return (0.0, position);
+    }
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    if shape_triangles(collider.shape().as_ref(), &Isometry::identity(), &mut vertices, &mut indices).is_none() {
+        // Without a surface to clip, assume the AABB is filled uniformly.
+        let fraction = ((height - aabb.min.y) / (aabb.max.y - aabb.min.y)).clamp(0.0, 1.0);
+        let centroid = Vec3::new(position.x, 0.5 * (aabb.min.y + height.min(aabb.max.y)), position.z);
+        return (fraction * collider_volume(collider), centroid);
+    }
+
+    // Tetrahedra are fanned from a point on the plane, so the faces of the
+    // clipped solid that lie on the plane contribute no volume and don't need
+    // to be built.
+    let reference = Vec3::new(position.x, height, position.z);
+    let tetrahedron = |a: Vec3, b: Vec3, c: Vec3| (a - reference).dot((b - reference).cross(c - reference)) / 6.0;
+
+    let mut tessellated_volume = 0.0;
+    let mut clipped_volume = 0.0;
+    let mut clipped_moment = Vec3::ZERO;
+    for triangle in &indices {
+        let corners = triangle.map(|i| position + rotation * vertices[i as usize]);
+        tessellated_volume += tetrahedron(corners[0], corners[1], corners[2]);
+
+        let clipped = clip_polygon_below(&corners, height);
+        for k in 1..clipped.len().saturating_sub(1) {
+            let (a, b, c) = (clipped[0], clipped[k], clipped[k + 1]);
+            let volume = tetrahedron(a, b, c);
+            clipped_volume += volume;
+            clipped_moment += volume * (reference + a + b + c) / 4.0;
         }
+    }
 
-        ShapeType::Cylinder => {
-            if let Some(cylinder) = shape.as_cylinder() {
-                cylinder_volume(cylinder.radius, cylinder.half_height * 2.0)
-            } else {
-                0.0
-            }
+    if tessellated_volume.abs() <= f32::EPSILON || clipped_volume.abs() <= f32::EPSILON {
+        return (0.0, position);
+    }
+    let fraction = (clipped_volume / tessellated_volume).clamp(0.0, 1.0);
+    (fraction * collider_volume(collider), clipped_moment / clipped_volume)
+}
+
+/// Keep the part of a convex polygon at or below `height` (Sutherland–Hodgman).
+fn clip_polygon_below(polygon: &[Vec3], height: f32) -> Vec<Vec3> {
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (i, &a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        let (a_below, b_below) = (a.y <= height, b.y <= height);
+        if a_below {
+            clipped.push(a);
         }
+        if a_below != b_below {
+            let t = (height - a.y) / (b.y - a.y);
+            clipped.push(a.lerp(b, t));
+        }
+    }
+    clipped
+}
 
-        ShapeType::Capsule => {
-            if let Some(capsule) = shape.as_capsule() {
-                capsule_volume(capsule.radius, capsule.half_height())
-            } else {
-                0.0
+/// Triangulated surface of a shape placed at `isometry`, appended to
+/// `vertices` and `indices`. Returns `None` for shapes without a closed
+/// surface.
+fn shape_triangles(
+    shape: &dyn Shape,
+    isometry: &Isometry<Real>,
+    vertices: &mut Vec<Vec3>,
+    indices: &mut Vec<[u32; 3]>,
+) -> Option<()> {
+    let (points, triangles) = match shape.shape_type() {
+        ShapeType::Compound => {
+            for (child_isometry, child) in shape.as_compound()?.shapes() {
+                shape_triangles(child.as_ref(), &(isometry * child_isometry), vertices, indices)?;
             }
+            return Some(());
         }
-
-        _ => {
-            // For other shapes, use AABB volume as fallback
-            let aabb = collider.aabb(Vec3::ZERO, Quat::IDENTITY);
-            aabb_volume(&aabb) * 0.5 // Conservative estimate
+        ShapeType::TriMesh => {
+            let trimesh = shape.as_trimesh()?;
+            (trimesh.vertices().to_vec(), trimesh.indices().to_vec())
         }
-    }
+        ShapeType::ConvexPolyhedron => shape.as_convex_polyhedron()?.to_trimesh(),
+        ShapeType::Cuboid => shape.as_cuboid()?.to_trimesh(),
+        ShapeType::Ball => shape.as_ball()?.to_trimesh(CLIPPING_SUBDIVISIONS, CLIPPING_SUBDIVISIONS),
+        ShapeType::Cylinder => shape.as_cylinder()?.to_trimesh(CLIPPING_SUBDIVISIONS),
+        ShapeType::Capsule => shape.as_capsule()?.to_trimesh(CLIPPING_SUBDIVISIONS, CLIPPING_SUBDIVISIONS),
+        ShapeType::Cone => shape.as_cone()?.to_trimesh(CLIPPING_SUBDIVISIONS),
+        _ => return None,
+    };
+    let offset = vertices.len() as u32;
+    vertices.extend(points.iter().map(|point| Vec3::from(isometry * point)));
+    indices.extend(triangles.iter().map(|triangle| triangle.map(|i| i + offset)));
+    Some(())
 }
 
 /// Get the half-extents of an AABB (half of the size)
@@ -335,3 +819,145 @@ pub fn aabb_volume(aabb: &ColliderAabb) -> f32 {
     let size = aabb.size();
     size.x * size.y * size.z
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mesh_utils::create_icosphere_mesh;
+    use bevy::render::mesh::{Mesh, VertexAttributeValues};
+
+    fn square(min: Vec2, max: Vec2) -> Vec<Vec2> {
+        vec![min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)]
+    }
+
+    fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn union_of_overlapping_squares_counts_the_overlap_once() {
+        let polygons = [
+            square(Vec2::new(0.0, 0.0), Vec2::new(2.0, 2.0)),
+            square(Vec2::new(1.0, 1.0), Vec2::new(3.0, 3.0)),
+        ];
+        let (area, centroid) = union_area_and_centroid_of_convex_polygons(&polygons);
+        assert_close(area, 7.0, 1e-5);
+        assert_close(centroid.x, 1.5, 1e-5);
+        assert_close(centroid.y, 1.5, 1e-5);
+        assert_close(union_area_of_convex_polygons(&polygons), 7.0, 1e-5);
+    }
+
+    #[test]
+    fn union_centroid_follows_the_covered_area() {
+        // An L-shape: a 2 x 2 square and a 2 x 1 bar overlapping by 1 x 1.
+        let polygons = [
+            square(Vec2::new(0.0, 0.0), Vec2::new(2.0, 2.0)),
+            square(Vec2::new(1.0, 0.0), Vec2::new(3.0, 1.0)),
+        ];
+        let (area, centroid) = union_area_and_centroid_of_convex_polygons(&polygons);
+        assert_close(area, 5.0, 1e-5);
+        // 4 m² centered on (1, 1) plus 1 m² centered on (2.5, 0.5).
+        assert_close(centroid.x, (4.0 * 1.0 + 2.5) / 5.0, 1e-5);
+        assert_close(centroid.y, (4.0 * 1.0 + 0.5) / 5.0, 1e-5);
+    }
+
+    #[test]
+    fn union_of_crossing_polygons_is_exact() {
+        // A diamond over a square, with edges crossing near every corner of
+        // the square.
+        let diamond = vec![
+            Vec2::new(1.0, -0.5),
+            Vec2::new(2.5, 1.0),
+            Vec2::new(1.0, 2.5),
+            Vec2::new(-0.5, 1.0),
+        ];
+        let polygons = [square(Vec2::ZERO, Vec2::splat(2.0)), diamond];
+        // The square (4 m²) sticks out of the diamond (4.5 m²) by four corner
+        // triangles of 0.125 m² each.
+        let (area, centroid) = union_area_and_centroid_of_convex_polygons(&polygons);
+        assert_close(area, 4.0 + 4.5 - 3.5, 1e-5);
+        assert!(centroid.distance(Vec2::ONE) < 1e-5, "{centroid:?}");
+    }
+
+    #[test]
+    fn union_of_disjoint_polygons_adds_up() {
+        let polygons = [
+            square(Vec2::ZERO, Vec2::ONE),
+            square(Vec2::new(2.0, 0.0), Vec2::new(3.0, 2.0)),
+        ];
+        let (area, centroid) = union_area_and_centroid_of_convex_polygons(&polygons);
+        assert_close(area, 3.0, 1e-5);
+        assert_close(centroid.x, (0.5 + 2.0 * 2.5) / 3.0, 1e-5);
+        assert_close(centroid.y, (0.5 + 2.0 * 1.0) / 3.0, 1e-5);
+    }
+
+    #[test]
+    fn silhouette_of_rotated_cuboid() {
+        // A 2 x 4 x 6 m box turned 45° about Y and raised by 1 m, seen along X.
+        let collider = Collider::compound(vec![(
+            Vec3::Y,
+            Quat::from_rotation_y(PI / 4.0),
+            Collider::cuboid(2.0, 4.0, 6.0),
+        )]);
+        let silhouette = projected_silhouette(&collider, Vec3::X);
+        let width = 2.0 * (1.0 + 3.0) * (PI / 4.0).cos();
+        assert_close(silhouette.area, width * 4.0, 1e-4);
+        assert!(silhouette.centroid.distance(Vec3::Y) < 1e-4, "{:?}", silhouette.centroid);
+    }
+
+    #[test]
+    fn silhouette_of_overlapping_compound_counts_the_overlap_once() {
+        let collider = Collider::compound(vec![
+            (Vec3::ZERO, Quat::IDENTITY, Collider::cuboid(2.0, 2.0, 2.0)),
+            (Vec3::new(1.0, 1.0, 0.0), Quat::IDENTITY, Collider::cuboid(2.0, 2.0, 2.0)),
+        ]);
+        let silhouette = projected_silhouette(&collider, Vec3::Z);
+        assert_close(silhouette.area, 7.0, 1e-4);
+        let centroid = silhouette.centroid;
+        assert!(centroid.distance(Vec3::new(0.5, 0.5, 0.0)) < 1e-4, "{centroid:?}");
+    }
+
+    fn mesh_triangles(mesh: &Mesh) -> (Vec<Vec3>, Vec<[u32; 3]>) {
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+            panic!("mesh has no positions");
+        };
+        let vertices = positions.iter().map(|&p| Vec3::from(p)).collect();
+        let indices: Vec<u32> = mesh.indices().unwrap().iter().map(|i| i as u32).collect();
+        let triangles = indices.chunks_exact(3).map(|t| [t[0], t[1], t[2]]).collect();
+        (vertices, triangles)
+    }
+
+    #[test]
+    fn volume_of_icosahedron() {
+        let radius = 2.0;
+        let (vertices, triangles) = mesh_triangles(&create_icosphere_mesh(radius, 0));
+        // V = 5/12 (3 + √5) a³ with edge a = 4R / √(10 + 2√5).
+        let sqrt5 = 5.0_f32.sqrt();
+        let edge = 4.0 * radius / (10.0 + 2.0 * sqrt5).sqrt();
+        let expected = 5.0 / 12.0 * (3.0 + sqrt5) * edge.powi(3);
+        assert_close(trimesh_volume(&vertices, &triangles), expected, expected * 1e-5);
+    }
+
+    #[test]
+    fn volume_of_icosphere_approaches_the_sphere() {
+        let radius = 1.5;
+        let (vertices, triangles) = mesh_triangles(&create_icosphere_mesh(radius, 3));
+        let volume = trimesh_volume(&vertices, &triangles);
+        let sphere = sphere_volume(radius);
+        assert!(volume < sphere);
+        assert_close(volume, sphere, sphere * 0.01);
+    }
+
+    #[test]
+    fn volume_ignores_winding() {
+        let cuboid = avian3d::parry::shape::Cuboid::new(Vector::new(0.5, 1.0, 1.5));
+        let (points, indices) = cuboid.to_trimesh();
+        let vertices: Vec<Vec3> = points.into_iter().map(Into::into).collect();
+        assert_close(trimesh_volume(&vertices, &indices), 6.0, 1e-5);
+        let reversed: Vec<[u32; 3]> = indices.iter().map(|&[a, b, c]| [a, c, b]).collect();
+        assert_close(trimesh_volume(&vertices, &reversed), 6.0, 1e-5);
+    }
+}
@@ -0,0 +1,348 @@
+//! Flat cutting patterns for the gores of an envelope.
+//!
+//! Balloon envelopes are sewn or heat sealed from gores, long flat panels
+//! that run from the bottom to the top fitting. For an axisymmetric envelope
+//! made of `N` identical gores, each gore spans `1/N` of every horizontal
+//! ring, so its flat width at a distance `s` along the meridian is
+//! `2π r(s) / N`. The pattern lists that width along the meridian and adds a
+//! seam allowance outside the seam line for the overlap that joins
+//! neighboring gores.
+//!
+//! The meridian is read from the vertices of a mesh whose axis of symmetry is
+//! its local Y axis, or taken from a [`Meridian`] directly. Vertices are put
+//! in order by the angle they make around the middle of the axis, which
+//! works for convex envelopes such as spheres and natural shapes. Lobed
+//! shapes such as pumpkins are not axisymmetric and are rejected.
+//!
+//! Patterns export as CSV for cutting tables and as SVG, in millimeters, for
+//! plotters and cutters.
+
+use std::{
+    f32::consts::TAU,
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use bevy::prelude::*;
+
+use crate::envelope_shapes::Meridian;
+
+/// Largest spread in distance from the middle of the axis, relative to the
+/// largest radius, among vertices on the same ring of an axisymmetric mesh.
+const AXISYMMETRY_TOLERANCE: f32 = 0.02;
+
+/// Vertices closer than this in angle (rad), seen from the middle of the
+/// axis, are on the same ring.
+const RING_TOLERANCE: f32 = 1.0e-4;
+
+#[derive(Debug)]
+pub enum GoreError {
+    Io(io::Error),
+    /// A pattern needs at least three gores.
+    TooFewGores(usize),
+    /// The mesh has no vertex positions, or too few to span a meridian.
+    EmptyMesh,
+    /// Vertices on the same ring are at different distances from the axis.
+    NotAxisymmetric { height: f32, spread: f32 },
+}
+
+impl fmt::Display for GoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GoreError::Io(e) => write!(f, "could not write gore pattern: {}", e),
+            GoreError::TooFewGores(gores) => write!(f, "a gore pattern needs at least 3 gores, not {}", gores),
+            GoreError::EmptyMesh => write!(f, "the envelope mesh has no meridian to flatten"),
+            GoreError::NotAxisymmetric { height, spread } => write!(
+                f,
+                "the envelope is not axisymmetric: the ring at {:.3} m varies by {:.3} m",
+                height, spread
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoreError {}
+
+impl From<io::Error> for GoreError {
+    fn from(e: io::Error) -> Self {
+        GoreError::Io(e)
+    }
+}
+
+/// A station along the meridian of a gore.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GoreStation {
+    /// Distance (m) along the meridian from the bottom end of the gore.
+    pub arc_length: f32,
+    /// Height (m) of the station on the inflated envelope.
+    pub height: f32,
+    /// Distance (m) from the axis of the inflated envelope.
+    pub radius: f32,
+    /// Width (m) of the gore between its seam lines.
+    pub width: f32,
+}
+
+/// The flat cutting pattern shared by every gore of an envelope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GorePattern {
+    pub gores: usize,
+    /// Width (m) of material added outside each seam line.
+    pub seam_allowance: f32,
+    /// Stations from the bottom to the top of the gore.
+    pub stations: Vec<GoreStation>,
+}
+
+impl GorePattern {
+    /// The pattern for an envelope whose meridian passes through the given
+    /// radii (m) and heights (m), ordered from bottom to top.
+    pub fn from_profile(
+        profile: impl IntoIterator<Item = (f32, f32)>,
+        gores: usize,
+        seam_allowance: f32,
+    ) -> Result<Self, GoreError> {
+        if gores < 3 {
+            return Err(GoreError::TooFewGores(gores));
+        }
+        let mut stations: Vec<GoreStation> = Vec::new();
+        for (radius, height) in profile {
+            let radius = radius.max(0.0);
+            let arc_length = stations.last().map_or(0.0, |previous| {
+                previous.arc_length + Vec2::new(radius - previous.radius, height - previous.height).length()
+            });
+            stations.push(GoreStation {
+                arc_length,
+                height,
+                radius,
+                width: TAU * radius / gores as f32,
+            });
+        }
+        if stations.len() < 2 {
+            return Err(GoreError::EmptyMesh);
+        }
+        Ok(GorePattern {
+            gores,
+            seam_allowance: seam_allowance.max(0.0),
+            stations,
+        })
+    }
+
+    /// The pattern for a natural-shape meridian.
+    pub fn from_meridian(meridian: &Meridian, gores: usize, seam_allowance: f32) -> Result<Self, GoreError> {
+        GorePattern::from_profile(
+            meridian.points.iter().map(|p| (p.radius, p.height)),
+            gores,
+            seam_allowance,
+        )
+    }
+
+    /// The pattern for an envelope mesh, symmetric about its local Y axis.
+    pub fn from_mesh(mesh: &Mesh, gores: usize, seam_allowance: f32) -> Result<Self, GoreError> {
+        let positions = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .and_then(|positions| positions.as_float3())
+            .ok_or(GoreError::EmptyMesh)?;
+        let positions: Vec<Vec3> = positions.iter().map(|&p| p.into()).collect();
+        GorePattern::from_positions(&positions, gores, seam_allowance)
+    }
+
+    /// The pattern for the vertices (m) of an envelope, symmetric about the
+    /// Y axis.
+    pub fn from_positions(positions: &[Vec3], gores: usize, seam_allowance: f32) -> Result<Self, GoreError> {
+        let (min_height, max_height) = positions
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), p| (min.min(p.y), max.max(p.y)));
+        if positions.is_empty() {
+            return Err(GoreError::EmptyMesh);
+        }
+        let middle = 0.5 * (min_height + max_height);
+
+        // Each vertex as its distance from the axis and height, ordered by
+        // the angle it makes around the middle of the axis.
+        let mut points: Vec<(f32, Vec2)> = positions
+            .iter()
+            .map(|p| {
+                let point = Vec2::new(Vec2::new(p.x, p.z).length(), p.y);
+                ((point.y - middle).atan2(point.x), point)
+            })
+            .collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let max_radius = points.iter().map(|(_, p)| p.x).fold(0.0, f32::max);
+
+        // Vertices in the same direction are on the same ring, and must be
+        // the same distance from the middle.
+        let mut profile = Vec::new();
+        let mut start = 0;
+        while start < points.len() {
+            let mut end = start + 1;
+            while end < points.len() && points[end].0 - points[start].0 <= RING_TOLERANCE {
+                end += 1;
+            }
+            let ring = &points[start..end];
+            let (min, max) = ring.iter().fold((f32::INFINITY, 0.0_f32), |(min, max), (_, p)| {
+                let distance = Vec2::new(p.x, p.y - middle).length();
+                (min.min(distance), max.max(distance))
+            });
+            let mean = ring.iter().map(|(_, p)| *p).sum::<Vec2>() / ring.len() as f32;
+            if max - min > AXISYMMETRY_TOLERANCE * max_radius {
+                return Err(GoreError::NotAxisymmetric {
+                    height: mean.y,
+                    spread: max - min,
+                });
+            }
+            profile.push((mean.x, mean.y));
+            start = end;
+        }
+        GorePattern::from_profile(profile, gores, seam_allowance)
+    }
+
+    /// Length (m) of the gore along the meridian, between seam lines.
+    pub fn length(&self) -> f32 {
+        self.stations.last().map_or(0.0, |s| s.arc_length)
+    }
+
+    /// Widest point (m) of the gore between its seam lines.
+    pub fn max_width(&self) -> f32 {
+        self.stations.iter().map(|s| s.width).fold(0.0, f32::max)
+    }
+
+    /// Area (m²) of one gore between its seam lines.
+    pub fn area(&self) -> f32 {
+        self.stations
+            .windows(2)
+            .map(|pair| 0.5 * (pair[0].width + pair[1].width) * (pair[1].arc_length - pair[0].arc_length))
+            .sum()
+    }
+
+    /// Width (m) of the gore across its cut lines at each station. The seam
+    /// allowance is measured square to the seam line, so it widens the gore
+    /// more where the seam runs at an angle.
+    pub fn cut_widths(&self) -> Vec<f32> {
+        self.seam_normals()
+            .iter()
+            .zip(&self.stations)
+            .map(|(normal, station)| station.width + 2.0 * self.seam_allowance / normal.x.max(f32::EPSILON))
+            .collect()
+    }
+
+    /// Outward unit normal of the right seam line at each station, in the
+    /// plane of the pattern with x across and y along the gore.
+    fn seam_normals(&self) -> Vec<Vec2> {
+        let n = self.stations.len();
+        (0..n)
+            .map(|i| {
+                let (a, b) = (&self.stations[i.saturating_sub(1)], &self.stations[(i + 1).min(n - 1)]);
+                let tangent = Vec2::new(0.5 * (b.width - a.width), b.arc_length - a.arc_length);
+                let tangent = tangent.try_normalize().unwrap_or(Vec2::Y);
+                Vec2::new(tangent.y, -tangent.x)
+            })
+            .collect()
+    }
+
+    /// Right half of the seam line and of the cut line, from the bottom to
+    /// the top of the gore.
+    fn outlines(&self) -> (Vec<Vec2>, Vec<Vec2>) {
+        let seam: Vec<Vec2> = self
+            .stations
+            .iter()
+            .map(|s| Vec2::new(0.5 * s.width, s.arc_length))
+            .collect();
+        let mut cut = vec![Vec2::new(0.0, -self.seam_allowance)];
+        cut.extend(
+            seam.iter()
+                .zip(self.seam_normals())
+                .map(|(point, normal)| *point + self.seam_allowance * normal),
+        );
+        cut.push(Vec2::new(0.0, self.length() + self.seam_allowance));
+        (seam, cut)
+    }
+
+    /// Write the pattern as CSV, one row per station, in meters.
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "# gores: {}, seam allowance (m): {}", self.gores, self.seam_allowance)?;
+        writeln!(writer, "arc_length_m,height_m,radius_m,gore_width_m,cut_width_m")?;
+        for (station, cut_width) in self.stations.iter().zip(self.cut_widths()) {
+            writeln!(
+                writer,
+                "{:.6},{:.6},{:.6},{:.6},{:.6}",
+                station.arc_length, station.height, station.radius, station.width, cut_width
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write one gore as SVG in millimeters, bottom end down. The cut line is
+    /// solid, the seam line dashed and the center line dotted.
+    pub fn write_svg(&self, mut writer: impl Write) -> io::Result<()> {
+        const MM: f32 = 1000.0;
+        let margin = 10.0;
+        let (seam, cut) = self.outlines();
+        let half_width = cut.iter().map(|p| p.x).fold(0.0, f32::max) * MM;
+        let width = 2.0 * (half_width + margin);
+        let height = (self.length() + 2.0 * self.seam_allowance) * MM + 2.0 * margin;
+        // Pattern coordinates to SVG coordinates, which grow down.
+        let to_svg = |p: Vec2| {
+            Vec2::new(
+                half_width + margin + p.x * MM,
+                height - margin - (p.y + self.seam_allowance) * MM,
+            )
+        };
+        let polygon = |half: &[Vec2]| {
+            half.iter()
+                .map(|p| to_svg(*p))
+                .chain(half.iter().rev().map(|p| to_svg(Vec2::new(-p.x, p.y))))
+                .map(|p| format!("{:.2},{:.2}", p.x, p.y))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.2}mm" height="{h:.2}mm" viewBox="0 0 {w:.2} {h:.2}">"#,
+            w = width,
+            h = height
+        )?;
+        writeln!(
+            writer,
+            "  <title>Gore pattern: {} gores, {:.3} m long, {:.1} mm seam allowance</title>",
+            self.gores,
+            self.length(),
+            self.seam_allowance * MM
+        )?;
+        writeln!(
+            writer,
+            r#"  <polygon id="cut" points="{}" fill="none" stroke="black" stroke-width="0.5"/>"#,
+            polygon(&cut)
+        )?;
+        writeln!(
+            writer,
+            r#"  <polygon id="seam" points="{}" fill="none" stroke="red" stroke-width="0.3" stroke-dasharray="4 2"/>"#,
+            polygon(&seam)
+        )?;
+        let (bottom, top) = (to_svg(Vec2::ZERO), to_svg(Vec2::new(0.0, self.length())));
+        writeln!(
+            writer,
+            r#"  <line id="center" x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="gray" stroke-width="0.2" stroke-dasharray="1 2"/>"#,
+            bottom.x, bottom.y, top.x, top.y
+        )?;
+        writeln!(writer, "</svg>")
+    }
+
+    /// Save the pattern as CSV.
+    pub fn save_csv(&self, path: impl AsRef<Path>) -> Result<(), GoreError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_csv(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Save the pattern as SVG.
+    pub fn save_svg(&self, path: impl AsRef<Path>) -> Result<(), GoreError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_svg(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
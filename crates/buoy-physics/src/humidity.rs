@@ -0,0 +1,167 @@
+//! Water vapor in the atmosphere.
+//!
+//! Moist air is lighter than dry air at the same temperature and pressure
+//! because water vapor has a lower molar mass than the gas it displaces. The
+//! effect is folded into the ideal gas law through the virtual temperature,
+//! the temperature dry air would need to have the same density.
+//!
+//! Saturation vapor pressure follows the Buck (1996) equations over water and
+//! over ice.
+//!
+//! Reference:
+//! - https://en.wikipedia.org/wiki/Arden_Buck_equation
+//! - https://glossary.ametsoc.org/wiki/Virtual_temperature
+
+use serde::{Deserialize, Deserializer, Serialize};
+use uom::si::{
+    f32::*,
+    pressure::{hectopascal, pascal},
+    ratio::ratio,
+    thermodynamic_temperature::{degree_celsius, kelvin},
+};
+
+use crate::{ideal_gas::GasSpecies, units};
+
+/// Moisture content of the atmosphere as a function of altitude.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub enum Humidity {
+    /// No water vapor at all.
+    #[default]
+    Dry,
+    /// Relative humidity (0–1, with respect to liquid water) interpolated
+    /// from a profile sorted by altitude.
+    RelativeHumidity(Vec<HumidityLevel>),
+    /// Dewpoint temperature (K) interpolated from a profile sorted by
+    /// altitude.
+    Dewpoint(#[serde(deserialize_with = "dewpoint_profile")] Vec<HumidityLevel>),
+}
+
+/// One level of a humidity profile.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub struct HumidityLevel {
+    /// Altitude (m).
+    #[serde(deserialize_with = "units::length")]
+    pub altitude: f32,
+    /// Relative humidity (0–1) or dewpoint (K), depending on the profile.
+    #[serde(deserialize_with = "units::dimensionless")]
+    pub value: f32,
+}
+
+/// A dewpoint profile level as written in a config, whose value is a
+/// temperature rather than a ratio.
+#[derive(Deserialize)]
+struct DewpointLevel {
+    #[serde(deserialize_with = "units::length")]
+    altitude: f32,
+    #[serde(deserialize_with = "units::temperature")]
+    value: f32,
+}
+
+fn dewpoint_profile<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<HumidityLevel>, D::Error> {
+    let levels = Vec::<DewpointLevel>::deserialize(deserializer)?;
+    Ok(levels
+        .into_iter()
+        .map(|level| HumidityLevel {
+            altitude: level.altitude,
+            value: level.value,
+        })
+        .collect())
+}
+
+impl Humidity {
+    /// The same relative humidity (0–1) at every altitude.
+    pub fn constant_relative_humidity(relative_humidity: f32) -> Self {
+        Humidity::RelativeHumidity(vec![HumidityLevel {
+            altitude: 0.0,
+            value: relative_humidity,
+        }])
+    }
+
+    /// Partial pressure (Pa) of water vapor at an altitude (m) where the air
+    /// has the given temperature. Never exceeds saturation.
+    pub fn vapor_pressure(&self, altitude: f32, temperature: ThermodynamicTemperature) -> Pressure {
+        let saturation = saturation_vapor_pressure(temperature);
+        match self {
+            Humidity::Dry => Pressure::default(),
+            Humidity::RelativeHumidity(profile) => {
+                saturation * interpolate(profile, altitude).clamp(0.0, 1.0)
+            }
+            Humidity::Dewpoint(profile) => {
+                let dewpoint = ThermodynamicTemperature::new::<kelvin>(interpolate(profile, altitude));
+                saturation_vapor_pressure(dewpoint).min(saturation)
+            }
+        }
+    }
+}
+
+/// Linear interpolation of a humidity profile, held constant beyond its ends.
+fn interpolate(profile: &[HumidityLevel], altitude: f32) -> f32 {
+    match profile {
+        [] => 0.0,
+        [only] => only.value,
+        _ => {
+            let upper = profile
+                .partition_point(|level| level.altitude <= altitude)
+                .clamp(1, profile.len() - 1);
+            let (a, b) = (profile[upper - 1], profile[upper]);
+            let span = b.altitude - a.altitude;
+            if span <= f32::EPSILON {
+                return a.value;
+            }
+            let t = ((altitude - a.altitude) / span).clamp(0.0, 1.0);
+            a.value + (b.value - a.value) * t
+        }
+    }
+}
+
+/// Saturation vapor pressure (Pa) over a flat surface of liquid water. Valid
+/// from -80 °C to 50 °C, including supercooled water.
+pub fn saturation_vapor_pressure(temperature: ThermodynamicTemperature) -> Pressure {
+    let t = temperature.get::<degree_celsius>();
+    Pressure::new::<hectopascal>(6.1121 * f32::exp((18.678 - t / 234.5) * (t / (257.14 + t))))
+}
+
+/// Saturation vapor pressure (Pa) over a flat surface of ice. Valid from
+/// -80 °C to 0 °C.
+pub fn saturation_vapor_pressure_over_ice(temperature: ThermodynamicTemperature) -> Pressure {
+    let t = temperature.get::<degree_celsius>();
+    Pressure::new::<hectopascal>(6.1115 * f32::exp((23.036 - t / 333.7) * (t / (279.82 + t))))
+}
+
+/// Dewpoint (K) of air with the given water vapor pressure (Pa), the inverse
+/// of [`saturation_vapor_pressure`].
+pub fn dewpoint(vapor_pressure: Pressure) -> ThermodynamicTemperature {
+    // Buck's equation is quadratic in temperature once the log is taken.
+    let l = f32::ln(vapor_pressure.get::<hectopascal>().max(f32::MIN_POSITIVE) / 6.1121);
+    let b = 18.678 - l;
+    let t = (b - f32::sqrt(b * b - 4.0 / 234.5 * 257.14 * l)) * 234.5 / 2.0;
+    ThermodynamicTemperature::new::<degree_celsius>(t)
+}
+
+/// Virtual temperature (K) of moist air: the temperature at which dry air of
+/// the given composition would have the same density at the same pressure.
+pub fn virtual_temperature(
+    temperature: ThermodynamicTemperature,
+    pressure: Pressure,
+    vapor_pressure: Pressure,
+    dry_air: &GasSpecies,
+) -> ThermodynamicTemperature {
+    if pressure.get::<pascal>() <= 0.0 {
+        return temperature;
+    }
+    let epsilon = (GasSpecies::water_vapor().molar_mass / dry_air.molar_mass).get::<ratio>();
+    let vapor_fraction = (vapor_pressure / pressure).get::<ratio>().clamp(0.0, 1.0);
+    ThermodynamicTemperature::new::<kelvin>(
+        temperature.get::<kelvin>() / (1.0 - vapor_fraction * (1.0 - epsilon)),
+    )
+}
+
+/// Water vapor mixing ratio (kg of vapor per kg of dry air).
+pub fn mixing_ratio(pressure: Pressure, vapor_pressure: Pressure, dry_air: &GasSpecies) -> f32 {
+    let epsilon = (GasSpecies::water_vapor().molar_mass / dry_air.molar_mass).get::<ratio>();
+    let dry_pressure = (pressure - vapor_pressure).get::<pascal>();
+    if dry_pressure <= 0.0 {
+        return 0.0;
+    }
+    epsilon * vapor_pressure.get::<pascal>() / dry_pressure
+}
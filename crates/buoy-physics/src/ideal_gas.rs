@@ -22,6 +22,7 @@ use uom::si::{
 use crate::{
     constants::{GAS_CONSTANT, STANDARD_GRAVITY},
     geometry::sphere_volume,
+    units,
 };
 
 pub(crate) fn plugin(_app: &mut App) {
@@ -39,6 +40,17 @@ pub fn ideal_gas_volume(
     (mass / species.molar_mass) * *GAS_CONSTANT * temperature / pressure
 }
 
+/// Pressure (Pa) of an ideal gas from its temperature (K), volume (m³),
+/// mass (kg) and molar mass (kg/mol).
+pub fn ideal_gas_pressure(
+    temperature: ThermodynamicTemperature,
+    volume: Volume,
+    mass: Mass,
+    species: &GasSpecies,
+) -> Pressure {
+    (mass / species.molar_mass) * *GAS_CONSTANT * temperature / volume
+}
+
 /// Density (kg/m³) of an ideal gas from its temperature (K), pressure (Pa),
 /// and molar mass (kg/mol)
 pub fn ideal_gas_density(
@@ -75,6 +87,22 @@ impl GasSpecies {
         }
     }
 
+    pub fn hydrogen() -> Self {
+        GasSpecies {
+            name: "Hydrogen".to_string(),
+            abbreviation: "H2".to_string(),
+            molar_mass: MolarMass::new::<kilogram_per_mole>(0.00201594),
+        }
+    }
+
+    pub fn water_vapor() -> Self {
+        GasSpecies {
+            name: "Water vapor".to_string(),
+            abbreviation: "H2O".to_string(),
+            molar_mass: MolarMass::new::<kilogram_per_mole>(0.01801528),
+        }
+    }
+
     pub fn new(name: String, abbreviation: String, molar_mass: MolarMass) -> Self {
         GasSpecies {
             name,
@@ -83,15 +111,29 @@ impl GasSpecies {
         }
     }
 
-    pub fn from_species_name(name: String) -> Self {
-        match name.as_str() {
-            "air" => GasSpecies::air(),
-            "helium" => GasSpecies::helium(),
-            _ => panic!("Unknown gas species: {}", name),
-        }
+    /// A species by its name or abbreviation, ignoring case.
+    pub fn from_species_name(name: String) -> Result<Self, UnknownGasSpecies> {
+        [GasSpecies::air(), GasSpecies::helium(), GasSpecies::hydrogen()]
+            .into_iter()
+            .find(|species| {
+                species.name.eq_ignore_ascii_case(&name) || species.abbreviation.eq_ignore_ascii_case(&name)
+            })
+            .ok_or(UnknownGasSpecies(name))
     }
 }
 
+/// A gas species name that matches none of the known species.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownGasSpecies(pub String);
+
+impl std::fmt::Display for UnknownGasSpecies {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown gas species {:?}, expected air, helium or hydrogen", self.0)
+    }
+}
+
+impl std::error::Error for UnknownGasSpecies {}
+
 impl Default for GasSpecies {
     fn default() -> Self {
         GasSpecies::helium()
@@ -102,6 +144,7 @@ impl Default for GasSpecies {
 pub struct GasSpeciesConfig {
     pub name: String,
     pub abbreviation: String,
+    #[serde(deserialize_with = "units::molar_mass")]
     pub molar_mass: f32, // [kg/mol]
 }
 
@@ -0,0 +1,292 @@
+//! Inflation planning for launch crews.
+//!
+//! Crews fill a balloon until it lifts a given weight at the neck rather than
+//! weighing the gas. The lifts are related by
+//!
+//! - gross lift: mass of the air displaced by the gas, less the gas itself,
+//! - neck lift: gross lift less the balloon, what a weight on the filler
+//!   neck balances,
+//! - free lift: neck lift less the payload, what is left to accelerate the
+//!   whole flight train upward.
+//!
+//! Lifts are expressed as masses (kg), as they are weighed. The gas is taken
+//! to be at the ambient temperature and pressure, as in a slack or latex
+//! balloon without superpressure.
+
+use std::fmt;
+
+use bevy::prelude::*;
+use uom::si::{
+    acceleration::meter_per_second_squared,
+    area::square_meter,
+    f32::{Acceleration, Area, Length, Mass, MassDensity, Pressure, ThermodynamicTemperature, Velocity, Volume},
+    length::meter,
+    mass::kilogram,
+    velocity::meter_per_second,
+    volume::cubic_meter,
+};
+
+use crate::{
+    atmosphere::Atmosphere,
+    clock::Epoch,
+    forces::drag,
+    geometry::{sphere_radius_from_volume, sphere_volume},
+    ideal_gas::{ideal_gas_density, ideal_gas_volume, GasSpecies, IdealGas},
+    planet::PlanetaryBody,
+};
+
+/// Iterations of the bisections, enough to resolve f32 values.
+const BISECTION_STEPS: usize = 64;
+
+/// Ambient conditions where the balloon is filled.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LaunchConditions {
+    pub temperature: ThermodynamicTemperature,
+    pub pressure: Pressure,
+    /// Density of the ambient air, including any water vapor.
+    pub density: MassDensity,
+    pub gravity: Acceleration,
+}
+
+impl LaunchConditions {
+    /// Conditions in an atmosphere at a position and time, under the
+    /// gravity of the body the atmosphere belongs to.
+    pub fn at(atmosphere: &Atmosphere, planet: &PlanetaryBody, position: Vec3, epoch: Epoch) -> Self {
+        LaunchConditions {
+            temperature: atmosphere.temperature(position, epoch),
+            pressure: atmosphere.pressure(position, epoch),
+            density: atmosphere.density(position, epoch),
+            gravity: planet.gravity(Length::new::<meter>(position.y)),
+        }
+    }
+}
+
+/// What a balloon is filled to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InflationTarget {
+    /// Free lift (kg).
+    FreeLift(Mass),
+    /// Neck lift (kg).
+    NeckLift(Mass),
+    /// Steady ascent rate (m/s) right after launch.
+    AscentRate(Velocity),
+}
+
+/// The flight train being inflated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlightTrain {
+    pub species: GasSpecies,
+    pub balloon_mass: Mass,
+    /// Everything hung below the balloon, ballast included.
+    pub payload_mass: Mass,
+    pub drag_coefficient: f32,
+}
+
+/// How much gas to fill and how the balloon then lifts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Inflation {
+    pub gas_mass: Mass,
+    /// Volume of the gas at launch.
+    pub volume: Volume,
+    /// Radius of the inflated balloon, taken as a sphere.
+    pub radius: Length,
+    pub gross_lift: Mass,
+    pub neck_lift: Mass,
+    pub free_lift: Mass,
+    /// Steady ascent rate right after launch.
+    pub ascent_rate: Velocity,
+}
+
+/// Why a balloon cannot be inflated to a target.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InflationError {
+    /// The lift gas is no lighter than the air around it.
+    NoBuoyancy(GasSpecies),
+    /// The target is negative or not a number.
+    InvalidTarget(InflationTarget),
+    /// No amount of gas reaches the target.
+    Unreachable(InflationTarget),
+}
+
+impl fmt::Display for InflationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InflationError::NoBuoyancy(species) => {
+                write!(f, "{} is no lighter than the ambient air", species.name)
+            }
+            InflationError::InvalidTarget(target) => write!(f, "invalid inflation target {target:?}"),
+            InflationError::Unreachable(target) => write!(f, "no amount of gas reaches {target:?}"),
+        }
+    }
+}
+
+impl std::error::Error for InflationError {}
+
+impl FlightTrain {
+    /// The lifts and ascent rate with a given mass of gas.
+    pub fn with_gas(&self, gas_mass: Mass, conditions: &LaunchConditions) -> Inflation {
+        let volume = ideal_gas_volume(conditions.temperature, conditions.pressure, gas_mass, &self.species);
+        let gross_lift = volume * conditions.density - gas_mass;
+        let neck_lift = gross_lift - self.balloon_mass;
+        let free_lift = neck_lift - self.payload_mass;
+        let radius = sphere_radius_from_volume(volume.get::<cubic_meter>());
+        Inflation {
+            gas_mass,
+            volume,
+            radius: Length::new::<meter>(radius),
+            gross_lift,
+            neck_lift,
+            free_lift,
+            ascent_rate: self.ascent_rate(free_lift, radius, conditions),
+        }
+    }
+
+    /// Steady ascent rate where drag balances the free lift.
+    fn ascent_rate(&self, free_lift: Mass, radius: f32, conditions: &LaunchConditions) -> Velocity {
+        let lift = free_lift.get::<kilogram>() * conditions.gravity.get::<meter_per_second_squared>();
+        if lift <= 0.0 {
+            return Velocity::new::<meter_per_second>(0.0);
+        }
+        // Drag grows with the square of airspeed, so its value at 1 m/s
+        // scales the balance directly.
+        let area = Area::new::<square_meter>(std::f32::consts::PI * radius * radius);
+        let unit_drag = drag(Vec3::Y, conditions.density, area, self.drag_coefficient).length();
+        Velocity::new::<meter_per_second>((lift / unit_drag).sqrt())
+    }
+
+    /// Gas mass needed to reach a target, and the lifts that result.
+    pub fn inflate(&self, target: InflationTarget, conditions: &LaunchConditions) -> Result<Inflation, InflationError> {
+        let gas_density = ideal_gas_density(conditions.temperature, conditions.pressure, &self.species);
+        // Each kilogram of gas lifts this many kilograms besides itself.
+        let lift_per_gas = (conditions.density / gas_density).value - 1.0;
+        if lift_per_gas.is_nan() || lift_per_gas <= 0.0 {
+            return Err(InflationError::NoBuoyancy(self.species.clone()));
+        }
+        let gas_for_neck_lift = |neck_lift: f32| (neck_lift + self.balloon_mass.get::<kilogram>()) / lift_per_gas;
+        let gas_mass = match target {
+            InflationTarget::FreeLift(free_lift) => {
+                let free_lift = free_lift.get::<kilogram>();
+                if !(free_lift.is_finite() && free_lift >= 0.0) {
+                    return Err(InflationError::InvalidTarget(target));
+                }
+                gas_for_neck_lift(free_lift + self.payload_mass.get::<kilogram>())
+            }
+            InflationTarget::NeckLift(neck_lift) => {
+                let neck_lift = neck_lift.get::<kilogram>();
+                if !(neck_lift.is_finite() && neck_lift >= 0.0) {
+                    return Err(InflationError::InvalidTarget(target));
+                }
+                gas_for_neck_lift(neck_lift)
+            }
+            InflationTarget::AscentRate(rate) => {
+                let rate = rate.get::<meter_per_second>();
+                if !(rate.is_finite() && rate >= 0.0) {
+                    return Err(InflationError::InvalidTarget(target));
+                }
+                self.gas_for_ascent_rate(rate, gas_for_neck_lift(self.payload_mass.get::<kilogram>()), conditions)
+                    .ok_or(InflationError::Unreachable(target))?
+            }
+        };
+        Ok(self.with_gas(Mass::new::<kilogram>(gas_mass), conditions))
+    }
+
+    /// Gas mass (kg) that rises at a rate (m/s), searching upward from the
+    /// gas mass with no free lift. The rate keeps growing with the gas mass,
+    /// if only as its sixth root.
+    fn gas_for_ascent_rate(&self, rate: f32, neutral: f32, conditions: &LaunchConditions) -> Option<f32> {
+        let rate_with = |gas: f32| {
+            self.with_gas(Mass::new::<kilogram>(gas), conditions)
+                .ascent_rate
+                .get::<meter_per_second>()
+        };
+        let (mut low, mut high) = (neutral, neutral.max(1e-3) * 2.0);
+        let mut doublings = 0;
+        while rate_with(high) < rate {
+            low = high;
+            high *= 2.0;
+            doublings += 1;
+            if doublings > BISECTION_STEPS || !high.is_finite() {
+                return None;
+            }
+        }
+        for _ in 0..BISECTION_STEPS {
+            let middle = 0.5 * (low + high);
+            if rate_with(middle) < rate {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        Some(high)
+    }
+}
+
+/// Altitude (m) at which a balloon reaches its burst diameter (m) while
+/// rising from a launch altitude (m), or `None` if it would float below the
+/// top of the atmosphere model.
+pub fn burst_altitude(
+    gas: &IdealGas,
+    burst_diameter: Length,
+    atmosphere: &Atmosphere,
+    launch_altitude: Length,
+    epoch: Epoch,
+) -> Option<Length> {
+    let burst_volume = sphere_volume(0.5 * burst_diameter.get::<meter>());
+    let volume_at = |altitude: f32| {
+        let position = Vec3::Y * altitude;
+        ideal_gas_volume(
+            atmosphere.temperature(position, epoch),
+            atmosphere.pressure(position, epoch),
+            gas.mass,
+            &gas.species,
+        )
+        .get::<cubic_meter>()
+    };
+    let mut low = launch_altitude.get::<meter>().max(Atmosphere::MIN_ALTITUDE);
+    let mut high = Atmosphere::MAX_ALTITUDE;
+    if volume_at(low) >= burst_volume {
+        return Some(Length::new::<meter>(low));
+    }
+    if volume_at(high) < burst_volume {
+        return None;
+    }
+    for _ in 0..BISECTION_STEPS {
+        let middle = 0.5 * (low + high);
+        if volume_at(middle) < burst_volume {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    Some(Length::new::<meter>(high))
+}
+
+/// Burst diameter (m) of a latex sounding balloon from its mass (kg),
+/// interpolated from typical manufacturer data.
+pub fn latex_burst_diameter(balloon_mass: Mass) -> Length {
+    const TABLE: [(f32, f32); 12] = [
+        (0.1, 1.96),
+        (0.2, 3.0),
+        (0.3, 3.78),
+        (0.35, 4.12),
+        (0.5, 4.99),
+        (0.6, 6.02),
+        (0.8, 7.0),
+        (1.0, 7.86),
+        (1.2, 8.63),
+        (1.5, 9.44),
+        (2.0, 10.54),
+        (3.0, 13.0),
+    ];
+    let mass = balloon_mass.get::<kilogram>();
+    let diameter = match TABLE.iter().position(|&(m, _)| mass <= m) {
+        Some(0) => TABLE[0].1 * (mass / TABLE[0].0).max(0.0).cbrt(),
+        Some(i) => {
+            let ((m0, d0), (m1, d1)) = (TABLE[i - 1], TABLE[i]);
+            d0 + (d1 - d0) * (mass - m0) / (m1 - m0)
+        }
+        // Burst volume grows about in proportion to the mass of latex.
+        None => TABLE[TABLE.len() - 1].1 * (mass / TABLE[TABLE.len() - 1].0).cbrt(),
+    };
+    Length::new::<meter>(diameter)
+}
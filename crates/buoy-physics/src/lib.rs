@@ -1,13 +1,45 @@
 #![allow(unused_imports)]
 pub mod atmosphere;
+pub mod climate;
+pub mod clock;
 pub mod constants;
+pub mod envelope;
+pub mod envelope_shapes;
+pub mod failure;
+pub mod fluid_volume;
 pub mod geometry;
+pub mod gore;
+pub mod humidity;
 pub mod ideal_gas;
+pub mod inflation;
+pub mod material_properties;
+pub mod membrane;
+pub mod mesh_io;
+pub mod mesh_utils;
 pub mod forces;
+pub mod planet;
+pub mod strain_field;
+pub mod turbulence;
+pub mod units;
+pub mod water;
 pub mod prelude {
     pub use crate::{
-        atmosphere::Atmosphere,
+        atmosphere::{Atmosphere, AtmosphereModel},
+        climate::Climate,
+        clock::{Epoch, SimulationClock},
+        envelope::Envelope,
+        envelope_shapes::{NaturalShape, PumpkinShape},
+        failure::{EnvelopeFailure, Failed, FailureKind},
+        fluid_volume::FluidGrid,
+        gore::GorePattern,
+        humidity::Humidity,
         ideal_gas::{GasSpecies, IdealGas},
+        material_properties::Skin,
+        membrane::MembraneStress,
+        planet::PlanetaryBody,
+        strain_field::{FieldQuantity, StrainField},
+        turbulence::{ConvectiveCells, Gust, Turbulence},
+        water::{Medium, Water},
     };
 }
 
@@ -23,9 +55,17 @@ impl Plugin for BuoyPhysicsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             PhysicsPlugins::default(),
+            clock::plugin,
             atmosphere::plugin,
             ideal_gas::plugin,
+            planet::plugin,
             forces::plugin,
+            mesh_utils::plugin,
+            envelope::plugin,
+            membrane::plugin,
+            failure::plugin,
+            strain_field::plugin,
+            turbulence::plugin,
         ));
     }
 }
@@ -1,9 +1,16 @@
+use bevy::prelude::*;
+
 /// The skin is the material that composes the outer surface of the balloon.
-/// TODO: Implement multiple material types, such as latex, polyurethane, etc.
+///
+/// The scalar properties hold at room temperature. Where `tables` has a
+/// curve for a property, [`Skin::at`] looks the property up at the film
+/// temperature instead.
 #[derive(Debug, Clone, Reflect)]
 pub struct Skin {
     // temperature (K) where the given material fails
     pub max_temperature: f32,
+    // temperature (K) below which the material is brittle and fails
+    pub min_temperature: f32,
     // density (kg/m³) of the envelope material
     pub density: f32,
     // how much thermal radiation is emitted
@@ -24,12 +31,15 @@ pub struct Skin {
     pub max_stress: f32,
     // thickness of the envelope material (m)
     pub thickness: f32,
+    // properties that vary with temperature
+    pub tables: SkinTables,
 }
 
 impl Default for Skin {
     fn default() -> Self {
         Skin {
             max_temperature: 373.0,
+            min_temperature: 0.0,
             density: 920.0,
             emissivity: 0.9,
             absorptivity: 0.9,
@@ -40,6 +50,205 @@ impl Default for Skin {
             max_strain: 0.8,
             max_stress: 0.5e6,
             thickness: 0.0001,
+            tables: SkinTables::default(),
+        }
+    }
+}
+
+impl Skin {
+    /// Natural rubber latex, as used for sounding balloons. It stiffens as
+    /// it cools toward its glass transition near -70 °C.
+    pub fn latex() -> Self {
+        Skin {
+            max_temperature: 373.0,
+            min_temperature: 203.0,
+            density: 930.0,
+            emissivity: 0.9,
+            absorptivity: 0.9,
+            thermal_conductivity: 0.13,
+            specific_heat: 1880.0,
+            poissons_ratio: 0.49,
+            elasticity: 1.5e6,
+            max_strain: 7.0,
+            max_stress: 25e6,
+            thickness: 0.0002,
+            tables: SkinTables {
+                elasticity: Some(TemperatureTable::new(vec![
+                    (203.0, 200e6),
+                    (213.0, 30e6),
+                    (223.0, 6e6),
+                    (243.0, 2.5e6),
+                    (293.0, 1.5e6),
+                    (343.0, 1.3e6),
+                ])),
+                max_strain: Some(TemperatureTable::new(vec![
+                    (203.0, 0.5),
+                    (213.0, 2.0),
+                    (223.0, 4.5),
+                    (243.0, 6.0),
+                    (293.0, 7.0),
+                    (343.0, 6.0),
+                ])),
+                max_stress: Some(TemperatureTable::new(vec![
+                    (203.0, 30e6),
+                    (243.0, 28e6),
+                    (293.0, 25e6),
+                    (343.0, 15e6),
+                ])),
+            },
+        }
+    }
+
+    /// Low density polyethylene film, as used for zero-pressure balloons. It
+    /// stays ductile far colder than latex but grows stiffer and less
+    /// extensible, and turns brittle near -100 °C.
+    pub fn polyethylene() -> Self {
+        Skin {
+            max_temperature: 378.0,
+            min_temperature: 173.0,
+            density: 920.0,
+            emissivity: 0.1,
+            absorptivity: 0.1,
+            thermal_conductivity: 0.33,
+            specific_heat: 2300.0,
+            poissons_ratio: 0.45,
+            elasticity: 0.2e9,
+            max_strain: 5.0,
+            max_stress: 20e6,
+            thickness: 25e-6,
+            tables: SkinTables {
+                elasticity: Some(TemperatureTable::new(vec![
+                    (173.0, 2.0e9),
+                    (203.0, 1.0e9),
+                    (233.0, 0.5e9),
+                    (293.0, 0.2e9),
+                    (353.0, 0.08e9),
+                ])),
+                max_strain: Some(TemperatureTable::new(vec![
+                    (173.0, 0.2),
+                    (203.0, 2.0),
+                    (233.0, 3.5),
+                    (293.0, 5.0),
+                    (353.0, 6.0),
+                ])),
+                max_stress: Some(TemperatureTable::new(vec![
+                    (173.0, 45e6),
+                    (203.0, 38e6),
+                    (233.0, 30e6),
+                    (293.0, 20e6),
+                    (353.0, 10e6),
+                ])),
+            },
+        }
+    }
+
+    /// The skin with its tabulated properties looked up at a film
+    /// temperature (K).
+    pub fn at(&self, temperature: f32) -> Skin {
+        let lookup = |table: &Option<TemperatureTable>, value: f32| {
+            table.as_ref().map_or(value, |table| table.value(temperature))
+        };
+        Skin {
+            elasticity: lookup(&self.tables.elasticity, self.elasticity),
+            max_strain: lookup(&self.tables.max_strain, self.max_strain),
+            max_stress: lookup(&self.tables.max_stress, self.max_stress),
+            ..self.clone()
+        }
+    }
+
+    /// Properties that no real material could have, as the name of the
+    /// field and what is wrong with it.
+    pub fn problems(&self) -> Vec<(&'static str, String)> {
+        let mut problems = Vec::new();
+        let mut positive = |field: &'static str, value: f32| {
+            if !(value.is_finite() && value > 0.0) {
+                problems.push((field, format!("must be positive, got {value}")));
+            }
+        };
+        positive("density", self.density);
+        positive("thermal_conductivity", self.thermal_conductivity);
+        positive("specific_heat", self.specific_heat);
+        positive("elasticity", self.elasticity);
+        positive("max_strain", self.max_strain);
+        positive("max_stress", self.max_stress);
+        positive("thickness", self.thickness);
+        positive("max_temperature", self.max_temperature);
+        for (field, value) in [("emissivity", self.emissivity), ("absorptivity", self.absorptivity)] {
+            if !(0.0..=1.0).contains(&value) {
+                problems.push((field, format!("must be between 0 and 1, got {value}")));
+            }
+        }
+        if !(self.poissons_ratio > -1.0 && self.poissons_ratio <= 0.5) {
+            problems.push((
+                "poissons_ratio",
+                format!("must be above -1 and at most 0.5, got {}", self.poissons_ratio),
+            ));
+        }
+        if !(self.min_temperature >= 0.0 && self.min_temperature < self.max_temperature) {
+            problems.push((
+                "min_temperature",
+                format!(
+                    "must be at least 0 K and below max_temperature ({} K), got {}",
+                    self.max_temperature, self.min_temperature
+                ),
+            ));
+        }
+        problems
+    }
+
+    /// Flexural rigidity (N·m) of the skin, its resistance to bending as a
+    /// thin plate.
+    pub fn flexural_rigidity(&self) -> f32 {
+        self.elasticity * self.thickness.powi(3) / (12.0 * (1.0 - self.poissons_ratio.powi(2)))
+    }
+}
+
+/// Properties of a [`Skin`] that vary with temperature. A missing table
+/// leaves the room temperature value in place.
+#[derive(Debug, Clone, Default, Reflect)]
+pub struct SkinTables {
+    /// Young's modulus (Pa).
+    pub elasticity: Option<TemperatureTable>,
+    /// Elongation at failure (decimal, unitless).
+    pub max_strain: Option<TemperatureTable>,
+    /// Tangential stress at failure (Pa).
+    pub max_stress: Option<TemperatureTable>,
+}
+
+/// A property tabulated against temperature, interpolated linearly between
+/// entries and held constant beyond the ends of the table.
+#[derive(Debug, Clone, Default, PartialEq, Reflect)]
+pub struct TemperatureTable {
+    /// Temperature (K) and value pairs, in ascending order of temperature.
+    points: Vec<(f32, f32)>,
+}
+
+impl TemperatureTable {
+    /// A table from temperature (K) and value pairs in any order.
+    pub fn new(mut points: Vec<(f32, f32)>) -> Self {
+        points.retain(|(temperature, value)| temperature.is_finite() && value.is_finite());
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        TemperatureTable { points }
+    }
+
+    pub fn points(&self) -> &[(f32, f32)] {
+        &self.points
+    }
+
+    /// The value at a temperature (K).
+    pub fn value(&self, temperature: f32) -> f32 {
+        let Some(&(first_temperature, first_value)) = self.points.first() else {
+            return 0.0;
+        };
+        if temperature <= first_temperature {
+            return first_value;
+        }
+        for pair in self.points.windows(2) {
+            let [(t0, v0), (t1, v1)] = [pair[0], pair[1]];
+            if temperature <= t1 {
+                return if t1 > t0 { v0 + (v1 - v0) * (temperature - t0) / (t1 - t0) } else { v1 };
+            }
         }
+        self.points[self.points.len() - 1].1
     }
 }
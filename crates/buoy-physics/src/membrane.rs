@@ -0,0 +1,281 @@
+//! Membrane stress in the balloon envelope.
+//!
+//! Edge strain says how far the mesh has stretched, but not how hard the skin
+//! is loaded. Here every face is a constant strain triangle (CST), the
+//! simplest membrane finite element: the deformation is uniform across the
+//! face, so its strain and stress are too.
+//!
+//! For each face:
+//!
+//! 1. The rest triangle is laid flat in its own plane. The deformation
+//!    gradient maps its edges onto the current, deformed edges.
+//! 2. The Green-Lagrange strain follows from the deformation gradient, which
+//!    stays exact for the large stretches of a latex envelope.
+//! 3. An isotropic, linear elastic material in plane stress (Saint
+//!    Venant-Kirchhoff) gives the second Piola-Kirchhoff stress from the
+//!    strain, using the skin's elastic modulus at the film temperature and
+//!    its Poisson's ratio.
+//! 4. Pushing that stress forward onto the deformed, thinned face gives the
+//!    true (Cauchy) stress. Its eigenvalues are the principal stresses.
+//!    Thinning conserves volume for an incompressible skin like latex.
+//!
+//! The von Mises stress of the principal stresses is compared against the
+//! skin's stress at failure. A margin of safety of zero means the face is at
+//! its limit and a negative margin means it has failed.
+//!
+//! A membrane cannot carry compression, so a negative principal stress means
+//! the face would wrinkle. It is reported as is.
+//!
+//! Reference:
+//! - Cook et al. (2002), Concepts and Applications of Finite Element
+//!   Analysis, 4th ed., chapter 7
+//! - Bonet & Wood (2008), Nonlinear Continuum Mechanics for Finite Element
+//!   Analysis, 2nd ed., chapter 6
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    envelope::{simulate_envelopes, Envelope},
+    material_properties::Skin,
+};
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        update_membrane_stress
+            .after(simulate_envelopes)
+            .in_set(PhysicsStepSet::First),
+    );
+}
+
+/// Stress state of one face of the envelope.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect)]
+pub struct FaceStress {
+    /// Larger principal stress (Pa), positive in tension.
+    pub max_principal: f32,
+    /// Smaller principal stress (Pa), positive in tension.
+    pub min_principal: f32,
+    /// Von Mises equivalent stress (Pa).
+    pub von_mises: f32,
+    /// Larger principal engineering strain.
+    pub max_strain: f32,
+    /// Smaller principal engineering strain.
+    pub min_strain: f32,
+    /// Ratio of the deformed to the rest area of the face.
+    pub area_ratio: f32,
+}
+
+impl FaceStress {
+    /// Stress in a face given its rest and current vertex positions (m).
+    pub fn from_triangle(rest: [Vec3; 3], current: [Vec3; 3], skin: &Skin) -> Self {
+        let Some(cauchy_green) = right_cauchy_green(rest, current) else {
+            return FaceStress::default();
+        };
+        let area_ratio = cauchy_green.determinant().max(0.0).sqrt();
+        let (max_stretch, min_stretch) = eigenvalues(cauchy_green);
+
+        // Green-Lagrange strain E = (C - I) / 2 and the plane stress
+        // constitutive law S = D : E.
+        let strain = (cauchy_green - Mat2::IDENTITY) * 0.5;
+        let (exx, eyy, exy) = (strain.x_axis.x, strain.y_axis.y, strain.y_axis.x);
+        let nu = skin.poissons_ratio;
+        let modulus = skin.elasticity / (1.0 - nu * nu);
+        let sxx = modulus * (exx + nu * eyy);
+        let syy = modulus * (eyy + nu * exx);
+        let sxy = modulus * (1.0 - nu) * exy;
+        let second_piola_kirchhoff = Mat2::from_cols(Vec2::new(sxx, sxy), Vec2::new(sxy, syy));
+
+        // The Cauchy stress σ = F S Fᵀ / J has the same eigenvalues as S C / J.
+        // The volume ratio J is the area ratio times the thickness ratio. The
+        // skin thins as A^(-ν/(1-ν)), which matches the plane stress
+        // condition for small strains and conserves volume when ν = 0.5.
+        let thickness_ratio = area_ratio.powf(-nu / (1.0 - nu).max(f32::EPSILON));
+        let volume_ratio = (area_ratio * thickness_ratio).max(f32::EPSILON);
+        let (max_principal, min_principal) =
+            eigenvalues(second_piola_kirchhoff * cauchy_green / volume_ratio);
+
+        FaceStress {
+            max_principal,
+            min_principal,
+            von_mises: von_mises(max_principal, min_principal),
+            max_strain: max_stretch.max(0.0).sqrt() - 1.0,
+            min_strain: min_stretch.max(0.0).sqrt() - 1.0,
+            area_ratio,
+        }
+    }
+
+    /// Margin of safety against the stress at failure (Pa): the ratio of
+    /// allowable to actual stress minus one.
+    pub fn margin(&self, max_stress: f32) -> f32 {
+        margin_of_safety(max_stress, self.von_mises)
+    }
+}
+
+/// Right Cauchy-Green deformation tensor C = FᵀF of a face, in a basis of
+/// the plane of its rest triangle, given its rest and current vertex
+/// positions (m). None if the rest triangle is degenerate.
+fn right_cauchy_green(rest: [Vec3; 3], current: [Vec3; 3]) -> Option<Mat2> {
+    // Lay the rest triangle flat in a basis of its own plane.
+    let rest_u = rest[1] - rest[0];
+    let rest_v = rest[2] - rest[0];
+    let normal = rest_u.cross(rest_v);
+    if 0.5 * normal.length() <= f32::EPSILON {
+        return None;
+    }
+    let e1 = rest_u.normalize();
+    let e2 = normal.cross(rest_u).normalize();
+    let rest_edges = Mat2::from_cols(
+        Vec2::new(rest_u.dot(e1), 0.0),
+        Vec2::new(rest_v.dot(e1), rest_v.dot(e2)),
+    );
+
+    // The deformation gradient F maps rest edges onto current edges. C is
+    // built from the dot products of the current edges.
+    let current_u = current[1] - current[0];
+    let current_v = current[2] - current[0];
+    let edge_metric = Mat2::from_cols(
+        Vec2::new(current_u.dot(current_u), current_u.dot(current_v)),
+        Vec2::new(current_v.dot(current_u), current_v.dot(current_v)),
+    );
+    let inverse = rest_edges.inverse();
+    Some(inverse.transpose() * edge_metric * inverse)
+}
+
+/// Principal engineering strains of a face, larger first, given its rest and
+/// current vertex positions (m).
+pub fn principal_strains(rest: [Vec3; 3], current: [Vec3; 3]) -> (f32, f32) {
+    right_cauchy_green(rest, current).map_or((0.0, 0.0), |cauchy_green| {
+        let (max_stretch, min_stretch) = eigenvalues(cauchy_green);
+        (max_stretch.max(0.0).sqrt() - 1.0, min_stretch.max(0.0).sqrt() - 1.0)
+    })
+}
+
+/// Von Mises equivalent stress (Pa) of a plane stress state given its
+/// principal stresses (Pa).
+pub fn von_mises(sigma_1: f32, sigma_2: f32) -> f32 {
+    (sigma_1 * sigma_1 - sigma_1 * sigma_2 + sigma_2 * sigma_2).max(0.0).sqrt()
+}
+
+/// Margin of safety of a stress (Pa) against an allowable stress (Pa).
+pub fn margin_of_safety(allowable: f32, stress: f32) -> f32 {
+    if stress <= f32::EPSILON {
+        f32::INFINITY
+    } else {
+        allowable / stress - 1.0
+    }
+}
+
+/// Eigenvalues of a 2x2 matrix with real eigenvalues, largest first.
+fn eigenvalues(m: Mat2) -> (f32, f32) {
+    let half_trace = 0.5 * (m.x_axis.x + m.y_axis.y);
+    let discriminant = (half_trace * half_trace - m.determinant()).max(0.0).sqrt();
+    (half_trace + discriminant, half_trace - discriminant)
+}
+
+/// Membrane stress in every face of a deformed mesh, with summary statistics
+/// for design reviews.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+pub struct MembraneStress {
+    /// Stress in each face, in the order of the mesh triangles.
+    pub faces: Vec<FaceStress>,
+    /// Highest von Mises stress (Pa) of any face.
+    pub max_von_mises: f32,
+    /// Area-weighted mean von Mises stress (Pa) over the rest surface.
+    pub mean_von_mises: f32,
+    /// Highest principal stress (Pa) of any face.
+    pub max_principal: f32,
+    /// Lowest principal stress (Pa) of any face. Negative where the skin is
+    /// in compression and would wrinkle.
+    pub min_principal: f32,
+    /// Face with the highest von Mises stress.
+    pub critical_face: Option<usize>,
+    /// Margin of safety of the critical face against the skin's stress at
+    /// failure.
+    pub margin: f32,
+}
+
+impl MembraneStress {
+    /// Solve for the stress in every triangle of a mesh, given its rest and
+    /// current vertex positions (m).
+    pub fn compute(rest_positions: &[Vec3], positions: &[Vec3], triangles: &[[u32; 3]], skin: &Skin) -> Self {
+        let mut stress = MembraneStress::default();
+        stress.update(rest_positions, positions, triangles, skin);
+        stress
+    }
+
+    /// Solve again for the stress in every triangle, reusing the face buffer.
+    pub fn update(&mut self, rest_positions: &[Vec3], positions: &[Vec3], triangles: &[[u32; 3]], skin: &Skin) {
+        self.faces.clear();
+        self.faces.reserve(triangles.len());
+        self.max_von_mises = 0.0;
+        self.max_principal = f32::NEG_INFINITY;
+        self.min_principal = f32::INFINITY;
+        self.critical_face = None;
+
+        let mut weighted_von_mises = 0.0;
+        let mut total_area = 0.0;
+        for (index, &[a, b, c]) in triangles.iter().enumerate() {
+            let (a, b, c) = (a as usize, b as usize, c as usize);
+            let rest = [rest_positions[a], rest_positions[b], rest_positions[c]];
+            let face = FaceStress::from_triangle(rest, [positions[a], positions[b], positions[c]], skin);
+
+            let rest_area = 0.5 * (rest[1] - rest[0]).cross(rest[2] - rest[0]).length();
+            weighted_von_mises += face.von_mises * rest_area;
+            total_area += rest_area;
+            self.max_principal = self.max_principal.max(face.max_principal);
+            self.min_principal = self.min_principal.min(face.min_principal);
+            if self.critical_face.is_none() || face.von_mises > self.max_von_mises {
+                self.max_von_mises = face.von_mises;
+                self.critical_face = Some(index);
+            }
+            self.faces.push(face);
+        }
+
+        if self.faces.is_empty() {
+            self.max_principal = 0.0;
+            self.min_principal = 0.0;
+        }
+        self.mean_von_mises = if total_area > f32::EPSILON {
+            weighted_von_mises / total_area
+        } else {
+            0.0
+        };
+        self.margin = margin_of_safety(skin.max_stress, self.max_von_mises);
+    }
+
+    /// Faces whose von Mises stress exceeds the skin's stress at failure.
+    pub fn failed_faces(&self, skin: &Skin) -> impl Iterator<Item = usize> + '_ {
+        let max_stress = skin.max_stress;
+        self.faces
+            .iter()
+            .enumerate()
+            .filter(move |(_, face)| face.von_mises > max_stress)
+            .map(|(index, _)| index)
+    }
+}
+
+/// Solve for the membrane stress of every deformable envelope.
+pub(crate) fn update_membrane_stress(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Envelope, Option<&mut MembraneStress>)>,
+) {
+    for (entity, envelope, stress) in query.iter_mut() {
+        match stress {
+            Some(mut stress) => stress.update(
+                envelope.rest_positions(),
+                envelope.positions(),
+                envelope.triangles(),
+                &envelope.material(),
+            ),
+            None => {
+                commands.entity(entity).insert(MembraneStress::compute(
+                    envelope.rest_positions(),
+                    envelope.positions(),
+                    envelope.triangles(),
+                    &envelope.material(),
+                ));
+            }
+        }
+    }
+}
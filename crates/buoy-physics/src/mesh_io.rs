@@ -0,0 +1,381 @@
+//! Reading and writing triangle meshes for envelopes and payloads.
+//!
+//! Shapes from CAD come in as OBJ, STL or glTF (`.gltf` or `.glb`) files and
+//! go out as physics-ready parts: a render [`Mesh`], the [`RestState`] that a
+//! deformable [`Envelope`](crate::envelope::Envelope) starts from, and a
+//! [`Collider`] with its volume. Vertices shared by several faces are welded
+//! so the mesh is closed even when the file splits vertices at texture or
+//! normal seams.
+//!
+//! The deformed shape of an envelope can be written back to OBJ or STL for
+//! inspection in CAD tools.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Seek, Write},
+    path::Path,
+};
+
+use avian3d::prelude::*;
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_asset::RenderAssetUsages, render_resource::PrimitiveTopology},
+};
+
+use crate::{
+    envelope::Envelope,
+    geometry::{collider_volume, trimesh_volume},
+    mesh_utils::RestState,
+};
+
+#[derive(Debug)]
+pub enum MeshIoError {
+    Io(io::Error),
+    Obj(tobj::LoadError),
+    Gltf(gltf::Error),
+    /// The format, named by its file extension, can't be read or written.
+    UnsupportedFormat(String),
+    /// The file has no triangles.
+    Empty,
+    /// A triangle refers to a vertex that doesn't exist.
+    IndexOutOfBounds { index: u32, vertices: usize },
+    /// No collider could be built from the triangles, for example because
+    /// they are all in one plane.
+    Collider,
+}
+
+impl fmt::Display for MeshIoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshIoError::Io(e) => write!(f, "could not access mesh file: {}", e),
+            MeshIoError::Obj(e) => write!(f, "could not parse OBJ file: {}", e),
+            MeshIoError::Gltf(e) => write!(f, "could not parse glTF file: {}", e),
+            MeshIoError::UnsupportedFormat(extension) => write!(f, "unsupported mesh format `{}`", extension),
+            MeshIoError::Empty => write!(f, "the mesh has no triangles"),
+            MeshIoError::IndexOutOfBounds { index, vertices } => {
+                write!(f, "a triangle refers to vertex {} but the mesh has {} vertices", index, vertices)
+            }
+            MeshIoError::Collider => write!(f, "could not build a collider from the mesh"),
+        }
+    }
+}
+
+impl std::error::Error for MeshIoError {}
+
+impl From<io::Error> for MeshIoError {
+    fn from(e: io::Error) -> Self {
+        MeshIoError::Io(e)
+    }
+}
+
+impl From<tobj::LoadError> for MeshIoError {
+    fn from(e: tobj::LoadError) -> Self {
+        MeshIoError::Obj(e)
+    }
+}
+
+impl From<gltf::Error> for MeshIoError {
+    fn from(e: gltf::Error) -> Self {
+        MeshIoError::Gltf(e)
+    }
+}
+
+/// File formats for triangle meshes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshFormat {
+    Obj,
+    Stl,
+    Gltf,
+}
+
+impl MeshFormat {
+    /// The format of a file, from its extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, MeshIoError> {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "obj" => Ok(MeshFormat::Obj),
+            "stl" => Ok(MeshFormat::Stl),
+            "gltf" | "glb" => Ok(MeshFormat::Gltf),
+            _ => Err(MeshIoError::UnsupportedFormat(extension)),
+        }
+    }
+}
+
+/// The collider built for an imported shape.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColliderKind {
+    /// Convex hull of the vertices. Fast, and a good fit for envelopes.
+    #[default]
+    ConvexHull,
+    /// The triangles themselves, for concave payloads.
+    Trimesh,
+}
+
+/// How to turn a mesh file into a shape.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImportOptions {
+    pub collider: ColliderKind,
+    /// Factor from file units to meters, e.g. 0.001 for CAD files in
+    /// millimeters.
+    pub scale: f32,
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        ImportOptions {
+            collider: ColliderKind::ConvexHull,
+            scale: 1.0,
+        }
+    }
+}
+
+/// A physics-ready shape read from a mesh file.
+#[derive(Debug)]
+pub struct ImportedShape {
+    pub mesh: Mesh,
+    pub rest_state: RestState,
+    pub collider: Collider,
+    /// Volume (m³) enclosed by the collider.
+    pub volume: f32,
+}
+
+/// Read a mesh file as a physics-ready shape. The format follows the file
+/// extension.
+pub fn load_shape(path: impl AsRef<Path>, options: ImportOptions) -> Result<ImportedShape, MeshIoError> {
+    let path = path.as_ref();
+    let (positions, triangles) = match MeshFormat::from_path(path)? {
+        MeshFormat::Obj => read_obj(path)?,
+        MeshFormat::Stl => read_stl(&mut BufReader::new(File::open(path)?))?,
+        MeshFormat::Gltf => read_gltf(path)?,
+    };
+    let positions: Vec<Vec3> = positions.into_iter().map(|p| p * options.scale).collect();
+    shape_from_triangles(positions, triangles, options.collider)
+}
+
+/// Build a physics-ready shape from vertices (m) and triangles.
+pub fn shape_from_triangles(
+    positions: Vec<Vec3>,
+    triangles: Vec<[u32; 3]>,
+    collider: ColliderKind,
+) -> Result<ImportedShape, MeshIoError> {
+    check_indices(&positions, &triangles)?;
+    let (positions, triangles) = weld(&positions, &triangles);
+    if triangles.is_empty() {
+        return Err(MeshIoError::Empty);
+    }
+    let (collider, volume) = match collider {
+        ColliderKind::ConvexHull => {
+            let hull = Collider::convex_hull(positions.clone()).ok_or(MeshIoError::Collider)?;
+            let volume = collider_volume(&hull);
+            (hull, volume)
+        }
+        ColliderKind::Trimesh => (
+            Collider::trimesh(positions.clone(), triangles.clone()),
+            trimesh_volume(&positions, &triangles),
+        ),
+    };
+
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    );
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_POSITION,
+        positions.iter().map(|&p| p.to_array()).collect::<Vec<_>>(),
+    );
+    mesh.insert_indices(Indices::U32(triangles.into_iter().flatten().collect()));
+    mesh.compute_smooth_normals();
+    let rest_state = RestState::from_mesh(&mesh);
+    Ok(ImportedShape {
+        mesh,
+        rest_state,
+        collider,
+        volume,
+    })
+}
+
+/// Make sure every triangle refers to an existing vertex.
+fn check_indices(positions: &[Vec3], triangles: &[[u32; 3]]) -> Result<(), MeshIoError> {
+    match triangles.iter().flatten().find(|&&i| i as usize >= positions.len()) {
+        Some(&index) => Err(MeshIoError::IndexOutOfBounds {
+            index,
+            vertices: positions.len(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Merge vertices at exactly the same position and drop triangles that
+/// collapse as a result.
+fn weld(positions: &[Vec3], triangles: &[[u32; 3]]) -> (Vec<Vec3>, Vec<[u32; 3]>) {
+    let mut welded = Vec::with_capacity(positions.len());
+    let mut index_of: HashMap<[u32; 3], u32> = HashMap::with_capacity(positions.len());
+    let remap: Vec<u32> = positions
+        .iter()
+        .map(|p| {
+            // Treat -0.0 and 0.0 as the same coordinate.
+            let key = (*p + Vec3::ZERO).to_array().map(f32::to_bits);
+            *index_of.entry(key).or_insert_with(|| {
+                welded.push(*p);
+                (welded.len() - 1) as u32
+            })
+        })
+        .collect();
+    let triangles = triangles
+        .iter()
+        .map(|t| t.map(|i| remap[i as usize]))
+        .filter(|[a, b, c]| a != b && b != c && c != a)
+        .collect();
+    (welded, triangles)
+}
+
+/// Read the vertices and triangles of every object in an OBJ file.
+/// Polygons are triangulated.
+pub fn read_obj(path: impl AsRef<Path>) -> Result<(Vec<Vec3>, Vec<[u32; 3]>), MeshIoError> {
+    let options = tobj::LoadOptions {
+        single_index: true,
+        triangulate: true,
+        ignore_points: true,
+        ignore_lines: true,
+    };
+    // Materials are irrelevant to the physics, so a missing MTL file is fine.
+    let (models, _materials) = tobj::load_obj(path.as_ref(), &options)?;
+    let mut positions = Vec::new();
+    let mut triangles = Vec::new();
+    for model in models {
+        let offset = positions.len() as u32;
+        positions.extend(model.mesh.positions.chunks_exact(3).map(|p| Vec3::new(p[0], p[1], p[2])));
+        triangles.extend(
+            model
+                .mesh
+                .indices
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]].map(|i| i.saturating_add(offset))),
+        );
+    }
+    check_indices(&positions, &triangles)?;
+    Ok((positions, triangles))
+}
+
+/// Read the vertices and triangles of an ASCII or binary STL file.
+pub fn read_stl(reader: &mut (impl Read + Seek)) -> Result<(Vec<Vec3>, Vec<[u32; 3]>), MeshIoError> {
+    let stl = stl_io::read_stl(reader)?;
+    let positions: Vec<Vec3> = stl.vertices.iter().map(|v| Vec3::from_array(v.0)).collect();
+    let triangles: Vec<[u32; 3]> = stl
+        .faces
+        .iter()
+        .map(|face| face.vertices.map(|i| u32::try_from(i).unwrap_or(u32::MAX)))
+        .collect();
+    check_indices(&positions, &triangles)?;
+    Ok((positions, triangles))
+}
+
+/// Read the vertices and triangles of every mesh in the default scene of a
+/// glTF file, placed by their node transforms.
+pub fn read_gltf(path: impl AsRef<Path>) -> Result<(Vec<Vec3>, Vec<[u32; 3]>), MeshIoError> {
+    let path = path.as_ref();
+    let gltf = gltf::Gltf::open(path)?;
+    let buffers = gltf::import_buffers(&gltf.document, path.parent(), gltf.blob.clone())?;
+    let mut positions = Vec::new();
+    let mut triangles = Vec::new();
+    let scene = gltf.document.default_scene().or_else(|| gltf.document.scenes().next());
+    let mut stack: Vec<(gltf::Node, Mat4)> = scene
+        .into_iter()
+        .flat_map(|scene| scene.nodes())
+        .map(|node| (node, Mat4::IDENTITY))
+        .collect();
+    while let Some((node, parent)) = stack.pop() {
+        let transform = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+        if let Some(mesh) = node.mesh() {
+            for primitive in mesh.primitives() {
+                if primitive.mode() != gltf::mesh::Mode::Triangles {
+                    continue;
+                }
+                let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data.0[..]));
+                let Some(primitive_positions) = reader.read_positions() else {
+                    continue;
+                };
+                let offset = positions.len() as u32;
+                positions.extend(primitive_positions.map(|p| transform.transform_point3(Vec3::from_array(p))));
+                let count = positions.len() as u32 - offset;
+                let indices: Vec<u32> = match reader.read_indices() {
+                    Some(indices) => indices.into_u32().collect(),
+                    None => (0..count).collect(),
+                };
+                // A mirroring transform turns the faces inside out.
+                let flip = transform.determinant() < 0.0;
+                triangles.extend(indices.chunks_exact(3).map(|t| {
+                    let [a, b, c] = [t[0], t[1], t[2]].map(|i| i.saturating_add(offset));
+                    if flip { [a, c, b] } else { [a, b, c] }
+                }));
+            }
+        }
+        stack.extend(node.children().map(|child| (child, transform)));
+    }
+    check_indices(&positions, &triangles)?;
+    Ok((positions, triangles))
+}
+
+/// Write triangles as a Wavefront OBJ file.
+pub fn write_obj(mut writer: impl Write, positions: &[Vec3], triangles: &[[u32; 3]]) -> Result<(), MeshIoError> {
+    check_indices(positions, triangles)?;
+    writeln!(writer, "# buoy mesh: {} vertices, {} faces", positions.len(), triangles.len())?;
+    for p in positions {
+        writeln!(writer, "v {} {} {}", p.x, p.y, p.z)?;
+    }
+    for [a, b, c] in triangles {
+        // OBJ indices start at 1.
+        writeln!(writer, "f {} {} {}", a + 1, b + 1, c + 1)?;
+    }
+    Ok(())
+}
+
+/// Write triangles as a binary STL file.
+pub fn write_stl(mut writer: impl Write, positions: &[Vec3], triangles: &[[u32; 3]]) -> Result<(), MeshIoError> {
+    check_indices(positions, triangles)?;
+    let stl: Vec<stl_io::Triangle> = triangles
+        .iter()
+        .map(|&[a, b, c]| {
+            let vertices = [positions[a as usize], positions[b as usize], positions[c as usize]];
+            let normal = (vertices[1] - vertices[0])
+                .cross(vertices[2] - vertices[0])
+                .normalize_or_zero();
+            stl_io::Triangle {
+                normal: stl_io::Normal::new(normal.to_array()),
+                vertices: vertices.map(|v| stl_io::Vertex::new(v.to_array())),
+            }
+        })
+        .collect();
+    stl_io::write_stl(&mut writer, stl.iter())?;
+    Ok(())
+}
+
+/// Save triangles as OBJ or STL, following the file extension. Nothing is
+/// written unless the format and the triangles are valid.
+pub fn save_mesh(path: impl AsRef<Path>, positions: &[Vec3], triangles: &[[u32; 3]]) -> Result<(), MeshIoError> {
+    let path = path.as_ref();
+    let write = match MeshFormat::from_path(path)? {
+        MeshFormat::Obj => write_obj,
+        MeshFormat::Stl => write_stl,
+        MeshFormat::Gltf => return Err(MeshIoError::UnsupportedFormat("gltf".to_string())),
+    };
+    check_indices(positions, triangles)?;
+    let mut writer = BufWriter::new(File::create(path)?);
+    write(&mut writer, positions, triangles)?;
+    writer.flush()?;
+    Ok(())
+}
+
+impl Envelope {
+    /// Save the deformed envelope, in the body's local frame, as OBJ or STL.
+    pub fn save_mesh(&self, path: impl AsRef<Path>) -> Result<(), MeshIoError> {
+        save_mesh(path, self.positions(), self.triangles())
+    }
+}
@@ -9,8 +9,13 @@ use bevy::{
 };
 use std::collections::HashMap;
 
+use crate::{envelope::Envelope, strain_field::StrainField};
+
 pub(crate) fn plugin(app: &mut App) {
-    app.add_systems(Update, calculate_strain_system);
+    app.add_systems(
+        Update,
+        calculate_strain_system.run_if(resource_exists::<Assets<Mesh>>),
+    );
 }
 
 #[derive(Component)]
@@ -237,24 +242,23 @@ fn generate_icosahedron(radius: f32) -> (Vec<[f32; 3]>, Vec<u16>) {
         [v[0]/len*radius, v[1]/len*radius, v[2]/len*radius]
     };
 
-    // The 12 vertices of an icosahedron
-    let mut vertices = Vec::with_capacity(12);
-
-    // Vertices of an icosahedron centered at the origin
-    vertices.push(normalize([-1.0,   t, 0.0]));
-    vertices.push(normalize([ 1.0,   t, 0.0]));
-    vertices.push(normalize([-1.0,  -t, 0.0]));
-    vertices.push(normalize([ 1.0,  -t, 0.0]));
-
-    vertices.push(normalize([0.0, -1.0,   t]));
-    vertices.push(normalize([0.0,  1.0,   t]));
-    vertices.push(normalize([0.0, -1.0,  -t]));
-    vertices.push(normalize([0.0,  1.0,  -t]));
-
-    vertices.push(normalize([  t, 0.0, -1.0]));
-    vertices.push(normalize([  t, 0.0,  1.0]));
-    vertices.push(normalize([ -t, 0.0, -1.0]));
-    vertices.push(normalize([ -t, 0.0,  1.0]));
+    // The 12 vertices of an icosahedron centered at the origin
+    let vertices = vec![
+        normalize([-1.0,   t, 0.0]),
+        normalize([ 1.0,   t, 0.0]),
+        normalize([-1.0,  -t, 0.0]),
+        normalize([ 1.0,  -t, 0.0]),
+
+        normalize([0.0, -1.0,   t]),
+        normalize([0.0,  1.0,   t]),
+        normalize([0.0, -1.0,  -t]),
+        normalize([0.0,  1.0,  -t]),
+
+        normalize([  t, 0.0, -1.0]),
+        normalize([  t, 0.0,  1.0]),
+        normalize([ -t, 0.0, -1.0]),
+        normalize([ -t, 0.0,  1.0]),
+    ];
 
     // Indices for the 20 triangular faces
     let indices = vec![
@@ -407,11 +411,12 @@ pub fn calculate_strain_system(
         &MeshHandle,
         &RestState,
         &mut Strain,
+        Option<&mut StrainField>,
+        Has<Envelope>,
     )>,
     meshes: Res<Assets<Mesh>>,
-    _asset_server: Res<AssetServer>,
 ) {
-    for (mesh_handle, rest_state, mut strain) in query.iter_mut() {
+    for (mesh_handle, rest_state, mut strain, field, is_envelope) in query.iter_mut() {
         // Get the current mesh data
         let Some(mesh) = meshes.get(&mesh_handle.0) else { continue };
 
@@ -457,6 +462,19 @@ pub fn calculate_strain_system(
             strain.max_strain = max_strain;
             strain.min_strain = min_strain;
         }
+
+        // Update the per-element field, if the entity keeps one. Envelopes
+        // have theirs filled in from the simulated shape and membrane stress.
+        if let Some(mut field) = field.filter(|_| !is_envelope) {
+            let rest_positions: Vec<Vec3> = rest_state.rest_positions.iter().map(|&p| p.into()).collect();
+            let current_positions: Vec<Vec3> = positions.iter().map(|&p| p.into()).collect();
+            let triangles: Vec<[u32; 3]> = rest_state
+                .indices
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]])
+                .collect();
+            field.update(&rest_positions, &current_positions, &triangles, None);
+        }
     }
 }
 
@@ -0,0 +1,218 @@
+//! Planetary bodies and their reference atmospheres.
+//!
+//! A [`PlanetaryBody`] describes the world the simulation runs on: its size,
+//! gravity, spin and the atmosphere that goes with it. Earth is the default.
+//! Mars, Venus and Titan presets let the same balloon models be flown on other
+//! bodies:
+//!
+//! ```ignore
+//! let mars = PlanetaryBody::mars();
+//! app.insert_resource(mars.atmosphere()).insert_resource(mars);
+//! ```
+//!
+//! Reference:
+//! - https://nssdc.gsfc.nasa.gov/planetary/planetfact.html
+//! - https://www.grc.nasa.gov/WWW/K-12/airplane/atmosmrm.html (Mars)
+//! - Seiff et al. (1985), Models of the structure of the atmosphere of Venus
+//!   from the surface to 100 kilometers altitude, Adv. Space Res. 5(11) (VIRA)
+//! - Fulchignoni et al. (2005), In situ measurements of the physical
+//!   characteristics of Titan's environment, Nature 438 (Huygens HASI)
+
+use bevy::prelude::*;
+use uom::si::{
+    acceleration::meter_per_second_squared,
+    angular_velocity::radian_per_second,
+    f32::*,
+    length::{kilometer, meter},
+    molar_mass::kilogram_per_mole,
+    pressure::kilopascal,
+    thermodynamic_temperature::degree_celsius,
+};
+
+use crate::{
+    atmosphere::{Atmosphere, AtmosphereModel, ProfilePoint},
+    clock::Epoch,
+    constants::{EARTH_RADIUS_M, STANDARD_GRAVITY},
+    ideal_gas::GasSpecies,
+};
+
+pub(crate) fn plugin(app: &mut App) {
+    app.init_resource::<PlanetaryBody>();
+}
+
+/// The body the simulation takes place on.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct PlanetaryBody {
+    pub name: String,
+    /// Mean radius of the body.
+    pub radius: Length,
+    /// Gravitational acceleration at the mean radius.
+    pub surface_gravity: Acceleration,
+    /// Sidereal rotation rate. Negative for retrograde rotation.
+    pub rotation_rate: AngularVelocity,
+    /// Bulk composition of the atmosphere.
+    pub atmosphere_composition: GasSpecies,
+    /// Where the atmosphere's properties come from.
+    pub atmosphere_model: AtmosphereModel,
+}
+
+impl Default for PlanetaryBody {
+    fn default() -> Self {
+        PlanetaryBody::earth()
+    }
+}
+
+impl PlanetaryBody {
+    pub fn earth() -> Self {
+        PlanetaryBody {
+            name: "Earth".to_string(),
+            radius: *EARTH_RADIUS_M,
+            surface_gravity: *STANDARD_GRAVITY,
+            rotation_rate: AngularVelocity::new::<radian_per_second>(7.292_115e-5),
+            atmosphere_composition: GasSpecies::air(),
+            atmosphere_model: AtmosphereModel::UsStandard1976,
+        }
+    }
+
+    /// Mars with the NASA Glenn curve fit to Mars Global Surveyor data.
+    pub fn mars() -> Self {
+        PlanetaryBody {
+            name: "Mars".to_string(),
+            radius: Length::new::<kilometer>(3389.5),
+            surface_gravity: Acceleration::new::<meter_per_second_squared>(3.721),
+            rotation_rate: AngularVelocity::new::<radian_per_second>(7.088_218e-5),
+            // 95.3% CO2, 2.7% N2, 1.6% Ar
+            atmosphere_composition: GasSpecies::new(
+                "Martian atmosphere".to_string(),
+                "MARS".to_string(),
+                MolarMass::new::<kilogram_per_mole>(0.04334),
+            ),
+            atmosphere_model: AtmosphereModel::Mars,
+        }
+    }
+
+    /// Venus with the VIRA low-latitude reference profile.
+    pub fn venus() -> Self {
+        PlanetaryBody {
+            name: "Venus".to_string(),
+            radius: Length::new::<kilometer>(6051.8),
+            surface_gravity: Acceleration::new::<meter_per_second_squared>(8.87),
+            rotation_rate: AngularVelocity::new::<radian_per_second>(-2.992_4e-7),
+            // 96.5% CO2, 3.5% N2
+            atmosphere_composition: GasSpecies::new(
+                "Venusian atmosphere".to_string(),
+                "VENUS".to_string(),
+                MolarMass::new::<kilogram_per_mole>(0.04345),
+            ),
+            atmosphere_model: AtmosphereModel::Profile(profile(VENUS_PROFILE)),
+        }
+    }
+
+    /// Titan with a profile smoothed from the Huygens HASI descent.
+    pub fn titan() -> Self {
+        PlanetaryBody {
+            name: "Titan".to_string(),
+            radius: Length::new::<kilometer>(2574.73),
+            surface_gravity: Acceleration::new::<meter_per_second_squared>(1.352),
+            rotation_rate: AngularVelocity::new::<radian_per_second>(4.5607e-6),
+            // 98.6% N2, 1.4% CH4
+            atmosphere_composition: GasSpecies::new(
+                "Titanian atmosphere".to_string(),
+                "TITAN".to_string(),
+                MolarMass::new::<kilogram_per_mole>(0.02785),
+            ),
+            atmosphere_model: AtmosphereModel::Profile(profile(TITAN_PROFILE)),
+        }
+    }
+
+    /// The atmosphere of this body.
+    pub fn atmosphere(&self) -> Atmosphere {
+        Atmosphere {
+            model: self.atmosphere_model.clone(),
+            composition: self.atmosphere_composition.clone(),
+            ..default()
+        }
+    }
+
+    /// Density of the reference atmosphere at the mean radius, the body's
+    /// counterpart of sea level density.
+    pub fn surface_density(&self) -> MassDensity {
+        self.atmosphere().density(Vec3::ZERO, Epoch::default())
+    }
+
+    /// Gravitational acceleration at an altitude above the mean radius,
+    /// following the inverse-square law.
+    pub fn gravity(&self, altitude: Length) -> Acceleration {
+        let ratio = (self.radius / (self.radius + altitude)).value;
+        self.surface_gravity * ratio * ratio
+    }
+}
+
+/// Temperature (K) of the Martian atmosphere at a given altitude (m).
+/// The curve fit is only meaningful in the lower atmosphere, so the
+/// temperature is held constant above 50 km.
+pub fn mars_temperature(altitude: f32) -> ThermodynamicTemperature {
+    let altitude = altitude.min(MARS_FIT_CEILING);
+    if altitude < 7000.0 {
+        ThermodynamicTemperature::new::<degree_celsius>(-31.0 - 0.000998 * altitude)
+    } else {
+        ThermodynamicTemperature::new::<degree_celsius>(-23.4 - 0.00222 * altitude)
+    }
+}
+
+/// Pressure (Pa) of the Martian atmosphere at a given altitude (m).
+pub fn mars_pressure(altitude: f32) -> Pressure {
+    Pressure::new::<kilopascal>(0.699 * f32::exp(-0.00009 * altitude))
+}
+
+/// Altitude (m) above which the Martian temperature fit is not extrapolated.
+const MARS_FIT_CEILING: f32 = 50000.0;
+
+/// Altitude (km), temperature (K), pressure (bar) at low latitudes.
+const VENUS_PROFILE: &[(f32, f32, f32)] = &[
+    (0.0, 735.3, 92.10),
+    (5.0, 697.0, 66.65),
+    (10.0, 658.2, 47.39),
+    (15.0, 621.5, 33.04),
+    (20.0, 580.7, 22.52),
+    (25.0, 539.2, 14.93),
+    (30.0, 496.9, 9.851),
+    (35.0, 455.5, 5.917),
+    (40.0, 417.6, 3.501),
+    (45.0, 385.4, 1.979),
+    (50.0, 350.5, 1.066),
+    (55.0, 302.3, 0.5314),
+    (60.0, 262.8, 0.2357),
+    (65.0, 243.2, 0.09765),
+    (70.0, 229.8, 0.03690),
+    (75.0, 215.4, 0.01363),
+    (80.0, 197.1, 0.004476),
+];
+
+/// Altitude (km), temperature (K), pressure (bar). Pressures are integrated
+/// hydrostatically from the 1.467 bar surface pressure.
+const TITAN_PROFILE: &[(f32, f32, f32)] = &[
+    (0.0, 93.65, 1.467),
+    (10.0, 86.5, 0.8889),
+    (20.0, 79.0, 0.5174),
+    (30.0, 73.5, 0.2889),
+    (40.0, 70.8, 0.1568),
+    (44.0, 70.4, 0.1223),
+    (60.0, 75.0, 0.04694),
+    (80.0, 115.0, 0.01875),
+    (100.0, 140.0, 0.009639),
+    (150.0, 165.0, 0.002489),
+    (200.0, 175.0, 0.0007737),
+    (250.0, 186.0, 0.0002677),
+];
+
+fn profile(table: &[(f32, f32, f32)]) -> Vec<ProfilePoint> {
+    table
+        .iter()
+        .map(|&(altitude_km, temperature_k, pressure_bar)| ProfilePoint {
+            altitude: Length::new::<kilometer>(altitude_km).get::<meter>(),
+            temperature: temperature_k,
+            pressure: pressure_bar * 1.0e5,
+        })
+        .collect()
+}
@@ -0,0 +1,330 @@
+//! Where on a deformable mesh the material is strained and stressed.
+//!
+//! [`Strain`](crate::mesh_utils::Strain) and [`MembraneStress`] summarize a
+//! whole mesh in a few numbers. A [`StrainField`] keeps a value for every
+//! edge, face and vertex so heat maps, burst prediction and logging can tell
+//! where the material is loaded:
+//!
+//! - Edges carry their engineering strain.
+//! - Faces carry their largest principal strain and, when the material is
+//!   known, their von Mises stress.
+//! - Vertices carry the average of the faces around them, weighted by rest
+//!   area, which is smooth enough to color a mesh by.
+//!
+//! Any of these can be summarized with [`StrainField::summary`]: its range,
+//! mean, percentiles, a histogram and where the peak is.
+//!
+//! The field of every [`Envelope`] is updated each fixed step. Other meshes
+//! with a [`RestState`](crate::mesh_utils::RestState) get their field updated
+//! alongside their `Strain` when they carry a `StrainField`.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    envelope::Envelope,
+    membrane::{principal_strains, update_membrane_stress, MembraneStress},
+};
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        update_strain_fields
+            .after(update_membrane_stress)
+            .in_set(PhysicsStepSet::First),
+    );
+}
+
+/// A quantity stored in a [`StrainField`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum FieldQuantity {
+    /// Engineering strain of each edge.
+    EdgeStrain,
+    /// Largest principal engineering strain of each face.
+    FaceStrain,
+    /// Von Mises stress (Pa) of each face.
+    FaceStress,
+    /// Face strain averaged around each vertex.
+    VertexStrain,
+    /// Face stress (Pa) averaged around each vertex.
+    VertexStress,
+}
+
+/// Strain and stress of every edge, face and vertex of a deformable mesh.
+#[derive(Component, Debug, Clone, Default, Reflect)]
+pub struct StrainField {
+    /// Vertex pairs of every edge, lower index first, in ascending order.
+    pub edges: Vec<[u32; 2]>,
+    /// Engineering strain of each edge.
+    pub edge_strain: Vec<f32>,
+    /// Largest principal engineering strain of each face, in the order of
+    /// the mesh triangles.
+    pub face_strain: Vec<f32>,
+    /// Von Mises stress (Pa) of each face. Empty when the material is not
+    /// known.
+    pub face_stress: Vec<f32>,
+    /// Face strain averaged around each vertex.
+    pub vertex_strain: Vec<f32>,
+    /// Face stress (Pa) averaged around each vertex. Empty when the material
+    /// is not known.
+    pub vertex_stress: Vec<f32>,
+    /// Current position (m) of each vertex in the mesh's frame.
+    positions: Vec<Vec3>,
+    triangles: Vec<[u32; 3]>,
+}
+
+impl StrainField {
+    /// The field of a mesh given its rest and current vertex positions (m),
+    /// with stresses taken from its membrane solution if there is one.
+    pub fn compute(
+        rest_positions: &[Vec3],
+        positions: &[Vec3],
+        triangles: &[[u32; 3]],
+        stress: Option<&MembraneStress>,
+    ) -> Self {
+        let mut field = StrainField::default();
+        field.update(rest_positions, positions, triangles, stress);
+        field
+    }
+
+    /// Update the field from the current vertex positions (m), reusing its
+    /// buffers.
+    pub fn update(
+        &mut self,
+        rest_positions: &[Vec3],
+        positions: &[Vec3],
+        triangles: &[[u32; 3]],
+        stress: Option<&MembraneStress>,
+    ) {
+        if self.triangles != triangles {
+            self.triangles = triangles.to_vec();
+            self.edges = triangles
+                .iter()
+                .flat_map(|&[a, b, c]| [[a, b], [b, c], [c, a]])
+                .map(|[a, b]| [a.min(b), a.max(b)])
+                .collect();
+            self.edges.sort_unstable();
+            self.edges.dedup();
+        }
+        self.positions.clear();
+        self.positions.extend_from_slice(positions);
+
+        self.edge_strain.clear();
+        self.edge_strain.extend(self.edges.iter().map(|&[a, b]| {
+            let rest_length = rest_positions[a as usize].distance(rest_positions[b as usize]);
+            let length = positions[a as usize].distance(positions[b as usize]);
+            if rest_length > f32::EPSILON { (length - rest_length) / rest_length } else { 0.0 }
+        }));
+
+        let faces = stress.filter(|stress| stress.faces.len() == triangles.len());
+        self.face_strain.clear();
+        self.face_stress.clear();
+        match faces {
+            Some(stress) => {
+                self.face_strain.extend(stress.faces.iter().map(|face| face.max_strain));
+                self.face_stress.extend(stress.faces.iter().map(|face| face.von_mises));
+            }
+            None => self.face_strain.extend(triangles.iter().map(|&[a, b, c]| {
+                let (a, b, c) = (a as usize, b as usize, c as usize);
+                principal_strains(
+                    [rest_positions[a], rest_positions[b], rest_positions[c]],
+                    [positions[a], positions[b], positions[c]],
+                )
+                .0
+            })),
+        }
+
+        // Average the faces around each vertex, weighted by rest area.
+        let n = positions.len();
+        let mut weights = vec![0.0; n];
+        self.vertex_strain.clear();
+        self.vertex_strain.resize(n, 0.0);
+        self.vertex_stress.clear();
+        if !self.face_stress.is_empty() {
+            self.vertex_stress.resize(n, 0.0);
+        }
+        for (face, &[a, b, c]) in triangles.iter().enumerate() {
+            let (a, b, c) = (a as usize, b as usize, c as usize);
+            let area = 0.5
+                * (rest_positions[b] - rest_positions[a])
+                    .cross(rest_positions[c] - rest_positions[a])
+                    .length();
+            for vertex in [a, b, c] {
+                weights[vertex] += area;
+                self.vertex_strain[vertex] += area * self.face_strain[face];
+                if let Some(stress) = self.face_stress.get(face) {
+                    self.vertex_stress[vertex] += area * stress;
+                }
+            }
+        }
+        for (vertex, weight) in weights.into_iter().enumerate() {
+            if weight > 0.0 {
+                self.vertex_strain[vertex] /= weight;
+                if let Some(stress) = self.vertex_stress.get_mut(vertex) {
+                    *stress /= weight;
+                }
+            }
+        }
+    }
+
+    /// Values of a quantity, one per edge, face or vertex.
+    pub fn values(&self, quantity: FieldQuantity) -> &[f32] {
+        match quantity {
+            FieldQuantity::EdgeStrain => &self.edge_strain,
+            FieldQuantity::FaceStrain => &self.face_strain,
+            FieldQuantity::FaceStress => &self.face_stress,
+            FieldQuantity::VertexStrain => &self.vertex_strain,
+            FieldQuantity::VertexStress => &self.vertex_stress,
+        }
+    }
+
+    /// Position (m) of an edge's midpoint, a face's centroid or a vertex, in
+    /// the mesh's frame.
+    pub fn location(&self, quantity: FieldQuantity, index: usize) -> Option<Vec3> {
+        let position = |vertex: u32| self.positions.get(vertex as usize).copied();
+        match quantity {
+            FieldQuantity::EdgeStrain => {
+                let [a, b] = *self.edges.get(index)?;
+                Some(0.5 * (position(a)? + position(b)?))
+            }
+            FieldQuantity::FaceStrain | FieldQuantity::FaceStress => {
+                let [a, b, c] = *self.triangles.get(index)?;
+                Some((position(a)? + position(b)? + position(c)?) / 3.0)
+            }
+            FieldQuantity::VertexStrain | FieldQuantity::VertexStress => position(index as u32),
+        }
+    }
+
+    /// Index and location of the largest value of a quantity.
+    pub fn peak(&self, quantity: FieldQuantity) -> Option<FieldPeak> {
+        let (index, &value) = self
+            .values(quantity)
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))?;
+        Some(FieldPeak {
+            index,
+            value,
+            position: self.location(quantity, index)?,
+        })
+    }
+
+    /// Statistics of a quantity, with a histogram of `bins` equal bins
+    /// between its smallest and largest values.
+    pub fn summary(&self, quantity: FieldQuantity, bins: usize) -> Option<FieldSummary> {
+        let values = self.values(quantity);
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        Some(FieldSummary {
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            mean: sorted.iter().sum::<f32>() / sorted.len() as f32,
+            p50: percentile(&sorted, 50.0),
+            p90: percentile(&sorted, 90.0),
+            p99: percentile(&sorted, 99.0),
+            histogram: Histogram::new(&sorted, bins),
+            peak: self.peak(quantity)?,
+        })
+    }
+}
+
+/// The largest value of a field quantity and where it is.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
+pub struct FieldPeak {
+    /// Index of the edge, face or vertex.
+    pub index: usize,
+    pub value: f32,
+    /// Position (m) in the mesh's frame.
+    pub position: Vec3,
+}
+
+/// Statistics of a field quantity.
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub struct FieldSummary {
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    /// Median.
+    pub p50: f32,
+    pub p90: f32,
+    pub p99: f32,
+    pub histogram: Histogram,
+    pub peak: FieldPeak,
+}
+
+/// Counts of values in equal bins between a lower and an upper bound.
+#[derive(Debug, Clone, Default, PartialEq, Reflect)]
+pub struct Histogram {
+    pub min: f32,
+    pub max: f32,
+    pub counts: Vec<u32>,
+}
+
+impl Histogram {
+    /// Histogram of values with `bins` equal bins spanning their range.
+    pub fn new(values: &[f32], bins: usize) -> Self {
+        let bins = bins.max(1);
+        let (min, max) = values
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &v| (min.min(v), max.max(v)));
+        if values.is_empty() {
+            return Histogram { min: 0.0, max: 0.0, counts: vec![0; bins] };
+        }
+        let mut counts = vec![0; bins];
+        let width = (max - min) / bins as f32;
+        for &value in values {
+            let bin = if width > 0.0 { ((value - min) / width) as usize } else { 0 };
+            counts[bin.min(bins - 1)] += 1;
+        }
+        Histogram { min, max, counts }
+    }
+
+    /// Width of each bin.
+    pub fn bin_width(&self) -> f32 {
+        (self.max - self.min) / self.counts.len().max(1) as f32
+    }
+
+    /// Lower and upper bound of a bin.
+    pub fn bin_range(&self, bin: usize) -> (f32, f32) {
+        let width = self.bin_width();
+        (self.min + width * bin as f32, self.min + width * (bin + 1) as f32)
+    }
+}
+
+/// Percentile (0–100) of sorted values, interpolated between neighbors.
+pub fn percentile(sorted: &[f32], percent: f32) -> f32 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (percent / 100.0).clamp(0.0, 1.0) * (sorted.len() - 1) as f32;
+    let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[below] + (sorted[above] - sorted[below]) * rank.fract()
+}
+
+/// Update the strain field of every deformable envelope.
+fn update_strain_fields(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Envelope, Option<&MembraneStress>, Option<&mut StrainField>)>,
+) {
+    for (entity, envelope, stress, field) in query.iter_mut() {
+        match field {
+            Some(mut field) => field.update(
+                envelope.rest_positions(),
+                envelope.positions(),
+                envelope.triangles(),
+                stress,
+            ),
+            None => {
+                commands.entity(entity).insert(StrainField::compute(
+                    envelope.rest_positions(),
+                    envelope.positions(),
+                    envelope.triangles(),
+                    stress,
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Stochastic turbulence superimposed on the mean wind.
+//!
+//! Each body carries its own [`Gust`], a random velocity added to the mean
+//! wind when computing drag. The gust has two parts:
+//!
+//! - Mechanical turbulence with Dryden intensities and length scales that
+//!   vary with height above the ground (MIL-HDBK-1797). Every axis is shaped by a
+//!   first-order filter, which matches the Dryden longitudinal spectrum. The
+//!   turbulence field is frozen and advected past the body at its airspeed.
+//! - Optional convective cells: a fixed pattern of updraft cores surrounded
+//!   by gentle downdrafts, confined to the convective boundary layer.
+//!
+//! Turbulence is opt-in: insert the [`Turbulence`] resource to enable it. All
+//! randomness derives from its seed, so runs with the same seed and the same
+//! spawn order produce the same gusts.
+//!
+//! Reference:
+//! - MIL-HDBK-1797, Flying Qualities of Piloted Aircraft, Appendix A
+//! - Lenschow et al. (1980), Mean-field and second-moment budgets in a
+//!   baroclinic, convective boundary layer, J. Atmos. Sci. 37
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    atmosphere::Atmosphere,
+    clock::SimulationClock,
+    forces::{net_force, DragCoefficient},
+    units,
+};
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (attach_gusts, update_gusts)
+            .chain()
+            .before(net_force)
+            .run_if(resource_exists::<Turbulence>)
+            .in_set(PhysicsStepSet::First),
+    );
+}
+
+const METERS_PER_FOOT: f32 = 0.3048;
+
+/// Top (m above the ground) of the Dryden low-altitude model.
+const LOW_ALTITUDE_CEILING: f32 = 1000.0 * METERS_PER_FOOT;
+
+/// Bottom (m above the ground) of the Dryden medium/high-altitude model.
+const HIGH_ALTITUDE_FLOOR: f32 = 2000.0 * METERS_PER_FOOT;
+
+/// Turbulence length scale (m) at medium and high altitude.
+const HIGH_ALTITUDE_LENGTH_SCALE: f32 = 1750.0 * METERS_PER_FOOT;
+
+/// Random gusts added to the mean wind. Light turbulence by default.
+#[derive(Resource, Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Turbulence {
+    /// Seed of every random draw.
+    pub seed: u64,
+    /// Mean wind speed (m/s) at 6 m (20 ft) above the ground, which sets the
+    /// turbulence intensity at low altitude. 7.7, 15.4 and 23.2 m/s are
+    /// light, moderate and severe.
+    #[serde(deserialize_with = "units::velocity")]
+    pub wind_speed_at_6m: f32,
+    /// RMS gust velocity (m/s) above 610 m (2000 ft).
+    #[serde(deserialize_with = "units::velocity")]
+    pub high_altitude_intensity: f32,
+    /// Slowest speed (m/s) the turbulence field is advected past a body.
+    /// Keeps gusts evolving for bodies that drift with the wind.
+    #[serde(deserialize_with = "units::velocity")]
+    pub min_advection_speed: f32,
+    /// Thermal updraft cells, if any.
+    pub convection: Option<ConvectiveCells>,
+    /// Altitude (m) of the ground. Turbulence near the surface depends on
+    /// the height above it.
+    #[serde(skip)]
+    pub ground_altitude: f32,
+}
+
+impl Default for Turbulence {
+    fn default() -> Self {
+        Turbulence {
+            seed: 0,
+            wind_speed_at_6m: 7.7,
+            high_altitude_intensity: 1.5,
+            min_advection_speed: 1.0,
+            convection: None,
+            ground_altitude: 0.0,
+        }
+    }
+}
+
+impl Turbulence {
+    pub fn with_seed(self, seed: u64) -> Self {
+        Turbulence { seed, ..self }
+    }
+
+    /// Height (m) of an altitude (m) above the ground.
+    pub fn height_above_ground(&self, altitude: f32) -> f32 {
+        altitude - self.ground_altitude
+    }
+
+    /// RMS gust velocity (m/s) and turbulence length scale (m) per world
+    /// axis at a height (m) above the ground. Horizontal axes use the
+    /// longitudinal values.
+    pub fn dryden_parameters(&self, height: f32) -> (Vec3, Vec3) {
+        let (low_sigma, low_length) =
+            dryden_low_altitude(height.min(LOW_ALTITUDE_CEILING), self.wind_speed_at_6m);
+        let high_sigma = Vec3::splat(self.high_altitude_intensity);
+        let high_length = Vec3::splat(HIGH_ALTITUDE_LENGTH_SCALE);
+        let t = ((height - LOW_ALTITUDE_CEILING) / (HIGH_ALTITUDE_FLOOR - LOW_ALTITUDE_CEILING))
+            .clamp(0.0, 1.0);
+        (low_sigma.lerp(high_sigma, t), low_length.lerp(high_length, t))
+    }
+}
+
+/// Dryden intensities and length scales below 1000 ft above the ground.
+fn dryden_low_altitude(height: f32, wind_speed_at_6m: f32) -> (Vec3, Vec3) {
+    let altitude_ft = (height / METERS_PER_FOOT).max(10.0);
+    let factor = 0.177 + 0.000823 * altitude_ft;
+    let sigma_vertical = 0.1 * wind_speed_at_6m;
+    let sigma_horizontal = sigma_vertical / factor.powf(0.4);
+    let length_vertical = altitude_ft * METERS_PER_FOOT;
+    let length_horizontal = length_vertical / factor.powf(1.2);
+    (
+        Vec3::new(sigma_horizontal, sigma_vertical, sigma_horizontal),
+        Vec3::new(length_horizontal, length_vertical, length_horizontal),
+    )
+}
+
+/// Thermals rising from a heated surface, laid out on a square grid.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ConvectiveCells {
+    /// Horizontal distance (m) between cell centers.
+    #[serde(deserialize_with = "units::length")]
+    pub spacing: f32,
+    /// Updraft (m/s) at the core of the strongest cells.
+    #[serde(deserialize_with = "units::velocity")]
+    pub peak_updraft: f32,
+    /// Top (m above the ground) of the convective boundary layer. There are
+    /// no cells above it.
+    #[serde(deserialize_with = "units::length")]
+    pub boundary_layer_height: f32,
+}
+
+impl Default for ConvectiveCells {
+    fn default() -> Self {
+        ConvectiveCells {
+            spacing: 2000.0,
+            peak_updraft: 2.0,
+            boundary_layer_height: 1500.0,
+        }
+    }
+}
+
+impl ConvectiveCells {
+    /// Relative radius of an updraft core within its cell.
+    const CORE_RADIUS: f32 = 0.15;
+
+    /// Vertical air velocity (m/s) at a position and its height (m) above
+    /// the ground. Each cell's strength is drawn from the seed, and the
+    /// updraft cores are balanced by weak downdrafts so the mean over a cell
+    /// is zero.
+    pub fn vertical_velocity(&self, position: Vec3, height: f32, seed: u64) -> f32 {
+        if self.spacing <= 0.0 || self.boundary_layer_height <= 0.0 {
+            return 0.0;
+        }
+        let zeta = height / self.boundary_layer_height;
+        if !(0.0..1.0).contains(&zeta) {
+            return 0.0;
+        }
+        // Updraft strength peaks at a quarter of the boundary layer height
+        // and vanishes at the ground and at the inversion.
+        let vertical_shape = zeta.cbrt() * (1.0 - zeta) / (0.25_f32.cbrt() * 0.75);
+
+        let cell = (Vec2::new(position.x, position.z) / self.spacing).floor();
+        let offset = Vec2::new(position.x, position.z) / self.spacing - cell - Vec2::splat(0.5);
+        let core = (-(offset.length() / Self::CORE_RADIUS).powi(2)).exp();
+        let core_mean = std::f32::consts::PI * Self::CORE_RADIUS * Self::CORE_RADIUS;
+        let horizontal_shape = (core - core_mean) / (1.0 - core_mean);
+
+        let strength = unit_hash(seed, cell.x as i64, cell.y as i64);
+        self.peak_updraft * strength * vertical_shape * horizontal_shape
+    }
+}
+
+/// Uniform value in [0, 1) derived from a seed and a cell index.
+fn unit_hash(seed: u64, i: i64, j: i64) -> f32 {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed ^ splitmix64(i as u64 ^ splitmix64(j as u64)));
+    rng.r#gen::<f32>()
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Gust velocity felt by a body, on top of the mean wind.
+#[derive(Component, Debug, Clone)]
+pub struct Gust {
+    /// Total gust velocity (m/s) in world space.
+    pub velocity: Vec3,
+    /// Mechanical part of the gust (m/s), without convection.
+    turbulence: Vec3,
+    rng: ChaCha8Rng,
+}
+
+impl Gust {
+    pub fn new(seed: u64) -> Self {
+        Gust {
+            velocity: Vec3::ZERO,
+            turbulence: Vec3::ZERO,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+}
+
+/// Give every body that feels drag its own gust stream.
+fn attach_gusts(
+    mut commands: Commands,
+    query: Query<Entity, (With<DragCoefficient>, Without<Gust>)>,
+    turbulence: Res<Turbulence>,
+) {
+    for entity in query.iter() {
+        commands
+            .entity(entity)
+            .insert(Gust::new(turbulence.seed ^ splitmix64(entity.to_bits())));
+    }
+}
+
+/// Step each gust as a Gauss-Markov process whose correlation time is the
+/// time the body takes to cross one turbulence length scale.
+fn update_gusts(
+    mut query: Query<(&mut Gust, &Position, &LinearVelocity)>,
+    turbulence: Res<Turbulence>,
+    atmosphere: Res<Atmosphere>,
+    clock: Res<SimulationClock>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+    let epoch = clock.now();
+    for (mut gust, position, velocity) in query.iter_mut() {
+        let airspeed = (velocity.0 - atmosphere.wind(position.0, epoch) - gust.velocity)
+            .length()
+            .max(turbulence.min_advection_speed);
+        let height = turbulence.height_above_ground(position.y);
+        let (sigma, length) = turbulence.dryden_parameters(height);
+        for axis in 0..3 {
+            let correlation = (-airspeed * dt / length[axis].max(f32::EPSILON)).exp();
+            let noise: f32 = gust.rng.sample(StandardNormal);
+            gust.turbulence[axis] = correlation * gust.turbulence[axis]
+                + sigma[axis] * (1.0 - correlation * correlation).sqrt() * noise;
+        }
+        let updraft = turbulence
+            .convection
+            .as_ref()
+            .map_or(0.0, |cells| cells.vertical_velocity(position.0, height, turbulence.seed));
+        gust.velocity = gust.turbulence + Vec3::Y * updraft;
+    }
+}
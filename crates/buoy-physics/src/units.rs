@@ -0,0 +1,502 @@
+//! Quantities written with units in configuration files.
+//!
+//! Config fields that hold a physical quantity accept either a bare number in
+//! SI base units or a string with a unit, such as `"1200 g"`, `"5 lb"`,
+//! `"18 °C"` or `"3 psi"`. Strings are converted to SI through `uom` while
+//! deserializing, and a unit of the wrong kind is an error rather than a
+//! silent reinterpretation:
+//!
+//! ```
+//! use buoy_physics::units::{parse_quantity, Dimension};
+//!
+//! assert_eq!(parse_quantity("1200 g", Dimension::Mass).unwrap(), 1.2);
+//! assert!(parse_quantity("18 °C", Dimension::Mass).is_err());
+//! ```
+//!
+//! Temperatures and temperature differences are distinct dimensions: `"10
+//! °C"` is 283.15 K as a temperature but 10 K as a difference.
+//!
+//! Fields opt in with `#[serde(deserialize_with = "units::mass")]` and the
+//! like, and keep their value in SI base units.
+
+use std::fmt;
+
+use serde::de::{self, Deserializer, Visitor};
+use uom::si::{
+    f64::{
+        Length, Mass, MassDensity, MassRate, MolarMass, Pressure, TemperatureInterval,
+        ThermodynamicTemperature, Time, Velocity, Volume,
+    },
+    length::{centimeter, foot, inch, kilometer, meter, mile, millimeter},
+    mass::{gram, kilogram, milligram, ounce, pound, ton},
+    mass_density::{gram_per_cubic_centimeter, kilogram_per_cubic_meter},
+    mass_rate::{gram_per_second, kilogram_per_minute, kilogram_per_second, pound_per_second},
+    molar_mass::{gram_per_mole, kilogram_per_mole},
+    pressure::{
+        atmosphere, bar, hectopascal, inch_of_mercury, kilopascal, megapascal, millibar,
+        millimeter_of_mercury, pascal, pound_force_per_square_inch,
+    },
+    temperature_interval,
+    thermodynamic_temperature::{degree_celsius, degree_fahrenheit, kelvin},
+    time::{day, hour, minute, second},
+    velocity::{foot_per_second, kilometer_per_hour, knot, meter_per_second, mile_per_hour},
+    volume::{cubic_foot, cubic_meter, liter},
+};
+
+/// Kind of physical quantity a config field holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dimension {
+    Dimensionless,
+    Length,
+    Mass,
+    MassDensity,
+    MassRate,
+    MolarMass,
+    Pressure,
+    Temperature,
+    /// A difference between two temperatures, such as an amplitude. Degrees
+    /// Celsius and kelvins are the same size here, so `"10 °C"` is 10 K.
+    TemperatureDifference,
+    Time,
+    Velocity,
+    Volume,
+}
+
+impl Dimension {
+    /// Symbol of the SI unit bare numbers are read in.
+    pub fn si_unit(self) -> &'static str {
+        match self {
+            Dimension::Dimensionless => "",
+            Dimension::Length => "m",
+            Dimension::Mass => "kg",
+            Dimension::MassDensity => "kg/m³",
+            Dimension::MassRate => "kg/s",
+            Dimension::MolarMass => "kg/mol",
+            Dimension::Pressure => "Pa",
+            Dimension::Temperature | Dimension::TemperatureDifference => "K",
+            Dimension::Time => "s",
+            Dimension::Velocity => "m/s",
+            Dimension::Volume => "m³",
+        }
+    }
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Dimension::Dimensionless => "dimensionless number",
+            Dimension::Length => "length",
+            Dimension::Mass => "mass",
+            Dimension::MassDensity => "density",
+            Dimension::MassRate => "mass flow rate",
+            Dimension::MolarMass => "molar mass",
+            Dimension::Pressure => "pressure",
+            Dimension::Temperature => "temperature",
+            Dimension::TemperatureDifference => "temperature difference",
+            Dimension::Time => "time",
+            Dimension::Velocity => "speed",
+            Dimension::Volume => "volume",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Converts a value in some unit to SI base units.
+type ToSi = fn(f64) -> f64;
+
+/// The dimension of a unit symbol and its conversion to SI, if it is known.
+fn unit(symbol: &str) -> Option<(Dimension, ToSi)> {
+    let unit: (Dimension, ToSi) = match symbol {
+        "" => (Dimension::Dimensionless, |v| v),
+        "%" => (Dimension::Dimensionless, |v| v / 100.0),
+
+        "m" => (Dimension::Length, |v| Length::new::<meter>(v).get::<meter>()),
+        "km" => (Dimension::Length, |v| Length::new::<kilometer>(v).get::<meter>()),
+        "cm" => (Dimension::Length, |v| Length::new::<centimeter>(v).get::<meter>()),
+        "mm" => (Dimension::Length, |v| Length::new::<millimeter>(v).get::<meter>()),
+        "ft" => (Dimension::Length, |v| Length::new::<foot>(v).get::<meter>()),
+        "in" => (Dimension::Length, |v| Length::new::<inch>(v).get::<meter>()),
+        "mi" => (Dimension::Length, |v| Length::new::<mile>(v).get::<meter>()),
+
+        "kg" => (Dimension::Mass, |v| Mass::new::<kilogram>(v).get::<kilogram>()),
+        "g" => (Dimension::Mass, |v| Mass::new::<gram>(v).get::<kilogram>()),
+        "mg" => (Dimension::Mass, |v| Mass::new::<milligram>(v).get::<kilogram>()),
+        "t" => (Dimension::Mass, |v| Mass::new::<ton>(v).get::<kilogram>()),
+        "lb" => (Dimension::Mass, |v| Mass::new::<pound>(v).get::<kilogram>()),
+        "oz" => (Dimension::Mass, |v| Mass::new::<ounce>(v).get::<kilogram>()),
+
+        "kg/m³" | "kg/m^3" | "kg/m3" => (Dimension::MassDensity, |v| {
+            MassDensity::new::<kilogram_per_cubic_meter>(v).get::<kilogram_per_cubic_meter>()
+        }),
+        // One gram per liter is one kilogram per cubic meter.
+        "g/L" | "g/l" => (Dimension::MassDensity, |v| {
+            MassDensity::new::<kilogram_per_cubic_meter>(v).get::<kilogram_per_cubic_meter>()
+        }),
+        "g/cm³" | "g/cm^3" | "g/cm3" => (Dimension::MassDensity, |v| {
+            MassDensity::new::<gram_per_cubic_centimeter>(v).get::<kilogram_per_cubic_meter>()
+        }),
+
+        "kg/s" => (Dimension::MassRate, |v| {
+            MassRate::new::<kilogram_per_second>(v).get::<kilogram_per_second>()
+        }),
+        "kg/min" => (Dimension::MassRate, |v| {
+            MassRate::new::<kilogram_per_minute>(v).get::<kilogram_per_second>()
+        }),
+        "g/s" => (Dimension::MassRate, |v| {
+            MassRate::new::<gram_per_second>(v).get::<kilogram_per_second>()
+        }),
+        "lb/s" => (Dimension::MassRate, |v| {
+            MassRate::new::<pound_per_second>(v).get::<kilogram_per_second>()
+        }),
+
+        "kg/mol" => (Dimension::MolarMass, |v| {
+            MolarMass::new::<kilogram_per_mole>(v).get::<kilogram_per_mole>()
+        }),
+        "g/mol" => (Dimension::MolarMass, |v| {
+            MolarMass::new::<gram_per_mole>(v).get::<kilogram_per_mole>()
+        }),
+
+        "Pa" => (Dimension::Pressure, |v| Pressure::new::<pascal>(v).get::<pascal>()),
+        "hPa" => (Dimension::Pressure, |v| Pressure::new::<hectopascal>(v).get::<pascal>()),
+        "kPa" => (Dimension::Pressure, |v| Pressure::new::<kilopascal>(v).get::<pascal>()),
+        "MPa" => (Dimension::Pressure, |v| Pressure::new::<megapascal>(v).get::<pascal>()),
+        "bar" => (Dimension::Pressure, |v| Pressure::new::<bar>(v).get::<pascal>()),
+        "mbar" => (Dimension::Pressure, |v| Pressure::new::<millibar>(v).get::<pascal>()),
+        "atm" => (Dimension::Pressure, |v| Pressure::new::<atmosphere>(v).get::<pascal>()),
+        "psi" => (Dimension::Pressure, |v| {
+            Pressure::new::<pound_force_per_square_inch>(v).get::<pascal>()
+        }),
+        "mmHg" => (Dimension::Pressure, |v| {
+            Pressure::new::<millimeter_of_mercury>(v).get::<pascal>()
+        }),
+        "inHg" => (Dimension::Pressure, |v| Pressure::new::<inch_of_mercury>(v).get::<pascal>()),
+
+        "K" => (Dimension::Temperature, |v| {
+            ThermodynamicTemperature::new::<kelvin>(v).get::<kelvin>()
+        }),
+        "°C" | "degC" | "C" => (Dimension::Temperature, |v| {
+            ThermodynamicTemperature::new::<degree_celsius>(v).get::<kelvin>()
+        }),
+        "°F" | "degF" | "F" => (Dimension::Temperature, |v| {
+            ThermodynamicTemperature::new::<degree_fahrenheit>(v).get::<kelvin>()
+        }),
+
+        "s" => (Dimension::Time, |v| Time::new::<second>(v).get::<second>()),
+        "min" => (Dimension::Time, |v| Time::new::<minute>(v).get::<second>()),
+        "h" => (Dimension::Time, |v| Time::new::<hour>(v).get::<second>()),
+        "d" => (Dimension::Time, |v| Time::new::<day>(v).get::<second>()),
+
+        "m/s" => (Dimension::Velocity, |v| {
+            Velocity::new::<meter_per_second>(v).get::<meter_per_second>()
+        }),
+        "km/h" => (Dimension::Velocity, |v| {
+            Velocity::new::<kilometer_per_hour>(v).get::<meter_per_second>()
+        }),
+        "ft/s" => (Dimension::Velocity, |v| {
+            Velocity::new::<foot_per_second>(v).get::<meter_per_second>()
+        }),
+        "mph" => (Dimension::Velocity, |v| {
+            Velocity::new::<mile_per_hour>(v).get::<meter_per_second>()
+        }),
+        "kn" | "kt" => (Dimension::Velocity, |v| Velocity::new::<knot>(v).get::<meter_per_second>()),
+
+        "m³" | "m^3" | "m3" => (Dimension::Volume, |v| Volume::new::<cubic_meter>(v).get::<cubic_meter>()),
+        "L" | "l" => (Dimension::Volume, |v| Volume::new::<liter>(v).get::<cubic_meter>()),
+        "ft³" | "ft^3" | "ft3" => (Dimension::Volume, |v| Volume::new::<cubic_foot>(v).get::<cubic_meter>()),
+
+        _ => return None,
+    };
+    Some(unit)
+}
+
+/// Conversion of a temperature unit to kelvins for a difference of
+/// temperatures, which scales with the unit but does not shift with its zero.
+fn temperature_difference_unit(symbol: &str) -> Option<ToSi> {
+    let to_si: ToSi = match symbol {
+        "K" => |v| {
+            TemperatureInterval::new::<temperature_interval::kelvin>(v)
+                .get::<temperature_interval::kelvin>()
+        },
+        "°C" | "degC" | "C" => |v| {
+            TemperatureInterval::new::<temperature_interval::degree_celsius>(v)
+                .get::<temperature_interval::kelvin>()
+        },
+        "°F" | "degF" | "F" => |v| {
+            TemperatureInterval::new::<temperature_interval::degree_fahrenheit>(v)
+                .get::<temperature_interval::kelvin>()
+        },
+        _ => return None,
+    };
+    Some(to_si)
+}
+
+/// Why a quantity could not be read.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnitError {
+    /// The text does not start with a number.
+    InvalidNumber(String),
+    /// The unit is not one we know.
+    UnknownUnit { text: String, unit: String },
+    /// The unit measures a different kind of quantity than the field.
+    Mismatch { text: String, expected: Dimension, found: Dimension },
+}
+
+impl fmt::Display for UnitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnitError::InvalidNumber(text) => write!(f, "{text:?} is not a number with a unit"),
+            UnitError::UnknownUnit { text, unit } => write!(f, "unknown unit {unit:?} in {text:?}"),
+            UnitError::Mismatch { text, expected, found } => write!(
+                f,
+                "expected a {expected} (e.g. \"1 {}\") but {text:?} is a {found}",
+                expected.si_unit()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UnitError {}
+
+/// Read a number followed by an optional unit and convert it to the SI base
+/// unit of a dimension. A number without a unit is already in SI units.
+pub fn parse_quantity(text: &str, expected: Dimension) -> Result<f64, UnitError> {
+    let trimmed = text.trim();
+    let split = trimmed
+        .char_indices()
+        .find(|&(i, c)| {
+            !(c.is_ascii_digit()
+                || c == '.'
+                || c == '_'
+                || ((c == '-' || c == '+') && (i == 0 || trimmed[..i].ends_with(['e', 'E'])))
+                || ((c == 'e' || c == 'E') && i > 0))
+        })
+        .map_or(trimmed.len(), |(i, _)| i);
+    let (number, symbol) = trimmed.split_at(split);
+    let value: f64 = number
+        .replace('_', "")
+        .parse()
+        .map_err(|_| UnitError::InvalidNumber(text.to_string()))?;
+    let symbol = symbol.trim();
+    if expected == Dimension::TemperatureDifference
+        && let Some(to_si) = temperature_difference_unit(symbol)
+    {
+        return Ok(to_si(value));
+    }
+    let Some((dimension, to_si)) = unit(symbol) else {
+        return Err(UnitError::UnknownUnit {
+            text: text.to_string(),
+            unit: symbol.to_string(),
+        });
+    };
+    // Bare numbers fit any field.
+    if symbol.is_empty() {
+        return Ok(value);
+    }
+    if dimension != expected {
+        return Err(UnitError::Mismatch {
+            text: text.to_string(),
+            expected,
+            found: dimension,
+        });
+    }
+    Ok(to_si(value))
+}
+
+struct QuantityVisitor(Dimension);
+
+impl Visitor<'_> for QuantityVisitor {
+    type Value = f64;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a {} in {} or a string with a unit", self.0, self.0.si_unit())
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<f64, E> {
+        Ok(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<f64, E> {
+        Ok(value as f64)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<f64, E> {
+        Ok(value as f64)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<f64, E> {
+        parse_quantity(value, self.0).map_err(E::custom)
+    }
+}
+
+/// Deserialize a quantity of a dimension into its SI value.
+pub fn quantity<'de, D: Deserializer<'de>>(deserializer: D, dimension: Dimension) -> Result<f64, D::Error> {
+    deserializer.deserialize_any(QuantityVisitor(dimension))
+}
+
+macro_rules! deserialize_dimension {
+    ($($(#[$doc:meta])* $name:ident => $dimension:ident;)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+                quantity(deserializer, Dimension::$dimension).map(|value| value as f32)
+            }
+        )*
+    };
+}
+
+deserialize_dimension! {
+    /// A plain number, or a percentage such as `"12 %"`.
+    dimensionless => Dimensionless;
+    /// Length (m).
+    length => Length;
+    /// Mass (kg).
+    mass => Mass;
+    /// Density (kg/m³).
+    mass_density => MassDensity;
+    /// Mass flow rate (kg/s).
+    mass_rate => MassRate;
+    /// Molar mass (kg/mol).
+    molar_mass => MolarMass;
+    /// Pressure (Pa).
+    pressure => Pressure;
+    /// Temperature (K).
+    temperature => Temperature;
+    /// Temperature difference (K).
+    temperature_difference => TemperatureDifference;
+    /// Speed (m/s).
+    velocity => Velocity;
+    /// Volume (m³).
+    volume => Volume;
+}
+
+/// Time (s), kept in double precision for long runs.
+pub fn time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    quantity(deserializer, Dimension::Time)
+}
+
+/// An optional time (s).
+pub fn optional_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    struct OptionalTime;
+
+    impl<'de> Visitor<'de> for OptionalTime {
+        type Value = Option<f64>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an optional time")
+        }
+
+        fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+            time(deserializer).map(Some)
+        }
+    }
+
+    deserializer.deserialize_option(OptionalTime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() <= 1e-6 * expected.abs().max(1.0),
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn bare_numbers_are_si() {
+        assert_close(parse_quantity("12.5", Dimension::Mass).unwrap(), 12.5);
+        assert_close(parse_quantity(" -3e2 ", Dimension::Length).unwrap(), -300.0);
+        assert_close(parse_quantity("1_000", Dimension::Time).unwrap(), 1000.0);
+    }
+
+    #[test]
+    fn units_convert_to_si() {
+        assert_close(parse_quantity("1200 g", Dimension::Mass).unwrap(), 1.2);
+        assert_close(parse_quantity("2.2 lb", Dimension::Mass).unwrap(), 2.2 * 0.45359237);
+        assert_close(parse_quantity("5250 ft", Dimension::Length).unwrap(), 5250.0 * 0.3048);
+        assert_close(parse_quantity("1.5km", Dimension::Length).unwrap(), 1500.0);
+        assert_close(parse_quantity("2 h", Dimension::Time).unwrap(), 7200.0);
+        assert_close(parse_quantity("10 g/s", Dimension::MassRate).unwrap(), 0.01);
+        assert_close(parse_quantity("1013.25 hPa", Dimension::Pressure).unwrap(), 101_325.0);
+        assert_close(parse_quantity("36 km/h", Dimension::Velocity).unwrap(), 10.0);
+        assert_close(parse_quantity("4 g/mol", Dimension::MolarMass).unwrap(), 0.004);
+        assert_close(parse_quantity("1 g/cm³", Dimension::MassDensity).unwrap(), 1000.0);
+        assert_close(parse_quantity("250 L", Dimension::Volume).unwrap(), 0.25);
+        assert_close(parse_quantity("60 %", Dimension::Dimensionless).unwrap(), 0.6);
+    }
+
+    #[test]
+    fn temperatures_shift_but_differences_do_not() {
+        assert_close(parse_quantity("10 °C", Dimension::Temperature).unwrap(), 283.15);
+        assert_close(parse_quantity("32 °F", Dimension::Temperature).unwrap(), 273.15);
+        assert_close(parse_quantity("10 °C", Dimension::TemperatureDifference).unwrap(), 10.0);
+        assert_close(parse_quantity("18 °F", Dimension::TemperatureDifference).unwrap(), 10.0);
+        assert_close(parse_quantity("7 K", Dimension::TemperatureDifference).unwrap(), 7.0);
+    }
+
+    #[test]
+    fn units_of_the_wrong_dimension_are_rejected() {
+        assert_eq!(
+            parse_quantity("18 °C", Dimension::Mass),
+            Err(UnitError::Mismatch {
+                text: "18 °C".to_string(),
+                expected: Dimension::Mass,
+                found: Dimension::Temperature,
+            })
+        );
+        assert_eq!(
+            parse_quantity("3 m", Dimension::TemperatureDifference),
+            Err(UnitError::Mismatch {
+                text: "3 m".to_string(),
+                expected: Dimension::TemperatureDifference,
+                found: Dimension::Length,
+            })
+        );
+        let message = parse_quantity("5 kg", Dimension::Length).unwrap_err().to_string();
+        assert_eq!(message, r#"expected a length (e.g. "1 m") but "5 kg" is a mass"#);
+    }
+
+    #[test]
+    fn unknown_units_and_missing_numbers_are_rejected() {
+        assert_eq!(
+            parse_quantity("5 furlongs", Dimension::Length),
+            Err(UnitError::UnknownUnit {
+                text: "5 furlongs".to_string(),
+                unit: "furlongs".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_quantity("kg", Dimension::Mass),
+            Err(UnitError::InvalidNumber("kg".to_string()))
+        );
+        assert_eq!(
+            parse_quantity("", Dimension::Mass),
+            Err(UnitError::InvalidNumber(String::new()))
+        );
+    }
+
+    #[test]
+    fn deserializes_numbers_and_strings() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Fields {
+            #[serde(deserialize_with = "mass")]
+            mass: f32,
+            #[serde(deserialize_with = "length")]
+            length: f32,
+            #[serde(deserialize_with = "optional_time")]
+            duration: Option<f64>,
+        }
+        let fields: Fields = ron::from_str(r#"(mass: "500 g", length: 3, duration: Some("1 min"))"#).unwrap();
+        assert_eq!(fields.mass, 0.5);
+        assert_eq!(fields.length, 3.0);
+        assert_eq!(fields.duration, Some(60.0));
+        let error = ron::from_str::<Fields>(r#"(mass: "5 m", length: 3, duration: None)"#).unwrap_err();
+        assert!(error.to_string().contains("expected a mass"), "{error}");
+    }
+}
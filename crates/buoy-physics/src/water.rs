@@ -0,0 +1,135 @@
+//! Water as an ambient medium below a free surface.
+//!
+//! Water is treated as incompressible: its density depends on temperature and
+//! salinity but not on depth. Seawater density follows the UNESCO 1981
+//! one-atmosphere equation of state (EOS-80), which takes practical salinity
+//! on the PSS-78 scale.
+//!
+//! Reference:
+//! - https://unesdoc.unesco.org/ark:/48223/pf0000047363
+//! - Millero & Poisson (1981), International one-atmosphere equation of
+//!   state of seawater, Deep-Sea Research 28A
+
+use avian3d::prelude::Collider;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use uom::si::{
+    acceleration::meter_per_second_squared,
+    f32::*,
+    length::meter,
+    mass_density::kilogram_per_cubic_meter,
+    pressure::pascal,
+    thermodynamic_temperature::degree_celsius,
+};
+
+use crate::geometry::volume_below_plane;
+
+/// The fluid surrounding a body.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Reflect, Deserialize, Serialize)]
+pub enum Medium {
+    #[default]
+    Air,
+    Water,
+}
+
+/// A body of water whose free surface is a horizontal plane.
+///
+/// Water is opt-in: insert this resource to simulate floats, splashdowns or
+/// subsurface buoys. Without it, the whole world is atmosphere.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Water {
+    /// Height (m) of the free surface, usually sea level.
+    pub surface_height: f32,
+    /// Practical salinity (PSS-78), a dimensionless conductivity ratio close
+    /// to the salt content in g/kg. Typical open ocean water is about 35.
+    pub salinity: f32,
+    /// Temperature of the water, uniform with depth.
+    pub temperature: ThermodynamicTemperature,
+}
+
+impl Default for Water {
+    fn default() -> Self {
+        Water::seawater()
+    }
+}
+
+impl Water {
+    /// Typical open-ocean seawater at sea level.
+    pub fn seawater() -> Self {
+        Water {
+            surface_height: 0.0,
+            salinity: 35.0,
+            temperature: ThermodynamicTemperature::new::<degree_celsius>(15.0),
+        }
+    }
+
+    /// Fresh water at sea level.
+    pub fn freshwater() -> Self {
+        Water {
+            salinity: 0.0,
+            ..Water::seawater()
+        }
+    }
+
+    /// Density (kg/m³) of the water, the same at every depth.
+    pub fn density(&self) -> MassDensity {
+        seawater_density(self.temperature, self.salinity)
+    }
+
+    /// Depth (m) of a position below the free surface. Negative above it.
+    pub fn depth(&self, position: Vec3) -> Length {
+        Length::new::<meter>(self.surface_height - position.y)
+    }
+
+    /// Which medium a position is in.
+    pub fn medium_at(&self, position: Vec3) -> Medium {
+        if position.y < self.surface_height {
+            Medium::Water
+        } else {
+            Medium::Air
+        }
+    }
+
+    /// Hydrostatic pressure (Pa) at a position below the surface, given the
+    /// atmospheric pressure acting on the surface and the gravity of the
+    /// body, see [`crate::planet::PlanetaryBody::gravity`].
+    pub fn pressure(&self, position: Vec3, surface_pressure: Pressure, gravity: Acceleration) -> Pressure {
+        let depth = self.depth(position).get::<meter>().max(0.0);
+        surface_pressure
+            + Pressure::new::<pascal>(
+                self.density().get::<kilogram_per_cubic_meter>()
+                    * gravity.get::<meter_per_second_squared>()
+                    * depth,
+            )
+    }
+
+    /// Volume (m³) of a collider below the free surface and the world-space
+    /// center of buoyancy of that volume.
+    pub fn submerged_volume(&self, collider: &Collider, position: Vec3, rotation: Quat) -> (f32, Vec3) {
+        volume_below_plane(collider, position, rotation, self.surface_height)
+    }
+}
+
+/// Density (kg/m³) of seawater at one standard atmosphere from its temperature
+/// and practical salinity (PSS-78), per the UNESCO 1981 equation of state.
+/// Valid for 0–40 °C and practical salinities of 0–42; zero salinity gives
+/// pure water.
+pub fn seawater_density(temperature: ThermodynamicTemperature, salinity: f32) -> MassDensity {
+    let t = temperature.get::<degree_celsius>() as f64;
+    let s = salinity.max(0.0) as f64;
+
+    // Standard mean ocean water
+    let pure_water = 999.842594 + 6.793952e-2 * t - 9.095290e-3 * t.powi(2)
+        + 1.001685e-4 * t.powi(3)
+        - 1.120083e-6 * t.powi(4)
+        + 6.536332e-9 * t.powi(5);
+
+    let a = 8.24493e-1 - 4.0899e-3 * t + 7.6438e-5 * t.powi(2) - 8.2467e-7 * t.powi(3)
+        + 5.3875e-9 * t.powi(4);
+    let b = -5.72466e-3 + 1.0227e-4 * t - 1.6546e-6 * t.powi(2);
+    let c = 4.8314e-4;
+
+    MassDensity::new::<kilogram_per_cubic_meter>(
+        (pure_water + a * s + b * s.powf(1.5) + c * s * s) as f32,
+    )
+}
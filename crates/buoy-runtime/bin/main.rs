@@ -1,17 +1,433 @@
-#![cfg_attr(not(feature = "dev"), windows_subsystem = "windows")]
-
-use std::time::Duration;
-use bevy::{app::ScheduleRunnerPlugin, prelude::*};
-use buoy_runtime::BuoyDefaultPlugins;
-
-fn main() {
-    App::new()
-        .add_plugins((
-            DefaultPlugins
-                .set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
-                    1.0 / 60.0,
-                ))),
-            BuoyDefaultPlugins,
-        ))
-        .run();
+use std::{path::PathBuf, time::Duration};
+
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    log::{Level, LogPlugin},
+    prelude::*,
+    state::app::StatesPlugin,
+    time::TimeUpdateStrategy,
+};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use uom::si::{
+    f32::{Length, Mass, Pressure, ThermodynamicTemperature, Velocity},
+    length::meter,
+    mass::kilogram,
+    pressure::pascal,
+    thermodynamic_temperature::kelvin,
+    velocity::meter_per_second,
+    volume::cubic_meter,
+};
+
+use buoy_physics::{
+    atmosphere::Atmosphere,
+    clock::Epoch,
+    ideal_gas::{ideal_gas_density, GasSpecies, IdealGas},
+    inflation::{burst_altitude, latex_burst_diameter, FlightTrain, InflationTarget, LaunchConditions},
+    planet::PlanetaryBody,
+    units::{parse_quantity, Dimension, UnitError},
+};
+
+use buoy_runtime::{
+    batch::{self, BatchOutputs, EndConditions, EXIT_IO_ERROR, EXIT_USAGE, MAX_STEP},
+    config::{ScenarioConfig, ScenarioError},
+    telemetry::{TelemetryConfig, TelemetryField},
+    BuoyDefaultPlugins, BuoyHeadlessPlugins,
+};
+
+/// Fly a balloon scenario.
+#[derive(Parser, Debug)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Scenario file to fly.
+    #[arg(default_value = "assets/configs/demo.scenario.ron")]
+    scenario: PathBuf,
+
+    /// Stop after this many simulated seconds, overriding the scenario.
+    #[arg(long, value_name = "SECONDS")]
+    duration: Option<f64>,
+
+    /// Stop when the vehicles land.
+    #[arg(long)]
+    until_landing: bool,
+
+    /// Stop when the envelopes of the vehicles burst.
+    #[arg(long)]
+    until_burst: bool,
+
+    /// Only watch this vehicle for landing or burst, overriding the scenario.
+    #[arg(long, value_name = "NAME")]
+    vehicle: Option<String>,
+
+    /// Give up after this much simulated time if no end condition has been
+    /// met. The interactive console has no limit.
+    #[arg(long, value_name = "TIME", value_parser = time, default_value = "2 d")]
+    time_limit: f64,
+
+    /// Fixed time step in seconds, at most 0.25.
+    #[arg(long, value_name = "SECONDS", default_value_t = 1.0 / 60.0)]
+    dt: f64,
+
+    /// Pace the simulation in real time instead of running it as fast as
+    /// possible.
+    #[arg(long)]
+    realtime: bool,
+
+    /// Record the position and velocity of every balloon at every step to
+    /// this file, overriding the scenario's telemetry. Short for --telemetry
+    /// PATH --telemetry-interval 0 --telemetry-fields position,velocity.
+    #[arg(long, value_name = "PATH", conflicts_with = "telemetry")]
+    trajectory: Option<PathBuf>,
+
+    /// Record telemetry to this file, overriding the scenario. Files ending
+    /// in .parquet are written as Parquet, anything else as CSV.
+    #[arg(long, value_name = "PATH")]
+    telemetry: Option<PathBuf>,
+
+    /// Time between telemetry samples, e.g. "10 s".
+    #[arg(long, value_name = "TIME", value_parser = time, requires = "telemetry")]
+    telemetry_interval: Option<f64>,
+
+    /// Fields to record, overriding the scenario.
+    #[arg(long, value_name = "FIELD", value_enum, value_delimiter = ',', requires = "telemetry")]
+    telemetry_fields: Vec<Field>,
+
+    /// Write a summary of the run to this RON file when it ends.
+    #[arg(long, value_name = "PATH")]
+    summary: Option<PathBuf>,
+
+    /// Most verbose level of log messages to show.
+    #[arg(long, value_enum, default_value_t = LogLevel::Info)]
+    log_level: LogLevel,
+
+    /// Open the interactive console. The run is paced in real time.
+    #[arg(long)]
+    repl: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Work out how much gas a balloon needs and where it will burst.
+    Inflate(InflateArgs),
+}
+
+/// Quantities may be given with units, e.g. "1200 g" or "5 lb".
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("target").required(true)))]
+struct InflateArgs {
+    /// Lift gas, e.g. helium or hydrogen.
+    #[arg(long, default_value = "helium")]
+    gas: String,
+
+    /// Mass of the balloon (kg).
+    #[arg(long, value_name = "MASS", value_parser = mass)]
+    balloon_mass: f32,
+
+    /// Mass of everything hung below the balloon (kg).
+    #[arg(long, value_name = "MASS", value_parser = mass, default_value = "0")]
+    payload_mass: f32,
+
+    /// Fill to this free lift (kg).
+    #[arg(long, value_name = "MASS", value_parser = mass, group = "target")]
+    free_lift: Option<f32>,
+
+    /// Fill to this neck lift (kg).
+    #[arg(long, value_name = "MASS", value_parser = mass, group = "target")]
+    neck_lift: Option<f32>,
+
+    /// Fill to rise at this rate (m/s) after launch.
+    #[arg(long, value_name = "SPEED", value_parser = velocity, group = "target")]
+    ascent_rate: Option<f32>,
+
+    /// Drag coefficient of the inflated balloon.
+    #[arg(long, default_value_t = 0.47)]
+    drag_coefficient: f32,
+
+    /// Altitude of the launch site (m).
+    #[arg(long, value_name = "LENGTH", value_parser = length, default_value = "0")]
+    altitude: f32,
+
+    /// Air temperature at the launch site (K), instead of the standard
+    /// atmosphere.
+    #[arg(long, value_name = "TEMPERATURE", value_parser = temperature)]
+    temperature: Option<f32>,
+
+    /// Air pressure at the launch site (Pa), instead of the standard
+    /// atmosphere.
+    #[arg(long, value_name = "PRESSURE", value_parser = pressure)]
+    pressure: Option<f32>,
+
+    /// Diameter (m) at which the balloon bursts. Defaults to that of a latex
+    /// sounding balloon of the given mass.
+    #[arg(long, value_name = "LENGTH", value_parser = length)]
+    burst_diameter: Option<f32>,
+}
+
+fn time(text: &str) -> Result<f64, UnitError> {
+    parse_quantity(text, Dimension::Time)
+}
+
+fn mass(text: &str) -> Result<f32, UnitError> {
+    parse_quantity(text, Dimension::Mass).map(|value| value as f32)
+}
+
+fn velocity(text: &str) -> Result<f32, UnitError> {
+    parse_quantity(text, Dimension::Velocity).map(|value| value as f32)
+}
+
+fn length(text: &str) -> Result<f32, UnitError> {
+    parse_quantity(text, Dimension::Length).map(|value| value as f32)
+}
+
+fn temperature(text: &str) -> Result<f32, UnitError> {
+    parse_quantity(text, Dimension::Temperature).map(|value| value as f32)
+}
+
+fn pressure(text: &str) -> Result<f32, UnitError> {
+    parse_quantity(text, Dimension::Pressure).map(|value| value as f32)
+}
+
+/// Telemetry fields, as named on the command line.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Position,
+    Velocity,
+    Forces,
+    Gas,
+    Temperatures,
+    Strain,
+}
+
+impl From<Field> for TelemetryField {
+    fn from(field: Field) -> Self {
+        match field {
+            Field::Position => TelemetryField::Position,
+            Field::Velocity => TelemetryField::Velocity,
+            Field::Forces => TelemetryField::Forces,
+            Field::Gas => TelemetryField::Gas,
+            Field::Temperatures => TelemetryField::Temperatures,
+            Field::Strain => TelemetryField::Strain,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => Level::ERROR,
+            LogLevel::Warn => Level::WARN,
+            LogLevel::Info => Level::INFO,
+            LogLevel::Debug => Level::DEBUG,
+            LogLevel::Trace => Level::TRACE,
+        }
+    }
+}
+
+fn main() -> AppExit {
+    let cli = Cli::parse();
+    if let Some(Command::Inflate(args)) = cli.command {
+        return inflate(args);
+    }
+    if !cli.dt.is_finite() || cli.dt <= 0.0 {
+        eprintln!("error: the time step must be a positive number of seconds");
+        return AppExit::from_code(EXIT_USAGE);
+    }
+    if cli.dt > MAX_STEP {
+        eprintln!("error: time steps longer than {MAX_STEP} s make the flight unstable");
+        return AppExit::from_code(EXIT_USAGE);
+    }
+    if cli.duration.is_some_and(|duration| !duration.is_finite() || duration < 0.0) {
+        eprintln!("error: the duration must be a non-negative number of seconds");
+        return AppExit::from_code(EXIT_USAGE);
+    }
+    let mut scenario = match ScenarioConfig::load(&cli.scenario) {
+        Ok(scenario) => scenario,
+        Err(ScenarioError::Invalid(report)) => {
+            eprintln!("error: {report}");
+            return AppExit::from_code(EXIT_IO_ERROR);
+        }
+        Err(error) => {
+            eprintln!("error: {}: {error}", cli.scenario.display());
+            return AppExit::from_code(EXIT_IO_ERROR);
+        }
+    };
+    if !(cli.time_limit.is_finite() && cli.time_limit > 0.0) {
+        eprintln!("error: the time limit must be a positive time");
+        return AppExit::from_code(EXIT_USAGE);
+    }
+    if let Some(name) = &cli.vehicle
+        && !scenario.vehicles.iter().any(|vehicle| vehicle.name == *name)
+    {
+        eprintln!("error: the scenario has no vehicle named {name:?}");
+        return AppExit::from_code(EXIT_USAGE);
+    }
+    // Flags add to the end conditions the scenario asks for, and are
+    // checked along with the rest of it when it is spawned.
+    let conditions = &mut scenario.end_conditions;
+    conditions.duration = cli.duration.or(conditions.duration);
+    conditions.landing |= cli.until_landing;
+    conditions.burst |= cli.until_burst;
+    if cli.vehicle.is_some() {
+        conditions.vehicle = cli.vehicle;
+    }
+    let report = scenario.validate();
+    if !report.is_empty() {
+        let text = std::fs::read_to_string(&cli.scenario).unwrap_or_default();
+        let report = report.with_source(cli.scenario, &text);
+        eprintln!("error: {report}");
+        return AppExit::from_code(EXIT_IO_ERROR);
+    }
+    if let Some(path) = cli.telemetry {
+        let scenario_telemetry = scenario.telemetry.take();
+        scenario.telemetry = Some(TelemetryConfig {
+            path,
+            interval: cli
+                .telemetry_interval
+                .or(scenario_telemetry.as_ref().map(|telemetry| telemetry.interval))
+                .unwrap_or(1.0),
+            fields: if cli.telemetry_fields.is_empty() {
+                scenario_telemetry.map_or_else(TelemetryField::all, |telemetry| telemetry.fields)
+            } else {
+                cli.telemetry_fields.into_iter().map(TelemetryField::from).collect()
+            },
+        });
+    }
+    if let Some(path) = cli.trajectory {
+        scenario.telemetry = Some(TelemetryConfig::trajectory(path));
+    }
+
+    let dt = Duration::from_secs_f64(cli.dt);
+    let realtime = cli.realtime || cli.repl;
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(if realtime { dt } else { Duration::ZERO })),
+        LogPlugin {
+            level: cli.log_level.into(),
+            ..default()
+        },
+        TransformPlugin,
+        StatesPlugin,
+        buoy_physics::BuoyPhysicsPlugin,
+        batch::plugin,
+    ));
+    if cli.repl {
+        app.add_plugins(BuoyDefaultPlugins);
+    } else {
+        app.add_plugins(BuoyHeadlessPlugins);
+    }
+
+    // Every frame advances by exactly one step unless the run is paced by
+    // the wall clock.
+    app.insert_resource(Time::<Fixed>::from_duration(dt));
+    // The virtual clock would otherwise cap each frame at a quarter second,
+    // so longer steps would be taken less often than every frame.
+    app.world_mut().resource_mut::<Time<Virtual>>().set_max_delta(dt);
+    if !realtime {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(dt));
+    }
+    app.insert_resource(EndConditions {
+        time_limit: (!cli.repl).then_some(cli.time_limit),
+        ..scenario.end_conditions()
+    });
+    app.insert_resource(BatchOutputs { summary: cli.summary });
+    app.add_systems(
+        Startup,
+        move |mut commands: Commands, mut exit: EventWriter<AppExit>| {
+            if let Err(error) = scenario.spawn(&mut commands) {
+                error!("{error}");
+                exit.write(AppExit::from_code(EXIT_IO_ERROR));
+            }
+        },
+    );
+    app.run()
+}
+
+/// Print the inflation that reaches the requested lift or ascent rate.
+fn inflate(args: InflateArgs) -> AppExit {
+    let species = match GasSpecies::from_species_name(args.gas) {
+        Ok(species) => species,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return AppExit::from_code(EXIT_USAGE);
+        }
+    };
+    let train = FlightTrain {
+        species: species.clone(),
+        balloon_mass: Mass::new::<kilogram>(args.balloon_mass),
+        payload_mass: Mass::new::<kilogram>(args.payload_mass),
+        drag_coefficient: args.drag_coefficient,
+    };
+    let target = match (args.free_lift, args.neck_lift, args.ascent_rate) {
+        (Some(free_lift), _, _) => InflationTarget::FreeLift(Mass::new::<kilogram>(free_lift)),
+        (_, Some(neck_lift), _) => InflationTarget::NeckLift(Mass::new::<kilogram>(neck_lift)),
+        (_, _, Some(rate)) => InflationTarget::AscentRate(Velocity::new::<meter_per_second>(rate)),
+        _ => unreachable!("clap requires a target"),
+    };
+
+    let planet = PlanetaryBody::earth();
+    let atmosphere = planet.atmosphere();
+    let epoch = Epoch::default();
+    let site = Vec3::Y * args.altitude;
+    let mut conditions = LaunchConditions::at(&atmosphere, &planet, site, epoch);
+    if let Some(temperature) = args.temperature {
+        conditions.temperature = ThermodynamicTemperature::new::<kelvin>(temperature);
+    }
+    if let Some(pressure) = args.pressure {
+        conditions.pressure = Pressure::new::<pascal>(pressure);
+    }
+    if args.temperature.is_some() || args.pressure.is_some() {
+        conditions.density = ideal_gas_density(conditions.temperature, conditions.pressure, &GasSpecies::air());
+    }
+
+    let inflation = match train.inflate(target, &conditions) {
+        Ok(inflation) => inflation,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return AppExit::from_code(EXIT_USAGE);
+        }
+    };
+    let gas = IdealGas::new(species, conditions.temperature, conditions.pressure, inflation.gas_mass);
+    let burst_diameter = args
+        .burst_diameter
+        .map_or_else(|| latex_burst_diameter(train.balloon_mass), Length::new::<meter>);
+    let burst = burst_altitude(&gas, burst_diameter, &atmosphere, Length::new::<meter>(args.altitude), epoch);
+
+    println!(
+        "Gas:            {:.3} kg of {} ({:.2} m³ at {:.1} K and {:.0} Pa)",
+        inflation.gas_mass.get::<kilogram>(),
+        gas.species.name,
+        inflation.volume.get::<cubic_meter>(),
+        conditions.temperature.get::<kelvin>(),
+        conditions.pressure.get::<pascal>(),
+    );
+    println!("Diameter:       {:.2} m", 2.0 * inflation.radius.get::<meter>());
+    println!("Gross lift:     {:.3} kg", inflation.gross_lift.get::<kilogram>());
+    println!("Neck lift:      {:.3} kg", inflation.neck_lift.get::<kilogram>());
+    println!("Free lift:      {:.3} kg", inflation.free_lift.get::<kilogram>());
+    let ascent_rate = inflation.ascent_rate.get::<meter_per_second>();
+    println!("Ascent rate:    {ascent_rate:.2} m/s");
+    println!("Burst diameter: {:.2} m", burst_diameter.get::<meter>());
+    match burst {
+        Some(altitude) => {
+            let altitude = altitude.get::<meter>();
+            print!("Burst altitude: {altitude:.0} m");
+            if ascent_rate > 0.0 {
+                print!(", about {:.0} min after launch", (altitude - args.altitude) / ascent_rate / 60.0);
+            }
+            println!();
+        }
+        None => println!("Burst altitude: none, the balloon floats below {:.0} m", Atmosphere::MAX_ALTITUDE),
+    }
+    AppExit::Success
 }
@@ -0,0 +1,331 @@
+//! Headless batch runs.
+//!
+//! A batch run flies a scenario without a window or console until one of its
+//! [`EndConditions`] is met, records the flight and exits with a status code
+//! that scripts can check:
+//!
+//! | Code | Meaning                                                  |
+//! |------|----------------------------------------------------------|
+//! | 0    | An end condition was met.                                |
+//! | 1    | The scenario or an output file could not be used.        |
+//! | 2    | The command line was invalid.                            |
+//! | 3    | The simulation faulted, e.g. its state became non-finite. |
+//! | 4    | The time limit passed before any end condition was met.  |
+//!
+//! Simulated time is measured by the [`SimulationClock`], so runs end at the
+//! same point whether they are paced in real time or not. The trajectory is
+//! recorded as telemetry, which is closed along with the run.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use buoy_physics::{clock::SimulationClock, failure::Failed, units};
+
+use crate::{objects::Balloon, telemetry::TelemetryRecorder, RuntimeState};
+
+/// Exit code when the scenario or an output file could not be used.
+pub const EXIT_IO_ERROR: u8 = 1;
+/// Exit code when the command line was invalid.
+pub const EXIT_USAGE: u8 = 2;
+/// Exit code when the simulation faulted.
+pub const EXIT_FAULTED: u8 = 3;
+/// Exit code when the time limit passed first.
+pub const EXIT_TIME_LIMIT: u8 = 4;
+
+/// Longest fixed step (s) a batch run takes. Drag and buoyancy are integrated
+/// explicitly, and the ascent of a sounding balloon diverges at steps not
+/// much longer than this.
+pub const MAX_STEP: f64 = 0.25;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<EndConditions>();
+    app.init_resource::<FlightRecord>();
+    app.add_systems(OnEnter(RuntimeState::Faulted), finish_faulted);
+    app.add_systems(
+        FixedLast,
+        (record_flight, check_end_conditions)
+            .chain()
+            .run_if(in_state(RuntimeState::Running)),
+    );
+}
+
+/// When a batch run stops. A run with no end conditions goes on until it is
+/// interrupted.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct EndConditions {
+    /// Simulated seconds after which the run stops.
+    #[serde(deserialize_with = "units::optional_time")]
+    pub duration: Option<f64>,
+    /// Stop once the watched vehicles have come back down to the ground.
+    pub landing: bool,
+    /// Stop once the envelopes of the watched vehicles have failed.
+    pub burst: bool,
+    /// Name of the one vehicle whose landing or burst ends the run. Every
+    /// vehicle is watched if none is named.
+    pub vehicle: Option<String>,
+    /// Altitude (m) of the ground a landing is measured against.
+    #[serde(skip)]
+    pub ground_altitude: f32,
+    /// Simulated seconds after which the run is cut short if no other end
+    /// condition has been met, e.g. a balloon that floats instead of
+    /// bursting.
+    #[serde(skip)]
+    pub time_limit: Option<f64>,
+}
+
+/// Why a batch run ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum RunOutcome {
+    /// The run lasted its full duration.
+    Duration,
+    /// The watched vehicles landed, or burst and then landed.
+    Landing,
+    /// The envelopes of the watched vehicles failed.
+    Burst,
+    /// The simulation faulted.
+    Faulted,
+    /// The time limit passed before any end condition was met.
+    TimeLimit,
+}
+
+impl RunOutcome {
+    /// The status the process exits with.
+    pub fn exit(self) -> AppExit {
+        match self {
+            RunOutcome::Faulted => AppExit::from_code(EXIT_FAULTED),
+            RunOutcome::TimeLimit => AppExit::from_code(EXIT_TIME_LIMIT),
+            _ => AppExit::Success,
+        }
+    }
+}
+
+/// Files a batch run writes to when it ends.
+#[derive(Resource, Debug, Default)]
+pub struct BatchOutputs {
+    /// Where the [`RunSummary`] is written when the run ends.
+    pub summary: Option<PathBuf>,
+}
+
+/// What happened over a batch run, written to the summary file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RunSummary {
+    pub outcome: RunOutcome,
+    /// Simulated seconds from the start to the end of the run.
+    pub elapsed: f64,
+    /// UTC epoch at the end of the run.
+    pub epoch: String,
+    /// Number of fixed steps taken.
+    pub steps: u64,
+    /// Highest altitude (m) any vehicle reached.
+    pub max_altitude: f32,
+    /// How each vehicle fared.
+    pub vehicles: Vec<VehicleSummary>,
+}
+
+/// What happened to one vehicle over a batch run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VehicleSummary {
+    pub name: String,
+    /// Highest altitude (m) the vehicle reached.
+    pub max_altitude: f32,
+    /// Position (m) at the end of the run.
+    pub final_position: [f32; 3],
+    /// Velocity (m/s) at the end of the run.
+    pub final_velocity: [f32; 3],
+    pub landed: bool,
+    pub burst: bool,
+}
+
+/// Running record of the flight.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct FlightRecord {
+    pub steps: u64,
+    pub vehicles: Vec<VehicleRecord>,
+}
+
+/// Running record of one vehicle.
+#[derive(Debug, Clone)]
+pub struct VehicleRecord {
+    pub entity: Entity,
+    pub name: String,
+    pub max_altitude: f32,
+    pub position: Vec3,
+    pub velocity: Vec3,
+    /// Whether the vehicle has come back down to the ground.
+    pub landed: bool,
+    /// Whether the envelope has failed.
+    pub burst: bool,
+}
+
+impl FlightRecord {
+    /// Highest altitude (m) any vehicle reached.
+    pub fn max_altitude(&self) -> f32 {
+        self.vehicles
+            .iter()
+            .map(|vehicle| vehicle.max_altitude)
+            .reduce(f32::max)
+            .unwrap_or_default()
+    }
+
+    /// The vehicles whose landing or burst ends the run.
+    fn watched<'a>(&'a self, conditions: &'a EndConditions) -> impl Iterator<Item = &'a VehicleRecord> {
+        self.vehicles.iter().filter(|vehicle| {
+            conditions
+                .vehicle
+                .as_ref()
+                .is_none_or(|name| *name == vehicle.name)
+        })
+    }
+}
+
+/// Note the state of every vehicle.
+fn record_flight(
+    balloons: Query<(Entity, Option<&Name>, &Position, &LinearVelocity, Has<Failed>), With<Balloon>>,
+    conditions: Res<EndConditions>,
+    mut record: ResMut<FlightRecord>,
+    mut next_state: ResMut<NextState<RuntimeState>>,
+) {
+    if balloons.is_empty() {
+        return;
+    }
+    let first_step = record.steps == 0;
+    record.steps += 1;
+    for (entity, name, position, velocity, failed) in balloons.iter() {
+        let name = name.map_or_else(|| "Balloon".to_string(), |name| name.to_string());
+        if !position.0.is_finite() || !velocity.0.is_finite() {
+            error!("State of {name} is not finite: {:?}, {:?}", position.0, velocity.0);
+            next_state.set(RuntimeState::Faulted);
+            return;
+        }
+        let index = match record.vehicles.iter().position(|vehicle| vehicle.entity == entity) {
+            Some(index) => index,
+            None => {
+                record.vehicles.push(VehicleRecord {
+                    entity,
+                    name: name.clone(),
+                    max_altitude: position.y,
+                    position: position.0,
+                    velocity: velocity.0,
+                    landed: false,
+                    burst: false,
+                });
+                record.vehicles.len() - 1
+            }
+        };
+        let vehicle = &mut record.vehicles[index];
+        vehicle.max_altitude = vehicle.max_altitude.max(position.y);
+        vehicle.position = position.0;
+        vehicle.velocity = velocity.0;
+        // Every vehicle starts on the ground, so it can only land after the
+        // first step.
+        vehicle.landed |=
+            !first_step && position.y <= conditions.ground_altitude && velocity.y <= 0.0;
+        vehicle.burst |= failed;
+    }
+}
+
+/// End the run once any of its end conditions is met. Landing and burst are
+/// met once every watched vehicle has landed or burst, whichever of the two
+/// are asked for.
+fn check_end_conditions(
+    conditions: Res<EndConditions>,
+    clock: Res<SimulationClock>,
+    record: Res<FlightRecord>,
+    outputs: Option<Res<BatchOutputs>>,
+    telemetry: Option<ResMut<TelemetryRecorder>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let mut watched = record.watched(&conditions).peekable();
+    let finished = (conditions.landing || conditions.burst)
+        && watched.peek().is_some()
+        && watched.all(|vehicle| (conditions.landing && vehicle.landed) || (conditions.burst && vehicle.burst));
+    let outcome = if finished {
+        if conditions.burst && record.watched(&conditions).all(|vehicle| vehicle.burst) {
+            RunOutcome::Burst
+        } else {
+            RunOutcome::Landing
+        }
+    } else if conditions.duration.is_some_and(|duration| clock.elapsed_secs() >= duration) {
+        RunOutcome::Duration
+    } else if conditions.time_limit.is_some_and(|limit| clock.elapsed_secs() >= limit) {
+        warn!("No end condition was met within the time limit");
+        RunOutcome::TimeLimit
+    } else {
+        return;
+    };
+    info!("Run ended by {outcome:?} after {:.1} s", clock.elapsed_secs());
+    exit.write(finish(outcome, &clock, &record, outputs, telemetry));
+}
+
+/// End the run when the simulation faults.
+fn finish_faulted(
+    clock: Res<SimulationClock>,
+    record: Res<FlightRecord>,
+    outputs: Option<Res<BatchOutputs>>,
+    telemetry: Option<ResMut<TelemetryRecorder>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    error!("Run faulted after {:.1} s", clock.elapsed_secs());
+    exit.write(finish(RunOutcome::Faulted, &clock, &record, outputs, telemetry));
+}
+
+/// Close the telemetry and write the summary. Failing to do so turns the
+/// exit status into an I/O error.
+fn finish(
+    outcome: RunOutcome,
+    clock: &SimulationClock,
+    record: &FlightRecord,
+    outputs: Option<Res<BatchOutputs>>,
+    telemetry: Option<ResMut<TelemetryRecorder>>,
+) -> AppExit {
+    let summary = RunSummary {
+        outcome,
+        elapsed: clock.elapsed_secs(),
+        epoch: clock.now().to_string(),
+        steps: record.steps,
+        max_altitude: record.max_altitude(),
+        vehicles: record
+            .vehicles
+            .iter()
+            .map(|vehicle| VehicleSummary {
+                name: vehicle.name.clone(),
+                max_altitude: vehicle.max_altitude,
+                final_position: vehicle.position.to_array(),
+                final_velocity: vehicle.velocity.to_array(),
+                landed: vehicle.landed,
+                burst: vehicle.burst,
+            })
+            .collect(),
+    };
+    let summary_path = outputs.as_ref().and_then(|outputs| outputs.summary.as_deref());
+    match write_outputs(summary_path, telemetry, &summary) {
+        Ok(()) => outcome.exit(),
+        Err(error) => {
+            error!("Could not write run outputs: {error}");
+            AppExit::from_code(EXIT_IO_ERROR)
+        }
+    }
+}
+
+fn write_outputs(
+    summary_path: Option<&Path>,
+    telemetry: Option<ResMut<TelemetryRecorder>>,
+    summary: &RunSummary,
+) -> io::Result<()> {
+    if let Some(mut telemetry) = telemetry {
+        telemetry.finish()?;
+    }
+    if let Some(path) = summary_path {
+        let text = ron::ser::to_string_pretty(summary, ron::ser::PrettyConfig::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        std::fs::write(path, text + "\n")?;
+    }
+    Ok(())
+}
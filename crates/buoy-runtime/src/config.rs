@@ -0,0 +1,448 @@
+//! Scenario configuration.
+//!
+//! A scenario describes a whole flight: when and where it starts, the
+//! atmosphere it flies through, the vehicles that are launched and when the
+//! run ends. Scenarios are RON files, and quantities may be written with
+//! units (see [`buoy_physics::units`]), for example:
+//!
+//! ```ron
+//! (
+//!     name: "Sounding flight",
+//!     epoch: "2025-07-26T14:00:00Z",
+//!     launch_site: (latitude: 40.0, longitude: -105.0, altitude: "5250 ft"),
+//!     atmosphere: (source: Reference, turbulence: None),
+//!     vehicles: [
+//!         (
+//!             lift_gas_species: "helium",
+//!             lift_gas_mass: "1.2 kg",
+//!             balloon_mass: "1200 g",
+//!             payload_mass: "2.2 lb",
+//!             drag_coefficient: 0.47,
+//!             controllers: [Ballast(rate: "10 g/s", trigger: Descending("2 m/s"))],
+//!         ),
+//!     ],
+//!     end_conditions: (duration: Some("2 h"), burst: true),
+//!     telemetry: Some((path: "flight.csv", interval: "10 s", fields: [Position, Gas])),
+//! )
+//! ```
+
+use std::{fmt, io, path::{Path, PathBuf}};
+
+use avian3d::prelude::*;
+use bevy::{asset::Asset, prelude::*, reflect::TypePath};
+use bevy_common_assets::ron::RonAssetPlugin;
+use serde::Deserialize;
+use uom::si::{f32::ThermodynamicTemperature, thermodynamic_temperature::kelvin};
+
+use buoy_physics::{
+    atmosphere::{Atmosphere, AtmosphereModel, ProfilePoint},
+    climate::Climate,
+    clock::{Epoch, SimulationClock},
+    fluid_volume::{FluidGrid, FluidGridError},
+    humidity::Humidity,
+    inflation::LaunchConditions,
+    planet::PlanetaryBody,
+    turbulence::Turbulence,
+    units,
+    water::Water,
+};
+
+use crate::{
+    batch::EndConditions,
+    objects::balloon::{Balloon, BalloonConfig, BalloonConfigError},
+    telemetry::{TelemetryConfig, TelemetryRecorder},
+    validation::ValidationReport,
+    RuntimeState,
+};
+
+/// A simulation scenario: when and where it starts, the air it flies
+/// through, what is flown and when it ends.
+#[derive(Deserialize, Debug, Clone, Asset, TypePath)]
+pub struct ScenarioConfig {
+    #[serde(default)]
+    pub name: String,
+    /// UTC epoch at the start of the simulation, e.g. "2025-07-26T14:00:00Z".
+    pub epoch: Epoch,
+    /// The body the scenario flies on, which sets gravity and the reference
+    /// atmosphere.
+    #[serde(default)]
+    pub planet: Planet,
+    #[serde(default)]
+    pub launch_site: LaunchSite,
+    #[serde(default)]
+    pub atmosphere: AtmosphereConfig,
+    /// A body of water below the atmosphere, for floats and splashdowns.
+    /// Without one the atmosphere reaches all the way down.
+    #[serde(default)]
+    pub water: Option<WaterConfig>,
+    #[serde(default)]
+    pub vehicles: Vec<BalloonConfig>,
+    #[serde(default)]
+    pub end_conditions: EndConditions,
+    /// Where to record the flight, if anywhere.
+    #[serde(default)]
+    pub telemetry: Option<TelemetryConfig>,
+}
+
+/// Bodies a scenario can fly on.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Planet {
+    #[default]
+    Earth,
+    Mars,
+    Venus,
+    Titan,
+}
+
+impl Planet {
+    pub fn body(self) -> PlanetaryBody {
+        match self {
+            Planet::Earth => PlanetaryBody::earth(),
+            Planet::Mars => PlanetaryBody::mars(),
+            Planet::Venus => PlanetaryBody::venus(),
+            Planet::Titan => PlanetaryBody::titan(),
+        }
+    }
+}
+
+/// Where the vehicles are launched from.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(default)]
+pub struct LaunchSite {
+    /// Latitude (°), positive north.
+    pub latitude: f32,
+    /// Longitude (°), positive east.
+    pub longitude: f32,
+    /// Altitude (m) of the ground at the site.
+    #[serde(deserialize_with = "units::length")]
+    pub altitude: f32,
+}
+
+/// The air a scenario flies through.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct AtmosphereConfig {
+    pub source: AtmosphereSource,
+    pub humidity: Humidity,
+    /// Daily and yearly variation, sampled at the launch site.
+    pub climate: Option<Climate>,
+    /// Gusts on top of the mean wind.
+    pub turbulence: Option<Turbulence>,
+}
+
+/// Where temperature, pressure and wind come from. The atmosphere is made
+/// of the planet's atmospheric gas whatever the source.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+pub enum AtmosphereSource {
+    /// The planet's reference atmosphere, the US Standard Atmosphere, 1976 on
+    /// Earth. Still air.
+    #[default]
+    Reference,
+    /// A vertical profile of temperature and pressure. Still air.
+    Profile(Vec<ProfilePoint>),
+    /// A fluid grid file with wind, relative to the scenario file.
+    Grid(PathBuf),
+}
+
+/// A body of water whose free surface is a horizontal plane. Open-ocean
+/// seawater at sea level by default.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct WaterConfig {
+    /// Altitude (m) of the free surface.
+    #[serde(deserialize_with = "units::length")]
+    pub surface_height: f32,
+    /// Practical salinity (PSS-78), about 35 in the open ocean and 0 for
+    /// fresh water.
+    #[serde(deserialize_with = "units::dimensionless")]
+    pub salinity: f32,
+    /// Temperature (K) of the water, the same at every depth.
+    #[serde(deserialize_with = "units::temperature")]
+    pub temperature: f32,
+}
+
+impl Default for WaterConfig {
+    fn default() -> Self {
+        let seawater = Water::seawater();
+        WaterConfig {
+            surface_height: seawater.surface_height,
+            salinity: seawater.salinity,
+            temperature: seawater.temperature.get::<kelvin>(),
+        }
+    }
+}
+
+impl WaterConfig {
+    pub fn water(&self) -> Water {
+        Water {
+            surface_height: self.surface_height,
+            salinity: self.salinity,
+            temperature: ThermodynamicTemperature::new::<kelvin>(self.temperature),
+        }
+    }
+}
+
+impl ScenarioConfig {
+    /// Read and validate a scenario from a RON file. Relative grid paths are
+    /// resolved against the directory of the file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let mut scenario: ScenarioConfig = ron::from_str(&text)?;
+        if let Some(directory) = path.parent() {
+            scenario.resolve_paths(directory);
+        }
+        let report = scenario.validate();
+        if !report.is_empty() {
+            return Err(ScenarioError::Invalid(report.with_source(path, &text)));
+        }
+        Ok(scenario)
+    }
+
+    /// Make the files the scenario reads relative to the directory it was
+    /// read from.
+    pub fn resolve_paths(&mut self, directory: &Path) {
+        if let AtmosphereSource::Grid(grid) = &mut self.atmosphere.source {
+            *grid = directory.join(&*grid);
+        }
+    }
+
+    /// A simulation clock that starts at this scenario's epoch.
+    pub fn clock(&self) -> SimulationClock {
+        SimulationClock::new(self.epoch)
+    }
+
+    /// The atmosphere the scenario flies through, starting from the
+    /// planet's reference atmosphere.
+    pub fn atmosphere(&self) -> Result<Atmosphere, ScenarioError> {
+        let config = &self.atmosphere;
+        let reference = self.planet.body().atmosphere();
+        let model = match &config.source {
+            AtmosphereSource::Reference => reference.model,
+            AtmosphereSource::Profile(profile) => {
+                let mut profile = profile.clone();
+                profile.sort_by(|a, b| a.altitude.total_cmp(&b.altitude));
+                AtmosphereModel::Profile(profile)
+            }
+            AtmosphereSource::Grid(path) => AtmosphereModel::Grid(FluidGrid::load(path)?),
+        };
+        let climate = config.climate.clone().map(|climate| Climate {
+            latitude: self.launch_site.latitude,
+            longitude: self.launch_site.longitude,
+            ground_altitude: self.launch_site.altitude,
+            ..climate
+        });
+        Ok(Atmosphere {
+            model,
+            humidity: config.humidity.clone(),
+            climate,
+            ..reference
+        })
+    }
+
+    /// When the run ends, with landings measured against the launch site.
+    pub fn end_conditions(&self) -> EndConditions {
+        EndConditions {
+            ground_altitude: self.launch_site.altitude,
+            ..self.end_conditions.clone()
+        }
+    }
+
+    /// Start the clock, set up the atmosphere and spawn every vehicle the
+    /// scenario flies. Nothing is spawned unless the scenario is valid.
+    pub fn spawn(&self, commands: &mut Commands) -> Result<(), ScenarioError> {
+        let report = self.validate();
+        if !report.is_empty() {
+            return Err(ScenarioError::Invalid(report));
+        }
+        let atmosphere = self.atmosphere()?;
+        let planet = self.planet.body();
+        let site = Vec3::Y * self.launch_site.altitude;
+        let conditions = LaunchConditions::at(&atmosphere, &planet, site, self.epoch);
+        let vehicles = self
+            .vehicles
+            .iter()
+            .map(|vehicle| Balloon::new_from_config(vehicle, &conditions))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(telemetry) = &self.telemetry {
+            commands.insert_resource(TelemetryRecorder::create(telemetry).map_err(ScenarioError::Telemetry)?);
+        }
+        commands.insert_resource(self.clock());
+        commands.insert_resource(atmosphere);
+        commands.insert_resource(planet);
+        match &self.atmosphere.turbulence {
+            Some(turbulence) => commands.insert_resource(Turbulence {
+                ground_altitude: self.launch_site.altitude,
+                ..turbulence.clone()
+            }),
+            None => commands.remove_resource::<Turbulence>(),
+        }
+        match &self.water {
+            Some(water) => commands.insert_resource(water.water()),
+            None => commands.remove_resource::<Water>(),
+        }
+        for vehicle in vehicles {
+            commands.spawn((
+                vehicle.launched_from(site),
+                RigidBody::Dynamic,
+                ExternalForce::default().with_persistence(false),
+            ));
+        }
+        info!("Spawned scenario {:?} with {} vehicles", self.name, self.vehicles.len());
+        Ok(())
+    }
+}
+
+/// Loads a scenario from the assets folder and spawns it once it is ready.
+/// Scenario assets are files whose names end in `.scenario.ron`, leaving
+/// other RON files to their own loaders.
+pub struct ScenarioPlugin {
+    /// Path of the scenario within the assets folder.
+    pub path: String,
+    /// Folder on disk the asset server reads from, which files named in the
+    /// scenario are found relative to. Must match [`AssetPlugin::file_path`].
+    pub asset_folder: PathBuf,
+}
+
+impl Default for ScenarioPlugin {
+    fn default() -> Self {
+        ScenarioPlugin {
+            path: "configs/demo.scenario.ron".to_string(),
+            asset_folder: asset_base_path().join("assets"),
+        }
+    }
+}
+
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<ScenarioConfig>::new(&["scenario.ron"]));
+        app.insert_resource(ScenarioAssetFolder(self.asset_folder.clone()));
+        let path = self.path.clone();
+        app.add_systems(Startup, move |mut commands: Commands, server: Res<AssetServer>| {
+            commands.insert_resource(ScenarioHandle(server.load(path.clone())));
+        });
+        app.add_systems(Update, spawn_loaded_scenario);
+    }
+}
+
+/// The scenario being loaded or flown.
+#[derive(Resource, Debug, Clone)]
+pub struct ScenarioHandle(pub Handle<ScenarioConfig>);
+
+/// Directory the asset server finds the assets folder in by default.
+fn asset_base_path() -> PathBuf {
+    #[cfg(not(target_arch = "wasm32"))]
+    return bevy::asset::io::file::FileAssetReader::get_base_path();
+    #[cfg(target_arch = "wasm32")]
+    return PathBuf::new();
+}
+
+/// See [`ScenarioPlugin::asset_folder`].
+#[derive(Resource, Debug, Clone)]
+struct ScenarioAssetFolder(PathBuf);
+
+fn spawn_loaded_scenario(
+    mut commands: Commands,
+    handle: Option<Res<ScenarioHandle>>,
+    asset_folder: Res<ScenarioAssetFolder>,
+    server: Res<AssetServer>,
+    scenarios: Res<Assets<ScenarioConfig>>,
+    mut spawned: Local<bool>,
+    mut next_state: ResMut<NextState<RuntimeState>>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+    if *spawned {
+        return;
+    }
+    if let Some(scenario) = scenarios.get(&handle.0) {
+        *spawned = true;
+        let mut scenario = scenario.clone();
+        let file = handle.0.path().map(|path| asset_folder.0.join(path.path()));
+        if let Some(directory) = file.as_ref().and_then(|file| file.parent()) {
+            scenario.resolve_paths(directory);
+        }
+        match scenario.spawn(&mut commands) {
+            Ok(()) => {}
+            Err(ScenarioError::Invalid(report)) => {
+                // The asset only keeps what was parsed, so read the text
+                // again to point at the fields.
+                let report = match &file {
+                    Some(file) => {
+                        let text = std::fs::read_to_string(file).unwrap_or_default();
+                        report.with_source(file, &text)
+                    }
+                    None => report,
+                };
+                error!("{report}");
+                commands.insert_resource(report);
+                next_state.set(RuntimeState::Faulted);
+            }
+            Err(error) => {
+                error!("Could not spawn scenario: {error}");
+                next_state.set(RuntimeState::Faulted);
+            }
+        }
+    } else if let Some(bevy::asset::LoadState::Failed(error)) = server.get_load_state(&handle.0) {
+        *spawned = true;
+        error!("Could not load scenario: {error}");
+        next_state.set(RuntimeState::Faulted);
+    }
+}
+
+/// Errors reading or spawning a scenario.
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Grid(FluidGridError),
+    Vehicle(BalloonConfigError),
+    Telemetry(io::Error),
+    Invalid(ValidationReport),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Io(error) => write!(f, "could not read scenario: {error}"),
+            ScenarioError::Parse(error) => write!(f, "invalid scenario: {error}"),
+            ScenarioError::Grid(error) => write!(f, "invalid scenario atmosphere: {error}"),
+            ScenarioError::Vehicle(error) => write!(f, "invalid scenario vehicle: {error}"),
+            ScenarioError::Telemetry(error) => write!(f, "could not create telemetry: {error}"),
+            ScenarioError::Invalid(report) => write!(f, "{report}"),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<io::Error> for ScenarioError {
+    fn from(error: io::Error) -> Self {
+        ScenarioError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for ScenarioError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        ScenarioError::Parse(error)
+    }
+}
+
+impl From<FluidGridError> for ScenarioError {
+    fn from(error: FluidGridError) -> Self {
+        ScenarioError::Grid(error)
+    }
+}
+
+impl From<BalloonConfigError> for ScenarioError {
+    fn from(error: BalloonConfigError) -> Self {
+        ScenarioError::Vehicle(error)
+    }
+}
+
+impl From<ValidationReport> for ScenarioError {
+    fn from(report: ValidationReport) -> Self {
+        ScenarioError::Invalid(report)
+    }
+}
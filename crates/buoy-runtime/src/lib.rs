@@ -1,7 +1,10 @@
+pub mod batch;
 pub mod format;
 pub mod sequencing;
 pub mod objects;
 pub mod config;
+pub mod validation;
+pub mod telemetry;
 
 use bevy::{app::PluginGroupBuilder, prelude::*};
 
@@ -9,12 +12,24 @@ use bevy::{app::PluginGroupBuilder, prelude::*};
 pub struct BuoyDefaultPlugins;
 
 impl PluginGroup for BuoyDefaultPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add_group(BuoyHeadlessPlugins)
+            .add_group(bevy_repl::prelude::ReplPlugins)
+    }
+}
+
+/// The runtime without the interactive console, for batch runs.
+pub struct BuoyHeadlessPlugins;
+
+impl PluginGroup for BuoyHeadlessPlugins {
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
             .add(RuntimePlugin)
             .add(sequencing::plugin)
+            .add(objects::plugin)
+            .add(telemetry::plugin)
             .add(format::PrettyPrintPlugin)
-            .add_group(bevy_repl::prelude::ReplPlugins)
     }
 }
 
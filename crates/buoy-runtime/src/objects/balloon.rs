@@ -5,37 +5,210 @@ use bevy::reflect::TypePath;
 use serde::Deserialize;
 
 use buoy_physics::{
-    forces::DragCoefficient,
-    geometry::sphere_radius_from_volume,
-    ideal_gas::{GasSpecies, IdealGas},
+    atmosphere::Atmosphere,
+    clock::SimulationClock,
+    envelope::Envelope,
+    failure::{evaluate_failure, EnvelopeFailure, Failed, FailureKind},
+    forces::{DragCoefficient, RotationalDamping},
+    geometry::{collider_volume, sphere_radius_from_volume},
+    ideal_gas::{GasSpecies, IdealGas, UnknownGasSpecies},
+    inflation::{latex_burst_diameter, FlightTrain, InflationError, InflationTarget, LaunchConditions},
+    material_properties::Skin,
+    units,
 };
 use uom::si::{
     f32::{Mass, Pressure, ThermodynamicTemperature},
     thermodynamic_temperature::kelvin,
     pressure::pascal,
     mass::kilogram,
+    length::meter,
     mass_density::kilogram_per_cubic_meter,
     volume::cubic_meter,
 };
 
+use super::controllers::{ControllerConfig, Controllers};
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        (inflate_balloons, burst_balloons).chain().before(PhysicsStepSet::First),
+    );
+}
+
+/// Relative change in volume before a balloon's collider is rebuilt.
+const INFLATION_TOLERANCE: f32 = 0.01;
+
+/// Smallest volume (m³) of the collider left after a balloon bursts.
+const MIN_REMAINS_VOLUME: f32 = 1e-4;
+
 #[derive(Component, Default)]
 #[require(Transform)]
 pub struct Balloon;
 
-#[derive(Deserialize, Debug, Asset, TypePath)]
+#[derive(Deserialize, Debug, Clone, Asset, TypePath)]
 pub struct BalloonConfig {
+    #[serde(default = "default_name")]
+    pub name: String,
     pub lift_gas_species: String,
-    pub lift_gas_mass: f32, // kg
+    /// Mass (kg) of lift gas. Give either this or `free_lift`.
+    #[serde(default, deserialize_with = "some_mass")]
+    pub lift_gas_mass: Option<f32>,
+    /// Free lift (kg) the balloon is filled to at the launch site.
+    #[serde(default, deserialize_with = "some_mass")]
+    pub free_lift: Option<f32>,
+    #[serde(deserialize_with = "units::mass")]
     pub balloon_mass: f32,  // kg
+    #[serde(deserialize_with = "units::mass")]
     pub payload_mass: f32,  // kg
+    #[serde(deserialize_with = "units::dimensionless")]
     pub drag_coefficient: f32,
+    /// Rotational damping coefficient (N·m·s/rad) at the surface density of
+    /// the planet. Without it the balloon only feels the moment of drag.
+    #[serde(default)]
+    pub rotational_damping: Option<f32>,
+    #[serde(default)]
+    pub material: SkinMaterial,
+    /// Diameter (m) at which the envelope bursts. Latex envelopes default to
+    /// that of a sounding balloon of the same mass, while polyethylene ones
+    /// do not burst unless given one.
+    #[serde(default, deserialize_with = "some_length")]
+    pub burst_diameter: Option<f32>,
+    #[serde(default, deserialize_with = "units::mass")]
+    pub ballast_mass: f32, // kg
+    /// Launch position (m) relative to the launch site, east, up and south.
+    #[serde(default)]
+    pub offset: [f32; 3],
+    #[serde(default)]
+    pub controllers: Vec<ControllerConfig>,
+}
+
+fn default_name() -> String {
+    "Balloon".to_string()
+}
+
+fn some_mass<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<f32>, D::Error> {
+    units::mass(deserializer).map(Some)
+}
+
+fn some_length<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<f32>, D::Error> {
+    units::length(deserializer).map(Some)
+}
+
+impl BalloonConfig {
+    /// The balloon, its payload and its ballast, for inflation planning.
+    pub fn flight_train(&self) -> Result<FlightTrain, UnknownGasSpecies> {
+        Ok(FlightTrain {
+            species: GasSpecies::from_species_name(self.lift_gas_species.clone())?,
+            balloon_mass: Mass::new::<kilogram>(self.balloon_mass),
+            payload_mass: Mass::new::<kilogram>(self.payload_mass + self.ballast_mass),
+            drag_coefficient: self.drag_coefficient,
+        })
+    }
+
+    /// Diameter (m) at which the envelope bursts, if it does.
+    pub fn burst_diameter(&self) -> Option<f32> {
+        match (self.burst_diameter, self.material) {
+            (Some(diameter), _) => Some(diameter),
+            (None, SkinMaterial::Latex) => {
+                Some(latex_burst_diameter(Mass::new::<kilogram>(self.balloon_mass)).get::<meter>())
+            }
+            (None, SkinMaterial::Polyethylene) => None,
+        }
+    }
+
+    /// Mass (kg) of lift gas, worked out from the free lift if that is what
+    /// the config gives.
+    pub fn gas_mass(&self, conditions: &LaunchConditions) -> Result<f32, BalloonConfigError> {
+        match (self.lift_gas_mass, self.free_lift) {
+            (Some(mass), None) => Ok(mass),
+            (None, Some(free_lift)) => {
+                let target = InflationTarget::FreeLift(Mass::new::<kilogram>(free_lift));
+                let inflation = self.flight_train()?.inflate(target, conditions)?;
+                Ok(inflation.gas_mass.get::<kilogram>())
+            }
+            _ => Err(BalloonConfigError::Lift),
+        }
+    }
+}
+
+/// Why a balloon cannot be built from its config.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BalloonConfigError {
+    Gas(UnknownGasSpecies),
+    Inflation(InflationError),
+    /// Neither or both of the lift gas mass and the free lift were given.
+    Lift,
+}
+
+impl std::fmt::Display for BalloonConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BalloonConfigError::Gas(error) => write!(f, "{error}"),
+            BalloonConfigError::Inflation(error) => write!(f, "cannot inflate balloon: {error}"),
+            BalloonConfigError::Lift => write!(f, "give either lift_gas_mass or free_lift"),
+        }
+    }
 }
 
+impl std::error::Error for BalloonConfigError {}
+
+impl From<UnknownGasSpecies> for BalloonConfigError {
+    fn from(error: UnknownGasSpecies) -> Self {
+        BalloonConfigError::Gas(error)
+    }
+}
+
+impl From<InflationError> for BalloonConfigError {
+    fn from(error: InflationError) -> Self {
+        BalloonConfigError::Inflation(error)
+    }
+}
+
+/// Envelope materials a scenario can pick from.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SkinMaterial {
+    #[default]
+    Latex,
+    Polyethylene,
+}
+
+impl SkinMaterial {
+    pub fn skin(self) -> Skin {
+        match self {
+            SkinMaterial::Latex => Skin::latex(),
+            SkinMaterial::Polyethylene => Skin::polyethylene(),
+        }
+    }
+}
+
+/// Mass (kg) a balloon carries besides its lift gas.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Reflect)]
+pub struct VehicleMass {
+    pub envelope: f32,
+    pub payload: f32,
+    pub ballast: f32,
+}
+
+impl VehicleMass {
+    pub fn total(&self) -> f32 {
+        self.envelope + self.payload + self.ballast
+    }
+}
+
+/// What a balloon's envelope is made of.
+#[derive(Component, Debug, Clone)]
+pub struct EnvelopeMaterial(pub Skin);
+
+/// Diameter (m) at which the envelope of a rigid balloon bursts. Infinite
+/// for envelopes that do not stretch until they burst.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct BurstDiameter(pub f32);
+
 impl Balloon {
     pub fn new() -> BalloonBundle {
         let balloon = Balloon;
         let lift_gas = IdealGas::new(
-            GasSpecies::from_species_name("helium".to_string()),
+            GasSpecies::helium(),
             ThermodynamicTemperature::new::<kelvin>(293.0),
             Pressure::new::<pascal>(101325.0),
             Mass::new::<kilogram>(1.0),
@@ -50,27 +223,48 @@ impl Balloon {
             collider: Collider::sphere(radius),
             collider_density: ColliderDensity(density),
             drag_coefficient: DragCoefficient(0.47),
+            rotational_damping: RotationalDamping::default(),
+            mass: VehicleMass::default(),
+            material: EnvelopeMaterial(Skin::latex()),
+            burst_diameter: BurstDiameter(f32::INFINITY),
+            controllers: Controllers::default(),
         }
     }
-    pub fn new_from_config(config: &BalloonConfig) -> BalloonBundle {
+    /// A balloon filled with gas at the conditions of its launch site.
+    pub fn new_from_config(
+        config: &BalloonConfig,
+        conditions: &LaunchConditions,
+    ) -> Result<BalloonBundle, BalloonConfigError> {
         let balloon = Balloon;
+        let gas_mass = config.gas_mass(conditions)?;
         let lift_gas = IdealGas::new(
-            GasSpecies::from_species_name(config.lift_gas_species.clone()),
-            ThermodynamicTemperature::new::<kelvin>(293.0),
-            Pressure::new::<pascal>(101325.0),
-            Mass::new::<kilogram>(config.lift_gas_mass),
+            GasSpecies::from_species_name(config.lift_gas_species.clone())?,
+            conditions.temperature,
+            conditions.pressure,
+            Mass::new::<kilogram>(gas_mass),
         );
-        let radius = sphere_radius_from_volume(lift_gas.volume().get::<cubic_meter>());
-        let density = lift_gas.density().get::<kilogram_per_cubic_meter>();
-        BalloonBundle {
-            name: Name::new("Balloon"),
+        let mass = VehicleMass {
+            envelope: config.balloon_mass,
+            payload: config.payload_mass,
+            ballast: config.ballast_mass,
+        };
+        let volume = lift_gas.volume().get::<cubic_meter>();
+        let radius = sphere_radius_from_volume(volume);
+        let density = (gas_mass + mass.total()) / volume;
+        Ok(BalloonBundle {
+            name: Name::new(config.name.clone()),
             balloon,
             lift_gas,
-            transform: Transform::from_xyz(0.0, radius * 2.0, 0.0),
+            transform: Transform::from_translation(Vec3::from(config.offset) + Vec3::Y * radius * 2.0),
             collider: Collider::sphere(radius),
             collider_density: ColliderDensity(density),
             drag_coefficient: DragCoefficient(config.drag_coefficient),
-        }
+            rotational_damping: RotationalDamping(config.rotational_damping.unwrap_or_default()),
+            mass,
+            material: EnvelopeMaterial(config.material.skin()),
+            burst_diameter: BurstDiameter(config.burst_diameter().unwrap_or(f32::INFINITY)),
+            controllers: Controllers(config.controllers.clone()),
+        })
     }
 }
 
@@ -83,4 +277,97 @@ pub struct BalloonBundle {
     collider: Collider,
     collider_density: ColliderDensity,
     drag_coefficient: DragCoefficient,
+    rotational_damping: RotationalDamping,
+    mass: VehicleMass,
+    material: EnvelopeMaterial,
+    burst_diameter: BurstDiameter,
+    controllers: Controllers,
+}
+
+impl BalloonBundle {
+    /// Move the balloon by the position (m) of the site it launches from.
+    pub fn launched_from(mut self, site: Vec3) -> Self {
+        self.transform.translation += site;
+        self
+    }
+}
+
+/// Let the lift gas of rigid balloons settle to the ambient pressure and
+/// temperature, resizing the collider with it. The density is kept such that
+/// the body weighs as much as its gas and everything it carries.
+fn inflate_balloons(
+    mut balloons: Query<
+        (&mut IdealGas, &mut Collider, &mut ColliderDensity, &Position, Option<&VehicleMass>),
+        (With<Balloon>, Without<Envelope>),
+    >,
+    atmosphere: Res<Atmosphere>,
+    clock: Res<SimulationClock>,
+) {
+    let epoch = clock.now();
+    for (mut gas, mut collider, mut density, position, mass) in balloons.iter_mut() {
+        gas.pressure = atmosphere.pressure(position.0, epoch);
+        gas.temperature = atmosphere.temperature(position.0, epoch);
+        let volume = gas.volume().get::<cubic_meter>();
+        if !volume.is_finite() || volume <= f32::EPSILON {
+            continue;
+        }
+        let current = collider_volume(&collider);
+        if (volume - current).abs() > INFLATION_TOLERANCE * current {
+            *collider = Collider::sphere(sphere_radius_from_volume(volume));
+        }
+        let total = gas.mass.get::<kilogram>() + mass.map_or(0.0, VehicleMass::total);
+        let target = total / collider_volume(&collider);
+        if (target - density.0).abs() > f32::EPSILON * density.0.abs().max(1.0) {
+            density.0 = target;
+        }
+    }
+}
+
+/// Burst rigid balloons that have grown to their burst diameter, or whose
+/// skin has gone past its temperature limits. The skin is taken to be at the
+/// temperature of the gas, and its strain is estimated from the diameter by
+/// taking the burst diameter to be where it reaches its room temperature
+/// strain at failure. A burst balloon loses its lift gas and falls with the
+/// remains of its envelope.
+fn burst_balloons(
+    mut commands: Commands,
+    mut balloons: Query<
+        (
+            Entity,
+            &mut IdealGas,
+            &mut Collider,
+            &mut ColliderDensity,
+            &EnvelopeMaterial,
+            &BurstDiameter,
+            Option<&VehicleMass>,
+        ),
+        (With<Balloon>, Without<Envelope>, Without<Failed>),
+    >,
+    mut failures: EventWriter<EnvelopeFailure>,
+) {
+    for (entity, mut gas, mut collider, mut density, material, burst_diameter, mass) in balloons.iter_mut() {
+        let temperature = gas.temperature.get::<kelvin>();
+        let diameter = 2.0 * sphere_radius_from_volume(gas.volume().get::<cubic_meter>());
+        let strain = (1.0 + material.0.max_strain) * diameter / burst_diameter.0 - 1.0;
+        let kind = match evaluate_failure(&material.0, temperature, 0.0) {
+            Some(kind) => kind,
+            None if diameter >= burst_diameter.0 => FailureKind::Overstrain,
+            None => continue,
+        };
+        warn!("Balloon burst by {kind} at {diameter:.2} m across and {temperature:.1} K");
+        commands.entity(entity).insert(Failed(kind));
+        failures.write(EnvelopeFailure {
+            entity,
+            kind,
+            temperature,
+            strain,
+            stress: 0.0,
+        });
+
+        gas.mass = Mass::new::<kilogram>(0.0);
+        let mass = mass.copied().unwrap_or_default();
+        let remains = (mass.envelope / material.0.density).max(MIN_REMAINS_VOLUME);
+        *collider = Collider::sphere(sphere_radius_from_volume(remains));
+        density.0 = mass.total() / remains;
+    }
 }
@@ -0,0 +1,88 @@
+//! Flight controllers that drop ballast or vent lift gas when a trigger
+//! condition holds.
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+use uom::si::{f32::Mass, mass::kilogram};
+
+use buoy_physics::{clock::SimulationClock, ideal_gas::IdealGas, units};
+
+use super::balloon::VehicleMass;
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_systems(FixedUpdate, run_controllers.before(PhysicsStepSet::First));
+}
+
+/// An action a vehicle takes on its own while its trigger holds.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ControllerConfig {
+    /// Drop ballast at a rate (kg/s) until none is left.
+    Ballast {
+        #[serde(deserialize_with = "units::mass_rate")]
+        rate: f32,
+        trigger: Trigger,
+    },
+    /// Vent lift gas at a rate (kg/s) until none is left.
+    Valve {
+        #[serde(deserialize_with = "units::mass_rate")]
+        rate: f32,
+        trigger: Trigger,
+    },
+}
+
+/// When a controller acts.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Trigger {
+    /// At or above an altitude (m).
+    Above(#[serde(deserialize_with = "units::length")] f32),
+    /// At or below an altitude (m).
+    Below(#[serde(deserialize_with = "units::length")] f32),
+    /// Rising faster than a speed (m/s).
+    Ascending(#[serde(deserialize_with = "units::velocity")] f32),
+    /// Sinking faster than a speed (m/s).
+    Descending(#[serde(deserialize_with = "units::velocity")] f32),
+    /// Once a number of simulated seconds have passed.
+    After(#[serde(deserialize_with = "units::time")] f64),
+}
+
+impl Trigger {
+    pub fn holds(&self, altitude: f32, vertical_speed: f32, elapsed: f64) -> bool {
+        match *self {
+            Trigger::Above(limit) => altitude >= limit,
+            Trigger::Below(limit) => altitude <= limit,
+            Trigger::Ascending(speed) => vertical_speed > speed,
+            Trigger::Descending(speed) => -vertical_speed > speed,
+            Trigger::After(seconds) => elapsed >= seconds,
+        }
+    }
+}
+
+/// Controllers flown by a vehicle.
+#[derive(Component, Debug, Clone, Default)]
+pub struct Controllers(pub Vec<ControllerConfig>);
+
+fn run_controllers(
+    time: Res<Time>,
+    clock: Res<SimulationClock>,
+    mut vehicles: Query<(&Controllers, &Position, &LinearVelocity, &mut IdealGas, &mut VehicleMass)>,
+) {
+    let dt = time.delta_secs();
+    for (controllers, position, velocity, mut gas, mut mass) in vehicles.iter_mut() {
+        for controller in &controllers.0 {
+            match controller {
+                ControllerConfig::Ballast { rate, trigger } => {
+                    if mass.ballast > 0.0 && trigger.holds(position.y, velocity.y, clock.elapsed_secs()) {
+                        mass.ballast = (mass.ballast - rate * dt).max(0.0);
+                    }
+                }
+                ControllerConfig::Valve { rate, trigger } => {
+                    let remaining = gas.mass.get::<kilogram>();
+                    if remaining > 0.0 && trigger.holds(position.y, velocity.y, clock.elapsed_secs()) {
+                        gas.mass = Mass::new::<kilogram>((remaining - rate * dt).max(0.0));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,13 @@
 pub mod balloon;
+pub mod controllers;
 pub mod environment;
 
+use bevy::prelude::*;
+
 pub use balloon::Balloon;
+pub use controllers::Controllers;
 pub use environment::GroundPlane;
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_plugins((balloon::plugin, controllers::plugin));
+}
@@ -32,6 +32,7 @@ use crate::RuntimeState;
 pub(crate) fn plugin(app: &mut App) {
     app.add_systems(OnEnter(RuntimeState::Stopped), pause);
     app.add_systems(OnExit(RuntimeState::Stopped), unpause);
+    app.add_systems(OnEnter(RuntimeState::Faulted), halt);
 
     // Configure system sets for different runtime states
     app.configure_sets(Update, (
@@ -54,6 +55,12 @@ pub fn pause(mut physics_time: ResMut<Time<Physics>>, mut next_state: ResMut<Nex
     next_state.set(RuntimeState::Stopped);
 }
 
+/// Stop physics for good once the simulation has faulted.
+pub fn halt(mut physics_time: ResMut<Time<Physics>>) {
+    physics_time.as_mut().pause();
+    warn!("simulation faulted, physics halted");
+}
+
 pub fn unpause(
     mut physics_time: ResMut<Time<Physics>>,
     mut next_state: ResMut<NextState<RuntimeState>>,
@@ -0,0 +1,455 @@
+//! Telemetry recording.
+//!
+//! The recorder samples the state of every balloon at a fixed cadence of
+//! simulated time and writes one row per balloon and sample. Every row is
+//! stamped with the simulated time and the UTC epoch. Column names end in
+//! their unit (`x_m`, `gas_pressure_pa`). CSV files open with a `# name: unit`
+//! comment line for every column that has a unit, and Parquet files carry
+//! the unit in each field's metadata. Values a balloon does not have, such as
+//! the strain of a rigid balloon, are written as NaN.
+//!
+//! Files ending in `.parquet` are written as Parquet when the `parquet`
+//! feature is enabled, anything else as CSV.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+use uom::si::{
+    mass::kilogram, pressure::pascal, thermodynamic_temperature::kelvin, volume::cubic_meter,
+};
+
+use buoy_physics::{
+    atmosphere::Atmosphere, clock::SimulationClock, envelope::Envelope, forces::ForceBreakdown,
+    ideal_gas::IdealGas, membrane::MembraneStress, units,
+};
+
+use crate::{objects::Balloon, RuntimeState};
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_systems(
+        FixedUpdate,
+        record_telemetry
+            .after(PhysicsStepSet::First)
+            .run_if(resource_exists::<TelemetryRecorder>)
+            .run_if(in_state(RuntimeState::Running)),
+    );
+    app.add_systems(Last, finish_telemetry.run_if(on_event::<AppExit>));
+}
+
+/// What to record, how often and where.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct TelemetryConfig {
+    pub path: PathBuf,
+    /// Simulated seconds between samples.
+    #[serde(default = "default_interval", deserialize_with = "units::time")]
+    pub interval: f64,
+    #[serde(default = "TelemetryField::all")]
+    pub fields: Vec<TelemetryField>,
+}
+
+impl TelemetryConfig {
+    /// Position and velocity of every balloon at every step.
+    pub fn trajectory(path: PathBuf) -> Self {
+        TelemetryConfig {
+            path,
+            interval: 0.0,
+            fields: vec![TelemetryField::Position, TelemetryField::Velocity],
+        }
+    }
+}
+
+fn default_interval() -> f64 {
+    1.0
+}
+
+/// Groups of columns a recording can hold.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelemetryField {
+    /// Position (m).
+    Position,
+    /// Linear velocity (m/s).
+    Velocity,
+    /// Net force (N) applied over the step, and the weight, buoyancy and
+    /// drag it is made of.
+    Forces,
+    /// Mass (kg), pressure (Pa), temperature (K) and volume (m³) of the
+    /// lift gas.
+    Gas,
+    /// Temperature (K) of the ambient air and of the envelope film.
+    Temperatures,
+    /// Highest strain and von Mises stress (Pa) in the envelope.
+    Strain,
+}
+
+impl TelemetryField {
+    pub fn all() -> Vec<TelemetryField> {
+        vec![
+            TelemetryField::Position,
+            TelemetryField::Velocity,
+            TelemetryField::Forces,
+            TelemetryField::Gas,
+            TelemetryField::Temperatures,
+            TelemetryField::Strain,
+        ]
+    }
+
+    /// Names and units of the columns this field adds.
+    fn columns(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            TelemetryField::Position => &[("x_m", "m"), ("y_m", "m"), ("z_m", "m")],
+            TelemetryField::Velocity => &[
+                ("vx_m_per_s", "m/s"),
+                ("vy_m_per_s", "m/s"),
+                ("vz_m_per_s", "m/s"),
+            ],
+            TelemetryField::Forces => &[
+                ("fx_n", "N"),
+                ("fy_n", "N"),
+                ("fz_n", "N"),
+                ("weight_x_n", "N"),
+                ("weight_y_n", "N"),
+                ("weight_z_n", "N"),
+                ("buoyancy_x_n", "N"),
+                ("buoyancy_y_n", "N"),
+                ("buoyancy_z_n", "N"),
+                ("drag_x_n", "N"),
+                ("drag_y_n", "N"),
+                ("drag_z_n", "N"),
+            ],
+            TelemetryField::Gas => &[
+                ("gas_mass_kg", "kg"),
+                ("gas_pressure_pa", "Pa"),
+                ("gas_temperature_k", "K"),
+                ("gas_volume_m3", "m³"),
+            ],
+            TelemetryField::Temperatures => &[
+                ("ambient_temperature_k", "K"),
+                ("film_temperature_k", "K"),
+            ],
+            TelemetryField::Strain => &[("max_strain", "1"), ("max_stress_pa", "Pa")],
+        }
+    }
+}
+
+/// A column of the recording.
+#[derive(Debug, Clone, PartialEq)]
+struct Column {
+    name: &'static str,
+    unit: &'static str,
+    text: bool,
+}
+
+/// Columns every recording starts with.
+const STAMP_COLUMNS: [Column; 3] = [
+    Column { name: "time_s", unit: "s", text: false },
+    Column { name: "epoch_utc", unit: "", text: true },
+    Column { name: "vehicle", unit: "", text: true },
+];
+
+/// Something a recording can be written to.
+trait TelemetrySink: Send + Sync {
+    fn write(&mut self, text: &[String], values: &[f64]) -> io::Result<()>;
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// Writes telemetry to a file at a fixed cadence.
+#[derive(Resource)]
+pub struct TelemetryRecorder {
+    fields: Vec<TelemetryField>,
+    interval: f64,
+    next_sample: f64,
+    sink: Box<dyn TelemetrySink>,
+    path: PathBuf,
+}
+
+impl TelemetryRecorder {
+    /// Create the file and write its header.
+    pub fn create(config: &TelemetryConfig) -> io::Result<Self> {
+        if !(config.interval.is_finite() && config.interval >= 0.0) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("telemetry interval must not be negative, got {}", config.interval),
+            ));
+        }
+        let columns: Vec<Column> = STAMP_COLUMNS
+            .into_iter()
+            .chain(config.fields.iter().flat_map(|field| {
+                field.columns().iter().map(|&(name, unit)| Column { name, unit, text: false })
+            }))
+            .collect();
+        let sink: Box<dyn TelemetrySink> = if is_parquet(&config.path) {
+            parquet_sink(&config.path, &columns)?
+        } else {
+            Box::new(CsvSink::create(&config.path, &columns)?)
+        };
+        Ok(TelemetryRecorder {
+            fields: config.fields.clone(),
+            interval: config.interval,
+            next_sample: 0.0,
+            sink,
+            path: config.path.clone(),
+        })
+    }
+
+    /// Flush everything written so far and close the file.
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.sink.finish()
+    }
+}
+
+impl Drop for TelemetryRecorder {
+    fn drop(&mut self) {
+        if let Err(error) = self.sink.finish() {
+            error!("Could not finish telemetry {}: {error}", self.path.display());
+        }
+    }
+}
+
+fn is_parquet(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == "parquet")
+}
+
+struct CsvSink {
+    writer: Option<BufWriter<File>>,
+}
+
+impl CsvSink {
+    fn create(path: &Path, columns: &[Column]) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        for column in columns.iter().filter(|column| !column.unit.is_empty()) {
+            writeln!(writer, "# {}: {}", column.name, column.unit)?;
+        }
+        let names: Vec<&str> = columns.iter().map(|column| column.name).collect();
+        writeln!(writer, "{}", names.join(","))?;
+        Ok(CsvSink { writer: Some(writer) })
+    }
+}
+
+impl TelemetrySink for CsvSink {
+    fn write(&mut self, text: &[String], values: &[f64]) -> io::Result<()> {
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(());
+        };
+        // The stamp leads the row: time, then the text columns.
+        let mut row = vec![values[0].to_string()];
+        row.extend(text.iter().map(|text| text.replace(',', ";")));
+        row.extend(values[1..].iter().map(f64::to_string));
+        writeln!(writer, "{}", row.join(","))
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.writer.take() {
+            Some(mut writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(feature = "parquet"))]
+fn parquet_sink(_path: &Path, _columns: &[Column]) -> io::Result<Box<dyn TelemetrySink>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Parquet telemetry needs the `parquet` feature",
+    ))
+}
+
+#[cfg(feature = "parquet")]
+fn parquet_sink(path: &Path, columns: &[Column]) -> io::Result<Box<dyn TelemetrySink>> {
+    Ok(Box::new(parquet_file::ParquetSink::create(path, columns)?))
+}
+
+#[cfg(feature = "parquet")]
+mod parquet_file {
+    use std::{
+        collections::HashMap,
+        fs::File,
+        io,
+        path::Path,
+        sync::{Arc, Mutex, PoisonError},
+    };
+
+    use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema, SchemaRef};
+    use parquet::arrow::ArrowWriter;
+
+    use super::{Column, TelemetrySink};
+
+    /// Rows buffered before they are written as a row group.
+    const BATCH_ROWS: usize = 4096;
+
+    pub(super) struct ParquetSink {
+        schema: SchemaRef,
+        // The writer is not `Sync`, which resources must be.
+        writer: Mutex<Option<ArrowWriter<File>>>,
+        text: Vec<Vec<String>>,
+        values: Vec<Vec<f64>>,
+    }
+
+    impl ParquetSink {
+        pub(super) fn create(path: &Path, columns: &[Column]) -> io::Result<Self> {
+            let fields: Vec<Field> = columns
+                .iter()
+                .map(|column| {
+                    let data_type = if column.text { DataType::Utf8 } else { DataType::Float64 };
+                    let metadata = HashMap::from([("unit".to_string(), column.unit.to_string())]);
+                    Field::new(column.name, data_type, false).with_metadata(metadata)
+                })
+                .collect();
+            let schema = Arc::new(Schema::new(fields));
+            let writer = ArrowWriter::try_new(File::create(path)?, schema.clone(), None)
+                .map_err(io::Error::other)?;
+            let text_columns = columns.iter().filter(|column| column.text).count();
+            Ok(ParquetSink {
+                schema,
+                writer: Mutex::new(Some(writer)),
+                text: vec![Vec::new(); text_columns],
+                values: vec![Vec::new(); columns.len() - text_columns],
+            })
+        }
+
+        fn flush_rows(&mut self) -> io::Result<()> {
+            let Some(writer) = self.writer.get_mut().unwrap_or_else(PoisonError::into_inner) else {
+                return Ok(());
+            };
+            if self.values.first().is_none_or(Vec::is_empty) {
+                return Ok(());
+            }
+            let mut text = self.text.iter_mut();
+            let mut values = self.values.iter_mut();
+            let arrays: Vec<ArrayRef> = self
+                .schema
+                .fields()
+                .iter()
+                .map(|field| -> ArrayRef {
+                    if field.data_type() == &DataType::Utf8 {
+                        Arc::new(StringArray::from(std::mem::take(text.next().unwrap())))
+                    } else {
+                        Arc::new(Float64Array::from(std::mem::take(values.next().unwrap())))
+                    }
+                })
+                .collect();
+            let batch = RecordBatch::try_new(self.schema.clone(), arrays).map_err(io::Error::other)?;
+            writer.write(&batch).map_err(io::Error::other)
+        }
+    }
+
+    impl TelemetrySink for ParquetSink {
+        fn write(&mut self, text: &[String], values: &[f64]) -> io::Result<()> {
+            for (column, value) in self.text.iter_mut().zip(text) {
+                column.push(value.clone());
+            }
+            for (column, value) in self.values.iter_mut().zip(values) {
+                column.push(*value);
+            }
+            if self.values[0].len() >= BATCH_ROWS {
+                self.flush_rows()?;
+            }
+            Ok(())
+        }
+
+        fn finish(&mut self) -> io::Result<()> {
+            self.flush_rows()?;
+            match self.writer.get_mut().unwrap_or_else(PoisonError::into_inner).take() {
+                Some(writer) => writer.close().map(drop).map_err(io::Error::other),
+                None => Ok(()),
+            }
+        }
+    }
+}
+
+type TelemetryQuery<'a> = (
+    Option<&'a Name>,
+    &'a Position,
+    &'a LinearVelocity,
+    Option<&'a ExternalForce>,
+    Option<&'a ForceBreakdown>,
+    Option<&'a IdealGas>,
+    Option<&'a Envelope>,
+    Option<&'a MembraneStress>,
+);
+
+/// Sample every balloon once the forces of the step are known.
+fn record_telemetry(
+    mut recorder: ResMut<TelemetryRecorder>,
+    balloons: Query<TelemetryQuery, With<Balloon>>,
+    clock: Res<SimulationClock>,
+    atmosphere: Res<Atmosphere>,
+) {
+    let elapsed = clock.elapsed_secs();
+    // Balloons only get a position once physics has prepared them.
+    if elapsed < recorder.next_sample || balloons.is_empty() {
+        return;
+    }
+    recorder.next_sample = if recorder.interval > 0.0 {
+        // Stay on the cadence even when a step overshoots it.
+        (elapsed / recorder.interval).floor() * recorder.interval + recorder.interval
+    } else {
+        elapsed
+    };
+    let epoch = clock.now();
+    let mut failed = None;
+    for (name, position, velocity, force, breakdown, gas, envelope, stress) in balloons.iter() {
+        let vehicle = name.map_or_else(|| "Balloon".to_string(), |name| name.to_string());
+        let mut values = vec![elapsed];
+        for field in &recorder.fields {
+            match field {
+                TelemetryField::Position => values.extend(position.0.to_array().map(f64::from)),
+                TelemetryField::Velocity => values.extend(velocity.0.to_array().map(f64::from)),
+                TelemetryField::Forces => {
+                    let net = force.map_or(Vec3::NAN, |force| force.force());
+                    let terms = breakdown.map_or([Vec3::NAN; 3], |breakdown| {
+                        [breakdown.weight, breakdown.buoyancy, breakdown.drag]
+                    });
+                    for force in [net].iter().chain(&terms) {
+                        values.extend(force.to_array().map(f64::from));
+                    }
+                }
+                TelemetryField::Gas => match gas {
+                    Some(gas) => values.extend([
+                        gas.mass.get::<kilogram>(),
+                        gas.pressure.get::<pascal>(),
+                        gas.temperature.get::<kelvin>(),
+                        gas.volume().get::<cubic_meter>(),
+                    ].map(f64::from)),
+                    None => values.extend([f64::NAN; 4]),
+                },
+                TelemetryField::Temperatures => values.extend([
+                    f64::from(atmosphere.temperature(position.0, epoch).get::<kelvin>()),
+                    envelope.map_or(f64::NAN, |envelope| f64::from(envelope.film_temperature)),
+                ]),
+                TelemetryField::Strain => values.extend([
+                    envelope.map_or(f64::NAN, |envelope| f64::from(envelope.max_strain())),
+                    stress.map_or(f64::NAN, |stress| f64::from(stress.max_von_mises)),
+                ]),
+            }
+        }
+        if let Err(error) = recorder.sink.write(&[epoch.to_string(), vehicle], &values) {
+            failed = Some(error);
+            break;
+        }
+    }
+    if let Some(error) = failed {
+        error!("Could not write telemetry {}: {error}", recorder.path.display());
+        // Stop recording rather than report the same failure every step.
+        if let Err(error) = recorder.finish() {
+            error!("Could not finish telemetry {}: {error}", recorder.path.display());
+        }
+        recorder.interval = f64::INFINITY;
+        recorder.next_sample = f64::INFINITY;
+    }
+}
+
+/// Close the recording when the app exits.
+fn finish_telemetry(recorder: Option<ResMut<TelemetryRecorder>>) {
+    if let Some(mut recorder) = recorder
+        && let Err(error) = recorder.finish()
+    {
+        error!("Could not finish telemetry {}: {error}", recorder.path.display());
+    }
+}
@@ -0,0 +1,627 @@
+//! Checks that a scenario describes something that can be flown.
+//!
+//! Validation collects every problem in a scenario rather than stopping at
+//! the first, so one run of the simulator reports everything that needs to be
+//! fixed. Each [`Issue`] names the field it is about with a path such as
+//! `vehicles[0].payload_mass`. Given the text of the file, the report also
+//! points at the line and column the field is written on, or the nearest
+//! enclosing field when the offending one was left out.
+
+use std::{collections::HashMap, fmt, ops::RangeInclusive, path::PathBuf};
+
+use bevy::prelude::*;
+use uom::si::{
+    f32::{Length, Mass},
+    length::meter,
+    mass::kilogram,
+};
+
+use buoy_physics::{
+    atmosphere::Atmosphere,
+    humidity::Humidity,
+    ideal_gas::{GasSpecies, IdealGas},
+    inflation::{burst_altitude, LaunchConditions},
+};
+
+use crate::{
+    config::{AtmosphereSource, ScenarioConfig},
+    objects::{
+        balloon::BalloonConfig,
+        controllers::{ControllerConfig, Trigger},
+    },
+};
+
+/// Highest practical salinity the seawater equation of state holds for.
+const MAX_SALINITY: f32 = 42.0;
+
+/// Water temperatures (K) the seawater equation of state holds for, 0–40 °C.
+const WATER_TEMPERATURES: RangeInclusive<f32> = 273.15..=313.15;
+
+/// One problem with a scenario.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// Path of the offending field, e.g. `vehicles[0].lift_gas_species`.
+    pub path: String,
+    pub message: String,
+    /// Where the field is written in the scenario file, if known.
+    pub location: Option<Location>,
+}
+
+/// A line and column in a file, both counted from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Every problem found in a scenario.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// File the scenario was read from, if any.
+    pub file: Option<PathBuf>,
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    fn push(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.issues.push(Issue {
+            path: path.into(),
+            message: message.into(),
+            location: None,
+        });
+    }
+
+    /// Note the file the scenario was read from and where in its text each
+    /// issue's field is written.
+    pub fn with_source(mut self, file: impl Into<PathBuf>, text: &str) -> Self {
+        self.file = Some(file.into());
+        let fields = field_offsets(text);
+        for issue in &mut self.issues {
+            issue.location = ancestors(&issue.path)
+                .find_map(|path| fields.get(path))
+                .map(|&offset| location(text, offset));
+        }
+        self
+    }
+
+    /// Record a problem unless a value is finite and positive.
+    fn positive(&mut self, path: impl Into<String>, value: f32) {
+        if !(value.is_finite() && value > 0.0) {
+            self.push(path, format!("must be positive, got {value}"));
+        }
+    }
+
+    /// Record a problem unless a value is finite and not negative.
+    fn non_negative(&mut self, path: impl Into<String>, value: f32) {
+        if !(value.is_finite() && value >= 0.0) {
+            self.push(path, format!("must not be negative, got {value}"));
+        }
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file = self
+            .file
+            .as_ref()
+            .map_or_else(|| "scenario".to_string(), |file| file.display().to_string());
+        write!(f, "{file} has {} problem(s)", self.issues.len())?;
+        for issue in &self.issues {
+            match issue.location {
+                Some(location) => write!(f, "\n  {file}:{location}: {issue}")?,
+                None => write!(f, "\n  {file}: {issue}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationReport {}
+
+impl ScenarioConfig {
+    /// Check the whole scenario, reporting every problem found.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+
+        let site = &self.launch_site;
+        if !(-90.0..=90.0).contains(&site.latitude) {
+            report.push("launch_site.latitude", format!("must be between -90 and 90°, got {}", site.latitude));
+        }
+        if !(-180.0..=180.0).contains(&site.longitude) {
+            report.push(
+                "launch_site.longitude",
+                format!("must be between -180 and 180°, got {}", site.longitude),
+            );
+        }
+        if !(Atmosphere::MIN_ALTITUDE..=Atmosphere::MAX_ALTITUDE).contains(&site.altitude) {
+            report.push(
+                "launch_site.altitude",
+                format!(
+                    "must be between {} and {} m, got {}",
+                    Atmosphere::MIN_ALTITUDE,
+                    Atmosphere::MAX_ALTITUDE,
+                    site.altitude
+                ),
+            );
+        }
+
+        self.validate_atmosphere(&mut report);
+        if let Some(water) = &self.water {
+            if !water.surface_height.is_finite() {
+                report.push("water.surface_height", "must be a finite number");
+            }
+            if !(0.0..=MAX_SALINITY).contains(&water.salinity) {
+                report.push(
+                    "water.salinity",
+                    format!("must be between 0 and {MAX_SALINITY}, got {}", water.salinity),
+                );
+            }
+            if !WATER_TEMPERATURES.contains(&water.temperature) {
+                report.push(
+                    "water.temperature",
+                    format!(
+                        "must be between {} and {} K, got {}",
+                        WATER_TEMPERATURES.start(),
+                        WATER_TEMPERATURES.end(),
+                        water.temperature
+                    ),
+                );
+            }
+        }
+
+        if self.vehicles.is_empty() {
+            report.push("vehicles", "the scenario flies nothing");
+        }
+        // Free lift is only worth checking against an atmosphere that exists.
+        let atmosphere = report.is_empty().then(|| self.atmosphere().ok()).flatten();
+        for (index, vehicle) in self.vehicles.iter().enumerate() {
+            let known = report.issues.len();
+            validate_vehicle(&mut report, &format!("vehicles[{index}]"), vehicle);
+            if let Some(atmosphere) = &atmosphere
+                && report.issues.len() == known
+            {
+                self.validate_free_lift(&mut report, index, vehicle, atmosphere);
+            }
+        }
+
+        if let Some(duration) = self.end_conditions.duration
+            && !(duration.is_finite() && duration >= 0.0)
+        {
+            report.push("end_conditions.duration", format!("must not be negative, got {duration}"));
+        }
+        if let Some(name) = &self.end_conditions.vehicle
+            && !self.vehicles.iter().any(|vehicle| vehicle.name == *name)
+        {
+            report.push("end_conditions.vehicle", format!("no vehicle is named {name:?}"));
+        }
+        if let Some(atmosphere) = &atmosphere {
+            self.validate_end_conditions(&mut report, atmosphere);
+        }
+        if let Some(telemetry) = &self.telemetry {
+            if !(telemetry.interval.is_finite() && telemetry.interval >= 0.0) {
+                report.push(
+                    "telemetry.interval",
+                    format!("must not be negative, got {}", telemetry.interval),
+                );
+            }
+            if telemetry.fields.is_empty() {
+                report.push("telemetry.fields", "records nothing");
+            }
+        }
+        report
+    }
+
+    fn validate_atmosphere(&self, report: &mut ValidationReport) {
+        match &self.atmosphere.source {
+            AtmosphereSource::Profile(profile) => {
+                if profile.is_empty() {
+                    report.push("atmosphere.source", "the profile has no levels");
+                }
+                for (index, point) in profile.iter().enumerate() {
+                    let path = format!("atmosphere.source.Profile[{index}]");
+                    if !point.altitude.is_finite() {
+                        report.push(format!("{path}.altitude"), "must be a finite number");
+                    }
+                    report.positive(format!("{path}.temperature"), point.temperature);
+                    report.non_negative(format!("{path}.pressure"), point.pressure);
+                }
+            }
+            AtmosphereSource::Grid(path) => {
+                if !path.is_file() {
+                    report.push("atmosphere.source.Grid", format!("no fluid grid file at {}", path.display()));
+                }
+            }
+            AtmosphereSource::Reference => {}
+        }
+        if let Humidity::RelativeHumidity(levels) = &self.atmosphere.humidity {
+            for (index, level) in levels.iter().enumerate() {
+                if !(0.0..=1.0).contains(&level.value) {
+                    report.push(
+                        format!("atmosphere.humidity.RelativeHumidity[{index}].value"),
+                        format!("must be between 0 and 1, got {}", level.value),
+                    );
+                }
+            }
+        }
+        if let Some(turbulence) = &self.atmosphere.turbulence {
+            report.non_negative("atmosphere.turbulence.wind_speed_at_6m", turbulence.wind_speed_at_6m);
+            report.non_negative(
+                "atmosphere.turbulence.high_altitude_intensity",
+                turbulence.high_altitude_intensity,
+            );
+        }
+    }
+
+    /// A balloon that is heavier than the air it displaces never leaves the
+    /// ground.
+    fn validate_free_lift(
+        &self,
+        report: &mut ValidationReport,
+        index: usize,
+        vehicle: &BalloonConfig,
+        atmosphere: &Atmosphere,
+    ) {
+        let Ok(train) = vehicle.flight_train() else {
+            return;
+        };
+        let position = Vec3::Y * self.launch_site.altitude;
+        let conditions = LaunchConditions::at(atmosphere, &self.planet.body(), position, self.epoch);
+        let path = format!("vehicles[{index}]");
+        match vehicle.gas_mass(&conditions) {
+            Ok(gas_mass) => {
+                let free_lift = train
+                    .with_gas(Mass::new::<kilogram>(gas_mass), &conditions)
+                    .free_lift
+                    .get::<kilogram>();
+                if free_lift.is_finite() && free_lift <= 0.0 {
+                    report.push(
+                        format!("{path}.lift_gas_mass"),
+                        format!("free lift at the launch site is {free_lift:.3} kg, the balloon cannot take off"),
+                    );
+                }
+            }
+            Err(error) => report.push(format!("{path}.free_lift"), error.to_string()),
+        }
+    }
+
+    /// A landing or burst condition must be able to fire for every vehicle
+    /// it watches. Balloons only come down once they burst or vent gas.
+    fn validate_end_conditions(&self, report: &mut ValidationReport, atmosphere: &Atmosphere) {
+        let conditions = &self.end_conditions;
+        if !(conditions.landing || conditions.burst) {
+            return;
+        }
+        let watched = self.vehicles.iter().enumerate().filter(|(_, vehicle)| {
+            conditions.vehicle.as_ref().is_none_or(|name| *name == vehicle.name)
+        });
+        for (index, vehicle) in watched {
+            let Some(bursts) = self.vehicle_bursts(vehicle, atmosphere) else {
+                continue;
+            };
+            let vents = vehicle
+                .controllers
+                .iter()
+                .any(|controller| matches!(controller, ControllerConfig::Valve { .. }));
+            let finishes = (conditions.burst && bursts) || (conditions.landing && (bursts || vents));
+            if finishes {
+                continue;
+            }
+            let (path, message) = if conditions.landing {
+                ("end_conditions.landing", "never bursts or vents gas, so it never comes down")
+            } else {
+                ("end_conditions.burst", "never reaches its burst diameter")
+            };
+            report.push(path, format!("vehicles[{index}] ({:?}) {message}", vehicle.name));
+        }
+    }
+
+    /// Whether a vehicle rises far enough to reach its burst diameter, or
+    /// `None` if that can't be worked out.
+    fn vehicle_bursts(&self, vehicle: &BalloonConfig, atmosphere: &Atmosphere) -> Option<bool> {
+        let Some(burst_diameter) = vehicle.burst_diameter() else {
+            return Some(false);
+        };
+        let launch_altitude = self.launch_site.altitude + vehicle.offset[1];
+        let position = Vec3::Y * launch_altitude;
+        let conditions = LaunchConditions::at(atmosphere, &self.planet.body(), position, self.epoch);
+        let gas = IdealGas::new(
+            GasSpecies::from_species_name(vehicle.lift_gas_species.clone()).ok()?,
+            conditions.temperature,
+            conditions.pressure,
+            Mass::new::<kilogram>(vehicle.gas_mass(&conditions).ok()?),
+        );
+        let burst_altitude = burst_altitude(
+            &gas,
+            Length::new::<meter>(burst_diameter),
+            atmosphere,
+            Length::new::<meter>(launch_altitude),
+            self.epoch,
+        );
+        Some(burst_altitude.is_some())
+    }
+}
+
+fn validate_vehicle(report: &mut ValidationReport, path: &str, vehicle: &BalloonConfig) {
+    if let Err(error) = GasSpecies::from_species_name(vehicle.lift_gas_species.clone()) {
+        report.push(format!("{path}.lift_gas_species"), error.to_string());
+    }
+    match (vehicle.lift_gas_mass, vehicle.free_lift) {
+        (Some(mass), None) => report.positive(format!("{path}.lift_gas_mass"), mass),
+        (None, Some(free_lift)) => report.positive(format!("{path}.free_lift"), free_lift),
+        (Some(_), Some(_)) => report.push(
+            format!("{path}.free_lift"),
+            "give either lift_gas_mass or free_lift, not both",
+        ),
+        (None, None) => report.push(format!("{path}.lift_gas_mass"), "missing, give it or free_lift"),
+    }
+    report.non_negative(format!("{path}.balloon_mass"), vehicle.balloon_mass);
+    report.non_negative(format!("{path}.payload_mass"), vehicle.payload_mass);
+    report.non_negative(format!("{path}.ballast_mass"), vehicle.ballast_mass);
+    report.positive(format!("{path}.drag_coefficient"), vehicle.drag_coefficient);
+    if let Some(damping) = vehicle.rotational_damping {
+        report.non_negative(format!("{path}.rotational_damping"), damping);
+    }
+    if let Some(diameter) = vehicle.burst_diameter {
+        report.positive(format!("{path}.burst_diameter"), diameter);
+    }
+    if !vehicle.offset.iter().all(|value| value.is_finite()) {
+        report.push(format!("{path}.offset"), "must be finite");
+    }
+    for (field, message) in vehicle.material.skin().problems() {
+        report.push(format!("{path}.material.{field}"), message);
+    }
+    for (index, controller) in vehicle.controllers.iter().enumerate() {
+        let path = format!("{path}.controllers[{index}]");
+        let (rate, trigger) = match controller {
+            ControllerConfig::Ballast { rate, trigger } => {
+                if vehicle.ballast_mass <= 0.0 {
+                    report.push(&path, "drops ballast but the vehicle carries none");
+                }
+                (rate, trigger)
+            }
+            ControllerConfig::Valve { rate, trigger } => (rate, trigger),
+        };
+        report.positive(format!("{path}.rate"), *rate);
+        let finite = match *trigger {
+            Trigger::Above(value) | Trigger::Below(value) => value.is_finite(),
+            Trigger::Ascending(value) | Trigger::Descending(value) => value.is_finite() && value >= 0.0,
+            Trigger::After(value) => value.is_finite() && value >= 0.0,
+        };
+        if !finite {
+            report.push(format!("{path}.trigger"), format!("{trigger:?} can never be met"));
+        }
+    }
+}
+
+/// A field path followed by the paths of the fields enclosing it, e.g.
+/// `vehicles[0].rate`, `vehicles[0]` and `vehicles`.
+fn ancestors(path: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(path), |path| {
+        path.rfind(['.', '[']).map(|end| &path[..end])
+    })
+}
+
+/// The line and column of a byte offset into a text.
+fn location(text: &str, offset: usize) -> Location {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Location {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+/// Byte offset of every field, list element and enum variant in a RON text,
+/// by the path validation names it with. Variants appear in paths both with
+/// and without their name, as in `atmosphere.source.Profile[0]` and
+/// `telemetry.interval` for a field inside `Some(...)`. The text is only
+/// scanned, not parsed, so a malformed file yields whatever was found.
+fn field_offsets(text: &str) -> HashMap<String, usize> {
+    // Each step into the text, e.g. `.payload_mass` or `[2]`, and whether it
+    // names an enum variant.
+    let mut segments: Vec<(String, bool)> = Vec::new();
+    // Every open bracket, with the number of segments outside it and, for a
+    // list, the index of the current element.
+    let mut brackets: Vec<(usize, Option<usize>)> = Vec::new();
+    let mut offsets = HashMap::new();
+    let mut record = |segments: &[(String, bool)], offset: usize| {
+        let full: String = segments.iter().map(|(name, _)| name.as_str()).collect();
+        let plain: String = segments
+            .iter()
+            .filter(|(_, variant)| !variant)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        for path in [full, plain] {
+            offsets.entry(path.trim_start_matches('.').to_string()).or_insert(offset);
+        }
+    };
+    // Whether the next value starts a list element.
+    let mut element_pending = false;
+
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        if text[i..].starts_with("//") {
+            i = text[i..].find('\n').map_or(bytes.len(), |end| i + end);
+            continue;
+        }
+        if text[i..].starts_with("/*") {
+            i = text[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2);
+            continue;
+        }
+        if element_pending && c != b']' {
+            record(&segments, i);
+        }
+        element_pending = false;
+        match c {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+            }
+            b'(' | b'{' => brackets.push((segments.len(), None)),
+            b'[' => {
+                brackets.push((segments.len(), Some(0)));
+                segments.push(("[0]".to_string(), false));
+                element_pending = true;
+            }
+            b')' | b'}' | b']' => {
+                if let Some((outside, _)) = brackets.pop() {
+                    segments.truncate(outside);
+                }
+            }
+            b',' => {
+                if let Some((outside, Some(index))) = brackets.last_mut() {
+                    *index += 1;
+                    segments.truncate(*outside);
+                    segments.push((format!("[{index}]"), false));
+                    element_pending = true;
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                let end = text[i..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .map_or(bytes.len(), |end| i + end);
+                let name = format!(".{}", &text[i..end]);
+                let next = text[end..].trim_start();
+                if next.starts_with(':') {
+                    // A field replaces the one before it in the same brackets.
+                    let inside = match brackets.last() {
+                        Some((outside, list)) => outside + usize::from(list.is_some()),
+                        None => 0,
+                    };
+                    segments.truncate(inside);
+                    segments.push((name, false));
+                    record(&segments, i);
+                } else if next.starts_with('(') {
+                    segments.push((name, true));
+                    record(&segments, i);
+                }
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    offsets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"(
+    name: "field: not a field",
+    launch_site: (altitude: "5250 ft"),
+    atmosphere: (source: Profile([
+        (altitude: 0, temperature: 288, pressure: 101325),
+        (altitude: 1000, temperature: 281, pressure: 89000),
+    ])),
+    vehicles: [
+        (payload_mass: "2 lb", controllers: [Ballast(rate: "10 g/s")]),
+    ],
+    // commented: out
+    telemetry: Some((path: "a.csv", interval: "10 s")),
+)"#;
+
+    fn offset_of(needle: &str) -> usize {
+        SCENARIO.find(needle).unwrap()
+    }
+
+    #[test]
+    fn location_counts_lines_and_characters_from_one() {
+        let text = "a\nb°c: 1\n";
+        assert_eq!(location(text, 0), Location { line: 1, column: 1 });
+        assert_eq!(location(text, 2), Location { line: 2, column: 1 });
+        // The degree sign is two bytes but one column.
+        assert_eq!(location(text, text.find('c').unwrap()), Location { line: 2, column: 3 });
+    }
+
+    #[test]
+    fn ancestors_walk_out_to_the_top_field() {
+        let paths: Vec<_> = ancestors("vehicles[0].controllers[1].rate").collect();
+        assert_eq!(
+            paths,
+            [
+                "vehicles[0].controllers[1].rate",
+                "vehicles[0].controllers[1]",
+                "vehicles[0].controllers",
+                "vehicles[0]",
+                "vehicles",
+            ]
+        );
+    }
+
+    #[test]
+    fn field_offsets_follow_nesting_lists_and_variants() {
+        let fields = field_offsets(SCENARIO);
+        assert_eq!(fields["name"], offset_of("name"));
+        assert_eq!(fields["launch_site.altitude"], offset_of("altitude: \"5250"));
+        assert_eq!(fields["atmosphere.source.Profile"], offset_of("Profile"));
+        assert_eq!(fields["atmosphere.source.Profile[0]"], offset_of("(altitude: 0"));
+        assert_eq!(fields["atmosphere.source[1].temperature"], offset_of("temperature: 281"));
+        assert_eq!(fields["atmosphere.source.Profile[1].pressure"], offset_of("pressure: 89000"));
+        assert_eq!(fields["vehicles[0]"], offset_of("(payload_mass"));
+        assert_eq!(fields["vehicles[0].controllers[0].rate"], offset_of("rate"));
+        assert_eq!(fields["vehicles[0].controllers[0].Ballast.rate"], offset_of("rate"));
+        assert_eq!(fields["telemetry.interval"], offset_of("interval"));
+        assert_eq!(fields["telemetry.Some.path"], offset_of("path"));
+    }
+
+    #[test]
+    fn field_offsets_skip_strings_and_comments() {
+        let fields = field_offsets(SCENARIO);
+        assert!(!fields.contains_key("field"));
+        assert!(!fields.contains_key("commented"));
+        assert!(!fields.contains_key("vehicles[1]"));
+    }
+
+    #[test]
+    fn issues_point_at_their_field_or_the_nearest_enclosing_one() {
+        let mut report = ValidationReport::default();
+        report.positive("vehicles[0].payload_mass", -1.0);
+        report.push("vehicles[0].lift_gas_mass", "is missing");
+        report.push("planet", "is unknown");
+        let report = report.with_source("flight.scenario.ron", SCENARIO);
+
+        let locations: Vec<_> = report.issues.iter().map(|issue| issue.location).collect();
+        assert_eq!(
+            locations,
+            [
+                Some(location(SCENARIO, offset_of("payload_mass"))),
+                Some(location(SCENARIO, offset_of("(payload_mass"))),
+                None,
+            ]
+        );
+        assert_eq!(locations[0], Some(Location { line: 9, column: 10 }));
+        let text = report.to_string();
+        assert!(
+            text.contains("\n  flight.scenario.ron:9:10: vehicles[0].payload_mass: must be positive, got -1"),
+            "{text}"
+        );
+        assert!(text.contains("\n  flight.scenario.ron: planet: is unknown"), "{text}");
+    }
+}